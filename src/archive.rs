@@ -0,0 +1,269 @@
+use std::{
+    collections::HashSet,
+    fmt,
+    io::{self, Read, Seek, SeekFrom},
+    sync::Mutex,
+};
+
+use zip::{read::ZipFile, result::ZipError, ZipArchive};
+
+use crate::{
+    error::{FobzError, Result},
+    options::{Limit, ReadLimits},
+};
+
+// Signatures of the records of a ZIP archive.
+const CENTRAL_HEADER_SIGNATURE: u32 = 0x0201_4b50;
+const END_SIGNATURE: u32 = 0x0605_4b50;
+const ZIP64_END_SIGNATURE: u32 = 0x0606_4b50;
+const ZIP64_LOCATOR_SIGNATURE: u32 = 0x0706_4b50;
+
+// Sizes of the fixed parts of the records.
+const CENTRAL_HEADER_SIZE: usize = 46;
+const END_SIZE: usize = 22;
+const ZIP64_END_SIZE: usize = 56;
+const ZIP64_LOCATOR_SIZE: usize = 20;
+
+// Entries up to this uncompressed size are not checked against the compression ratio limit.
+const RATIO_THRESHOLD: u64 = 1 << 20;
+
+/// Any reader that can back a lazily loaded `.fobz` archive.
+pub(crate) trait ReadSeek: Read + Seek + Send {}
+
+impl<T: Read + Seek + Send> ReadSeek for T {}
+
+/// Keeps an opened `.fobz` archive around so its entries can be read on demand.
+pub(crate) struct ArchiveSource {
+    archive: Mutex<ZipArchive<Box<dyn ReadSeek>>>,
+}
+
+impl ArchiveSource {
+    /// Wraps an opened archive.
+    ///
+    /// # Parameters
+    /// - `archive`: The archive whose entries will be read on demand.
+    pub(crate) fn new(archive: ZipArchive<Box<dyn ReadSeek>>) -> Self {
+        ArchiveSource {
+            archive: Mutex::new(archive),
+        }
+    }
+
+    /// Decompresses a single entry of the archive.
+    ///
+    /// # Parameters
+    /// - `name`: The path of the entry inside the archive.
+    ///
+    /// # Returns
+    /// A result containing the raw bytes of the entry, or an error if it could not be read.
+    pub(crate) fn read(&self, name: &str) -> Result<Vec<u8>> {
+        let mut archive = self
+            .archive
+            .lock()
+            .map_err(|_| io::Error::other("archive lock poisoned"))?;
+
+        let mut entry = archive
+            .by_name(name)
+            .map_err(|err| FobzError::entry(name, err))?;
+        read_file(&mut entry)
+    }
+}
+
+impl fmt::Debug for ArchiveSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArchiveSource").finish_non_exhaustive()
+    }
+}
+
+/// Opens a zip archive (a `.fobz` document or an EPUB), refusing it unless its entries are safe
+/// to read.
+///
+/// The names of the entries are read from the central directory before the archive is opened, so
+/// that entries sharing a name cannot hide behind each other. The sizes the archive declares are
+/// then checked against the limits; [`read_file`] makes sure they are not exceeded.
+///
+/// # Parameters
+/// - `reader`: The source of the archive.
+/// - `limits`: The bounds the archive must stay within.
+///
+/// # Returns
+/// A result containing the opened archive, `FobzError::AbsoluteEntryPath`,
+/// `FobzError::EntryPathTraversal`, `FobzError::BackslashInEntryPath`, or
+/// `FobzError::DuplicateEntry` if an entry name is unsafe, `FobzError::LimitExceeded` if the
+/// archive is too large, or another error if it is malformed.
+pub(crate) fn open<R: Read + Seek>(mut reader: R, limits: &ReadLimits) -> Result<ZipArchive<R>> {
+    let mut names = HashSet::new();
+    for name in entry_names(&mut reader, limits.max_entries)? {
+        check_name(&name)?;
+        if let Some(name) = names.replace(name) {
+            return Err(FobzError::DuplicateEntry(name));
+        }
+    }
+
+    let mut archive = ZipArchive::new(reader)?;
+    let mut total: u64 = 0;
+    for i in 0..archive.len() {
+        let file = archive.by_index_raw(i)?;
+        let name = file.name();
+        let size = file.size();
+        check_limit(Limit::EntrySize, Some(name), size, limits.max_entry_size)?;
+        if size > RATIO_THRESHOLD {
+            let ratio = size.div_ceil(file.compressed_size().max(1));
+            check_limit(
+                Limit::CompressionRatio,
+                Some(name),
+                ratio,
+                limits.max_compression_ratio,
+            )?;
+        }
+        total = total.saturating_add(size);
+    }
+    check_limit(Limit::TotalSize, None, total, limits.max_total_size)?;
+
+    Ok(archive)
+}
+
+/// Decompresses an entry of an archive opened with [`open`], never past its declared size.
+///
+/// # Returns
+/// A result containing the bytes of the entry, or an error if it could not be read or turned out
+/// larger than declared.
+pub(crate) fn read_file(file: &mut ZipFile<'_>) -> Result<Vec<u8>> {
+    let size = file.size();
+    let mut bytes = Vec::new();
+    file.take(size.saturating_add(1)).read_to_end(&mut bytes)?;
+
+    if bytes.len() as u64 > size {
+        return Err(invalid("entry larger than its declared size"));
+    }
+    Ok(bytes)
+}
+
+/// Checks that the name of an entry stays inside the archive wherever it is extracted.
+fn check_name(name: &str) -> Result<()> {
+    if name.contains('\\') {
+        return Err(FobzError::BackslashInEntryPath(name.into()));
+    }
+    let bytes = name.as_bytes();
+    if name.starts_with('/') || bytes.get(1) == Some(&b':') && bytes[0].is_ascii_alphabetic() {
+        return Err(FobzError::AbsoluteEntryPath(name.into()));
+    }
+    if name.split('/').any(|v| v == "..") {
+        return Err(FobzError::EntryPathTraversal(name.into()));
+    }
+
+    Ok(())
+}
+
+/// Fails with `FobzError::LimitExceeded` if `value` is over `max`.
+fn check_limit(limit: Limit, entry: Option<&str>, value: u64, max: u64) -> Result<()> {
+    if value <= max {
+        return Ok(());
+    }
+
+    Err(FobzError::LimitExceeded {
+        limit,
+        entry: entry.map(Into::into),
+        value,
+        max,
+    })
+}
+
+/// Lists the names of every entry recorded in the central directory of an archive, duplicates
+/// included.
+///
+/// # Returns
+/// A result containing the names in the order of the central directory,
+/// `FobzError::LimitExceeded` if there are more than `max_entries`, or an error if the central
+/// directory could not be found or read.
+fn entry_names<R: Read + Seek>(reader: &mut R, max_entries: usize) -> Result<Vec<String>> {
+    let len = reader.seek(SeekFrom::End(0))?;
+
+    // The end of central directory record is followed by a comment of at most 64 KiB.
+    let tail_start = len.saturating_sub((END_SIZE + u16::MAX as usize) as u64);
+    let tail = read_at(reader, tail_start, (len - tail_start) as usize)?;
+    let end = (0..(tail.len() + 1).saturating_sub(END_SIZE))
+        .rev()
+        .find(|&i| u32_at(&tail, i) == END_SIGNATURE)
+        .ok_or_else(|| invalid("end of central directory not found"))?;
+    let end_start = tail_start + end as u64;
+
+    let mut count = u64::from(u16_at(&tail, end + 10));
+    let mut size = u64::from(u32_at(&tail, end + 12));
+    // The central directory ends where the records describing it start.
+    let mut directory_end = end_start;
+
+    // A ZIP64 locator right before the record points to a record with the real values.
+    if end_start >= ZIP64_LOCATOR_SIZE as u64 {
+        let locator = read_at(
+            reader,
+            end_start - ZIP64_LOCATOR_SIZE as u64,
+            ZIP64_LOCATOR_SIZE,
+        )?;
+        if u32_at(&locator, 0) == ZIP64_LOCATOR_SIGNATURE {
+            let record_start = u64_at(&locator, 8);
+            if record_start.saturating_add(ZIP64_END_SIZE as u64) > len {
+                return Err(invalid("ZIP64 end of central directory not found"));
+            }
+            let record = read_at(reader, record_start, ZIP64_END_SIZE)?;
+            if u32_at(&record, 0) != ZIP64_END_SIGNATURE {
+                return Err(invalid("ZIP64 end of central directory not found"));
+            }
+            count = u64_at(&record, 32);
+            size = u64_at(&record, 40);
+            directory_end = record_start;
+        }
+    }
+
+    check_limit(Limit::EntryCount, None, count, max_entries as u64)?;
+    let directory_start = directory_end
+        .checked_sub(size)
+        .ok_or_else(|| invalid("central directory out of bounds"))?;
+    let directory = read_at(reader, directory_start, size as usize)?;
+
+    let mut names = Vec::with_capacity(count as usize);
+    let mut i = 0;
+    for _ in 0..count {
+        if i + CENTRAL_HEADER_SIZE > directory.len()
+            || u32_at(&directory, i) != CENTRAL_HEADER_SIGNATURE
+        {
+            return Err(invalid("invalid central directory header"));
+        }
+        let name_len = usize::from(u16_at(&directory, i + 28));
+        let extra_len = usize::from(u16_at(&directory, i + 30));
+        let comment_len = usize::from(u16_at(&directory, i + 32));
+
+        let name_start = i + CENTRAL_HEADER_SIZE;
+        let name = directory
+            .get(name_start..name_start + name_len)
+            .ok_or_else(|| invalid("invalid central directory header"))?;
+        names.push(String::from_utf8_lossy(name).into_owned());
+        i = name_start + name_len + extra_len + comment_len;
+    }
+
+    Ok(names)
+}
+
+/// Reads `len` bytes of `reader` starting at `offset`.
+fn read_at<R: Read + Seek>(reader: &mut R, offset: u64, len: usize) -> Result<Vec<u8>> {
+    reader.seek(SeekFrom::Start(offset))?;
+    let mut bytes = vec![0; len];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Builds the error reported for a malformed archive.
+fn invalid(message: &'static str) -> FobzError {
+    FobzError::Zip(ZipError::InvalidArchive(message))
+}
+
+fn u16_at(bytes: &[u8], i: usize) -> u16 {
+    u16::from_le_bytes([bytes[i], bytes[i + 1]])
+}
+
+fn u32_at(bytes: &[u8], i: usize) -> u32 {
+    u32::from_le_bytes(bytes[i..i + 4].try_into().unwrap())
+}
+
+fn u64_at(bytes: &[u8], i: usize) -> u64 {
+    u64::from_le_bytes(bytes[i..i + 8].try_into().unwrap())
+}
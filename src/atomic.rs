@@ -0,0 +1,79 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::error::Result;
+
+// Distinguishes the temporary files of concurrent saves within the same process.
+static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Writes a file so that `path` either keeps its previous contents or receives the complete new ones.
+///
+/// The data is written to a temporary file next to `path`, flushed to disk, and renamed over
+/// `path`. If writing fails, the temporary file is removed and `path` is left untouched.
+///
+/// # Parameters
+/// - `path`: The file to create or replace.
+/// - `write`: The function writing the new contents into the temporary file.
+///
+/// # Returns
+/// A result indicating success or the first error encountered.
+pub(crate) fn write_atomically<F>(path: &Path, write: F) -> Result<()>
+where
+    F: FnOnce(&mut File) -> Result<()>,
+{
+    let temp = temp_path(path);
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&temp)?;
+
+    // Keep the permissions of the file being replaced.
+    if let Ok(metadata) = fs::metadata(path) {
+        let _ = file.set_permissions(metadata.permissions());
+    }
+
+    let result = write(&mut file)
+        .and_then(|_| Ok(file.sync_all()?))
+        .and_then(|_| {
+            drop(file);
+            Ok(fs::rename(&temp, path)?)
+        });
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+        return result;
+    }
+
+    // Persist the rename itself; not every platform allows opening a directory.
+    if let Some(parent) = path.parent() {
+        let parent = if parent.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            parent
+        };
+        if let Ok(dir) = File::open(parent) {
+            let _ = dir.sync_all();
+        }
+    }
+
+    Ok(())
+}
+
+/// Builds the path of a hidden temporary file in the same directory as `path`.
+fn temp_path(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|v| v.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    path.with_file_name(format!(
+        ".{}.{}-{}.tmp",
+        name,
+        process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ))
+}
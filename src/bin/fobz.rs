@@ -0,0 +1,873 @@
+//! # `fobz`
+//!
+//! A command-line tool to inspect and edit `.fobz` documents.
+//!
+//! ```plaintext
+//! fobz [--json] <command> [arguments]
+//! ```
+//!
+//! Reading commands print text, or JSON with `--json`. Editing commands save the document in
+//! place and print nothing. Errors are printed to the standard error, as JSON with `--json`.
+//!
+//! ## Exit Codes
+//!
+//! - `0`: The command succeeded.
+//! - `1`: `validate` found errors in the document, or `links` found broken references.
+//! - `2`: The command line is invalid.
+//! - `3`: The requested entry does not exist.
+//! - `4`: The document or the operation is invalid (e.g., a malformed archive or a bad path).
+//! - `5`: A file could not be read or written.
+
+use std::{
+    collections::HashMap,
+    env,
+    fs::{self, File},
+    io::{self, Read, Write},
+    path::Path,
+    process::ExitCode,
+};
+
+use fobzip::{
+    error::FobzError,
+    import::{ImportIssueKind, ImportReport},
+    links::LinkProblem,
+    manifest::{Contributor, Identifier, Role, Series},
+    options::TextFormat,
+    sanitize::{RemovalKind, SanitizePolicy},
+    validate::{IssueKind, Severity},
+    FobZ,
+};
+use serde_json::{json, Value};
+
+const USAGE: &str = "\
+Usage: fobz [--json] <command> [arguments]
+
+Commands:
+  info <file>                               Show the manifest and the tables
+  ls <file>                                 List the contents, resources, and styles
+  cat <file> <path>                         Write an entry to the standard output
+  add-content <file> <path> <source>        Add a content [--title <title>] [--parent <path>]
+  add-resource <file> <path> <source>       Add a resource [--name <name>] [--type <media type>]
+  add-style <file> <path> <source>          Add a stylesheet
+  rm <file> <path>                          Remove a content, resource, or stylesheet
+  mv <file> <path> <new path>               Rename an entry, updating the references to it
+  set-meta <file> <key> [value]...          Set a manifest field, cleared when no value is given
+  validate <file>                           Check the consistency of the document
+  links <file>                              Check the references and list external links
+  sanitize <file>                           Remove unsafe HTML from the contents [--policy <policy>]
+  convert <input> <output>                  Convert between formats [--to <format>]
+
+Options:
+  --json      Print JSON instead of text
+  --replace   Let add-* commands replace an existing entry
+  -h, --help  Print this help
+
+A <source> of '-' is read from the standard input.
+
+set-meta keys: title, author, description, tags, index, cover, language, publisher,
+published, modified, rights, series (name and position), isbn, doi, uuid (added),
+editor, translator, illustrator (added), and extra.<key>.
+
+convert reads .fobz, .epub, and Markdown (.md file or directory), and writes .fobz, .epub,
+.txt, .md, or, with --to site, a static website in a directory. --to also accepts fobz, epub,
+txt, md, txt-sections, and md-sections.
+
+sanitize policies: strict, standard (default), and permissive.";
+
+/// Represents a failed command, deciding the exit code.
+///
+/// # Variants
+/// - `Usage`: The command line is invalid.
+/// - `Fobz`: The library refused the operation or could not read or write a file.
+enum Failure {
+    Usage(String),
+    Fobz(FobzError),
+}
+
+impl From<FobzError> for Failure {
+    fn from(err: FobzError) -> Self {
+        Failure::Fobz(err)
+    }
+}
+
+impl From<io::Error> for Failure {
+    fn from(err: io::Error) -> Self {
+        Failure::Fobz(err.into())
+    }
+}
+
+impl Failure {
+    fn exit_code(&self) -> u8 {
+        match self {
+            Failure::Usage(_) => 2,
+            Failure::Fobz(FobzError::UnknownPath(_) | FobzError::MissingEntry(_)) => 3,
+            Failure::Fobz(FobzError::Io(_)) => 5,
+            Failure::Fobz(_) => 4,
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            Failure::Usage(message) => message.clone(),
+            Failure::Fobz(err) => err.to_string(),
+        }
+    }
+}
+
+type Outcome = Result<ExitCode, Failure>;
+
+/// Represents the parsed command line.
+///
+/// # Fields
+/// - `positionals`: The command and its arguments, in order.
+/// - `options`: The options taking a value (e.g., `--title`), by name without the dashes.
+/// - `json`: Whether to print JSON.
+/// - `replace`: Whether `add-*` commands replace existing entries.
+struct Args {
+    positionals: Vec<String>,
+    options: HashMap<String, String>,
+    json: bool,
+    replace: bool,
+}
+
+impl Args {
+    /// Parses the arguments following the program name.
+    ///
+    /// # Returns
+    /// The parsed arguments, `None` if help was requested, or `Failure::Usage` if an option is
+    /// unknown or lacks its value.
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Option<Self>, Failure> {
+        let mut parsed = Args {
+            positionals: vec![],
+            options: HashMap::new(),
+            json: false,
+            replace: false,
+        };
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "--json" => parsed.json = true,
+                "--replace" => parsed.replace = true,
+                "--" => parsed.positionals.extend(args.by_ref()),
+                "--title" | "--parent" | "--name" | "--type" | "--to" | "--policy" => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| Failure::Usage(format!("{} requires a value", arg)))?;
+                    parsed.options.insert(arg[2..].into(), value);
+                }
+                _ if arg.starts_with("--") => {
+                    return Err(Failure::Usage(format!("unknown option '{}'", arg)));
+                }
+                _ => parsed.positionals.push(arg),
+            }
+        }
+
+        Ok(Some(parsed))
+    }
+
+    /// Retrieves the positional arguments of a command, checking their number.
+    ///
+    /// # Parameters
+    /// - `min`: The number of required arguments.
+    /// - `max`: The largest number of arguments, `None` for no limit.
+    fn arguments(&self, min: usize, max: Option<usize>) -> Result<&[String], Failure> {
+        let arguments = &self.positionals[1..];
+        if arguments.len() < min || max.is_some_and(|v| arguments.len() > v) {
+            return Err(Failure::Usage(format!(
+                "wrong number of arguments for '{}'",
+                self.positionals[0]
+            )));
+        }
+        Ok(arguments)
+    }
+
+    /// Checks that only the given options were passed to the current command.
+    fn allow_options(&self, allowed: &[&str]) -> Result<(), Failure> {
+        match self.options.keys().find(|v| !allowed.contains(&v.as_str())) {
+            Some(option) => Err(Failure::Usage(format!(
+                "option '--{}' does not apply to '{}'",
+                option, self.positionals[0]
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let args = match Args::parse(env::args().skip(1)) {
+        Ok(Some(args)) if !args.positionals.is_empty() => args,
+        Ok(Some(args)) => return report(&Failure::Usage("missing command".into()), args.json),
+        Ok(None) => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(failure) => return report(&failure, env::args().any(|v| v == "--json")),
+    };
+
+    match run(&args) {
+        Ok(code) => code,
+        Err(failure) => report(&failure, args.json),
+    }
+}
+
+/// Prints a failure to the standard error and returns its exit code.
+fn report(failure: &Failure, json: bool) -> ExitCode {
+    if json {
+        let error = json!({ "error": failure.message(), "code": failure.exit_code() });
+        eprintln!("{}", error);
+    } else {
+        eprintln!("fobz: {}", failure.message());
+        if matches!(failure, Failure::Usage(_)) {
+            eprintln!("Try 'fobz --help' for more information.");
+        }
+    }
+    ExitCode::from(failure.exit_code())
+}
+
+/// Runs the command of the command line.
+fn run(args: &Args) -> Outcome {
+    let allowed: &[&str] = match args.positionals[0].as_str() {
+        "add-content" => &["title", "parent"],
+        "add-resource" => &["name", "type"],
+        "convert" => &["to"],
+        "sanitize" => &["policy"],
+        _ => &[],
+    };
+    args.allow_options(allowed)?;
+
+    match args.positionals[0].as_str() {
+        "info" => info(args),
+        "ls" => ls(args),
+        "cat" => cat(args),
+        "add-content" => add_content(args),
+        "add-resource" => add_resource(args),
+        "add-style" => add_style(args),
+        "rm" => rm(args),
+        "mv" => mv(args),
+        "set-meta" => set_meta(args),
+        "validate" => validate(args),
+        "links" => links(args),
+        "sanitize" => sanitize(args),
+        "convert" => convert(args),
+        command => Err(Failure::Usage(format!("unknown command '{}'", command))),
+    }
+}
+
+/// Prints the manifest and the tables of a document.
+fn info(args: &Args) -> Outcome {
+    let file = &args.arguments(1, Some(1))?[0];
+    let fobz = FobZ::open(file)?;
+    let manifest = fobz.get_manifest();
+
+    if args.json {
+        print_json(&json!({
+            "manifest": manifest,
+            "toc": fobz.get_toc(),
+            "tor": fobz.get_tor(),
+            "tos": fobz.get_tos(),
+        }));
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    println!("Version: {}", manifest.get_version());
+    println!("Title: {}", manifest.get_title());
+    println!("Author: {}", manifest.get_author());
+    println!("Description: {}", manifest.get_description());
+    println!("Tags: {}", manifest.get_tags().join(", "));
+    println!("Index: {}", manifest.get_index());
+    println!("Cover: {}", manifest.get_cover());
+    for contributor in manifest.get_contributors() {
+        println!(
+            "Contributor: {} ({})",
+            contributor.name,
+            role_name(contributor.role)
+        );
+    }
+    let optional = [
+        ("Language", manifest.get_language()),
+        ("Publisher", manifest.get_publisher()),
+        ("Published", manifest.get_published()),
+        ("Modified", manifest.get_modified()),
+        ("Rights", manifest.get_rights()),
+    ];
+    for (label, value) in optional {
+        if let Some(value) = value {
+            println!("{}: {}", label, value);
+        }
+    }
+    for identifier in manifest.get_identifiers() {
+        println!("Identifier: {}", identifier_value(identifier));
+    }
+    if let Some(series) = manifest.get_series() {
+        println!("Series: {} #{}", series.name, series.position);
+    }
+    for (key, value) in manifest.get_extra() {
+        println!("Extra: {} = {}", key, value);
+    }
+
+    println!("\nContents:");
+    for info in fobz.get_toc().depth_first() {
+        let (depth, info) = info;
+        println!("  {}{} ({})", "  ".repeat(depth), info.title, info.href());
+    }
+    println!("\nResources:");
+    for info in fobz.get_tor().iter() {
+        println!("  {} ({}, {})", info.path, info.name, info.media_type);
+    }
+    println!("\nStyles:");
+    for info in fobz.get_tos().iter() {
+        println!("  {}", info.path);
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Lists the entries of a document with their kind and size.
+fn ls(args: &Args) -> Outcome {
+    let file = &args.arguments(1, Some(1))?[0];
+    let fobz = FobZ::open(file)?;
+
+    let mut entries: Vec<(&str, &str, usize)> = vec![];
+    for info in fobz.get_toc().iter() {
+        if entries.iter().any(|(_, path, _)| *path == info.path) {
+            continue;
+        }
+        if let Some((_, content)) = fobz.get_content(&info.path) {
+            entries.push(("content", &info.path, content.len()));
+        }
+    }
+    for info in fobz.get_tor().iter() {
+        if let Some((_, resource)) = fobz.get_resource(&info.path) {
+            entries.push(("resource", &info.path, resource.len()));
+        }
+    }
+    for info in fobz.get_tos().iter() {
+        if let Some((_, style)) = fobz.get_style(&info.path) {
+            entries.push(("style", &info.path, style.len()));
+        }
+    }
+
+    if args.json {
+        let entries: Vec<Value> = entries
+            .iter()
+            .map(|(kind, path, size)| json!({ "kind": kind, "path": path, "size": size }))
+            .collect();
+        print_json(&Value::Array(entries));
+    } else {
+        for (kind, path, size) in entries {
+            println!("{:<8} {:>10}  {}", kind, size, path);
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Writes a content, resource, or stylesheet to the standard output, as stored.
+fn cat(args: &Args) -> Outcome {
+    let arguments = args.arguments(2, Some(2))?;
+    let fobz = FobZ::open(&arguments[0])?;
+    let path = &arguments[1];
+
+    let bytes = if let Some((_, content)) = fobz.get_content(path) {
+        content.as_bytes()
+    } else if let Some((_, resource)) = fobz.get_resource(path) {
+        resource.as_slice()
+    } else if let Some((_, style)) = fobz.get_style(path) {
+        style.as_bytes()
+    } else {
+        return Err(FobzError::UnknownPath(path.clone()).into());
+    };
+
+    let mut stdout = io::stdout().lock();
+    stdout.write_all(bytes)?;
+    stdout.flush()?;
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Adds a content from a file, as a top-level section or as the child of another section.
+fn add_content(args: &Args) -> Outcome {
+    let arguments = args.arguments(3, Some(3))?;
+    let mut fobz = FobZ::open(&arguments[0])?;
+    let path = arguments[1].clone();
+    let content = String::from_utf8(read_source(&arguments[2])?)
+        .map_err(|_| FobzError::InvalidUtf8(arguments[2].clone()))?;
+    let title = args
+        .options
+        .get("title")
+        .cloned()
+        .unwrap_or_else(|| file_stem(&path));
+
+    match args.options.get("parent") {
+        Some(_) if args.replace => {
+            return Err(Failure::Usage(
+                "--replace cannot be used with --parent".into(),
+            ));
+        }
+        Some(parent) => fobz.add_child_content(parent, path, title, content)?,
+        None if args.replace => fobz.upsert_content(path, title, content)?,
+        None => fobz.add_content(path, title, content)?,
+    }
+
+    fobz.overwrite(&arguments[0])?;
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Adds a resource from a file, detecting its media type unless one is given.
+fn add_resource(args: &Args) -> Outcome {
+    let arguments = args.arguments(3, Some(3))?;
+    let mut fobz = FobZ::open(&arguments[0])?;
+    let path = arguments[1].clone();
+    let resource = read_source(&arguments[2])?;
+    let name = args
+        .options
+        .get("name")
+        .cloned()
+        .unwrap_or_else(|| file_stem(&path));
+
+    match (args.options.get("type").cloned(), args.replace) {
+        (Some(media_type), true) => {
+            fobz.upsert_resource_with_type(path, name, media_type, resource)?
+        }
+        (Some(media_type), false) => {
+            fobz.add_resource_with_type(path, name, media_type, resource)?
+        }
+        (None, true) => fobz.upsert_resource(path, name, resource)?,
+        (None, false) => fobz.add_resource(path, name, resource)?,
+    }
+
+    fobz.overwrite(&arguments[0])?;
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Adds a stylesheet from a file.
+fn add_style(args: &Args) -> Outcome {
+    let arguments = args.arguments(3, Some(3))?;
+    let mut fobz = FobZ::open(&arguments[0])?;
+    let path = arguments[1].clone();
+    let style = String::from_utf8(read_source(&arguments[2])?)
+        .map_err(|_| FobzError::InvalidUtf8(arguments[2].clone()))?;
+
+    if args.replace {
+        fobz.upsert_style(path, style)?;
+    } else {
+        fobz.add_style(path, style)?;
+    }
+
+    fobz.overwrite(&arguments[0])?;
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Removes a content, resource, or stylesheet.
+fn rm(args: &Args) -> Outcome {
+    let arguments = args.arguments(2, Some(2))?;
+    let mut fobz = FobZ::open(&arguments[0])?;
+    let path = arguments[1].clone();
+
+    if fobz.get_content_info(&path).is_some() {
+        fobz.remove_content(path);
+    } else if fobz.get_resource_info(&path).is_some() {
+        fobz.remove_resource(path);
+    } else if fobz.get_style_info(&path).is_some() {
+        fobz.remove_style(path);
+    } else {
+        return Err(FobzError::UnknownPath(path).into());
+    }
+
+    fobz.overwrite(&arguments[0])?;
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Renames a content, resource, or stylesheet, updating the references to it.
+fn mv(args: &Args) -> Outcome {
+    let arguments = args.arguments(3, Some(3))?;
+    let mut fobz = FobZ::open(&arguments[0])?;
+
+    fobz.rename(&arguments[1], arguments[2].clone())?;
+    fobz.overwrite(&arguments[0])?;
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Sets, clears, or adds to a field of the manifest.
+fn set_meta(args: &Args) -> Outcome {
+    let arguments = args.arguments(2, None)?;
+    let mut fobz = FobZ::open(&arguments[0])?;
+    let key = arguments[1].as_str();
+    let values = &arguments[2..];
+    let value = values.join(" ");
+    let optional = (!value.is_empty()).then(|| value.clone());
+    let manifest = fobz.get_manifest_mut();
+
+    let single = |values: &[String]| match values.len() {
+        0 | 1 => Ok(()),
+        _ => Err(Failure::Usage(format!("'{}' takes a single value", key))),
+    };
+
+    match key {
+        "title" => manifest.set_title(value),
+        "author" => manifest.set_author(value),
+        "description" => manifest.set_description(value),
+        "tags" => {
+            let tags = manifest.get_tags().clone();
+            manifest.remove_tags(tags);
+            manifest.add_tags(values.to_vec());
+        }
+        "index" => {
+            single(values)?;
+            manifest.set_index(value);
+        }
+        "cover" => {
+            single(values)?;
+            manifest.set_cover(value);
+        }
+        "language" => {
+            single(values)?;
+            manifest.set_language(optional);
+        }
+        "publisher" => manifest.set_publisher(optional),
+        "published" => {
+            single(values)?;
+            manifest.set_published(optional);
+        }
+        "modified" => {
+            single(values)?;
+            manifest.set_modified(optional);
+        }
+        "rights" => manifest.set_rights(optional),
+        "series" => {
+            let series = match values {
+                [] => None,
+                [name] => Some(Series::new(name.clone(), 1)),
+                [name, position] => {
+                    let position = position.parse().map_err(|_| {
+                        Failure::Usage(format!("invalid series position '{}'", position))
+                    })?;
+                    Some(Series::new(name.clone(), position))
+                }
+                _ => {
+                    return Err(Failure::Usage(
+                        "'series' takes a name and a position".into(),
+                    ))
+                }
+            };
+            manifest.set_series(series);
+        }
+        "isbn" | "doi" | "uuid" => {
+            single(values)?;
+            let identifier = match key {
+                "isbn" => Identifier::Isbn(value),
+                "doi" => Identifier::Doi(value),
+                _ => Identifier::Uuid(value),
+            };
+            if identifier.value().is_empty() {
+                return Err(Failure::Usage(format!("'{}' requires a value", key)));
+            }
+            manifest.add_identifier(identifier);
+        }
+        "editor" | "translator" | "illustrator" => {
+            let role = match key {
+                "editor" => Role::Editor,
+                "translator" => Role::Translator,
+                _ => Role::Illustrator,
+            };
+            if value.is_empty() {
+                return Err(Failure::Usage(format!("'{}' requires a value", key)));
+            }
+            manifest.add_contributor(Contributor::new(value, role));
+        }
+        _ => match key.strip_prefix("extra.") {
+            Some(extra) if !extra.is_empty() => match optional {
+                Some(value) => {
+                    manifest.set_extra(extra.into(), value);
+                }
+                None => {
+                    manifest.remove_extra(extra);
+                }
+            },
+            _ => return Err(Failure::Usage(format!("unknown metadata key '{}'", key))),
+        },
+    }
+
+    fobz.overwrite(&arguments[0])?;
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Checks the consistency of a document, exiting with `1` if it has errors.
+fn validate(args: &Args) -> Outcome {
+    let file = &args.arguments(1, Some(1))?[0];
+    let report = FobZ::validate_file(file)?;
+
+    if args.json {
+        let issues: Vec<Value> = report
+            .issues()
+            .iter()
+            .map(|v| {
+                json!({
+                    "severity": match v.severity {
+                        Severity::Error => "error",
+                        Severity::Warning => "warning",
+                    },
+                    "kind": issue_kind_name(v.kind),
+                    "path": v.path,
+                    "message": v.message,
+                })
+            })
+            .collect();
+        print_json(&json!({ "valid": report.is_valid(), "issues": issues }));
+    } else {
+        for issue in report.issues() {
+            println!("{}", issue);
+        }
+        if report.is_valid() {
+            println!("{} is valid", file);
+        }
+    }
+
+    Ok(if report.is_valid() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(1)
+    })
+}
+
+/// Reports the broken references of a document and lists its external links.
+fn links(args: &Args) -> Outcome {
+    let file = &args.arguments(1, Some(1))?[0];
+    let report = FobZ::open(file)?.check_links()?;
+
+    if args.json {
+        let broken: Vec<Value> = report
+            .broken()
+            .iter()
+            .map(|v| {
+                json!({
+                    "source": v.source,
+                    "line": v.line,
+                    "reference": v.reference,
+                    "target": v.target,
+                    "problem": link_problem_name(v.problem),
+                })
+            })
+            .collect();
+        let external: Vec<Value> = report
+            .external()
+            .iter()
+            .map(|v| json!({ "source": v.source, "line": v.line, "url": v.url }))
+            .collect();
+        print_json(&json!({ "ok": report.is_ok(), "broken": broken, "external": external }));
+    } else {
+        for link in report.broken() {
+            println!("{}", link);
+        }
+        if !report.external().is_empty() {
+            println!("External links:");
+            for link in report.external() {
+                println!("  {}:{}: {}", link.source, link.line, link.url);
+            }
+        }
+    }
+
+    Ok(if report.is_ok() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(1)
+    })
+}
+
+/// Sanitizes the contents of a document in place and lists what was removed.
+fn sanitize(args: &Args) -> Outcome {
+    let file = &args.arguments(1, Some(1))?[0];
+    let policy = match args.options.get("policy").map(String::as_str) {
+        Some("strict") => SanitizePolicy::Strict,
+        None | Some("standard") => SanitizePolicy::Standard,
+        Some("permissive") => SanitizePolicy::Permissive,
+        Some(policy) => {
+            return Err(Failure::Usage(format!(
+                "unknown sanitization policy '{}'",
+                policy
+            )))
+        }
+    };
+
+    let mut fobz = FobZ::open(file)?;
+    let report = fobz.sanitize(&policy)?;
+    if !report.is_clean() {
+        fobz.overwrite(file)?;
+    }
+
+    if args.json {
+        let removals: Vec<Value> = report
+            .removals()
+            .iter()
+            .map(|v| {
+                json!({
+                    "kind": removal_kind_name(v.kind),
+                    "path": v.path,
+                    "element": v.element,
+                    "attribute": v.attribute,
+                })
+            })
+            .collect();
+        print_json(&json!({ "removals": removals }));
+    } else {
+        for removal in report.removals() {
+            println!("{}", removal);
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Converts a document between formats, chosen from the file extensions unless `--to` is given.
+fn convert(args: &Args) -> Outcome {
+    let arguments = args.arguments(2, Some(2))?;
+    let (input, output) = (&arguments[0], &arguments[1]);
+
+    let to = match args.options.get("to") {
+        Some(to) => to.as_str(),
+        None => match extension(output).as_deref() {
+            Some("fobz") => "fobz",
+            Some("epub") => "epub",
+            Some("txt") => "txt",
+            Some("md" | "markdown") => "md",
+            _ => {
+                return Err(Failure::Usage(format!(
+                    "cannot tell the format of '{}', use --to",
+                    output
+                )))
+            }
+        },
+    };
+
+    let (fobz, report) = if Path::new(input).is_dir() {
+        FobZ::import_markdown_dir(input)?
+    } else {
+        match extension(input).as_deref() {
+            Some("epub") => FobZ::import_epub(File::open(input)?)?,
+            Some("md" | "markdown") => FobZ::import_markdown(std::slice::from_ref(input))?,
+            _ => (FobZ::open(input)?, ImportReport::default()),
+        }
+    };
+
+    match to {
+        "fobz" => fobz.save_to(output)?,
+        "epub" => {
+            let mut file = fobz.export_epub(File::create(output)?)?;
+            file.flush()?;
+        }
+        "txt" => fobz.export_text(output, TextFormat::Plain)?,
+        "md" => fobz.export_text(output, TextFormat::Markdown)?,
+        "txt-sections" => fobz.export_text_sections(output, TextFormat::Plain)?,
+        "md-sections" => fobz.export_text_sections(output, TextFormat::Markdown)?,
+        "site" => fobz.export_site(output)?,
+        to => return Err(Failure::Usage(format!("unknown format '{}'", to))),
+    }
+
+    if args.json {
+        let issues: Vec<Value> = report
+            .issues()
+            .iter()
+            .map(|v| {
+                json!({
+                    "kind": import_kind_name(v.kind),
+                    "source": v.source,
+                    "message": v.message,
+                })
+            })
+            .collect();
+        print_json(&json!({ "issues": issues }));
+    } else {
+        for issue in report.issues() {
+            eprintln!("warning: {}", issue);
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Reads the bytes of a source file, or of the standard input for `-`.
+fn read_source(source: &str) -> Result<Vec<u8>, Failure> {
+    if source == "-" {
+        let mut bytes = vec![];
+        io::stdin().read_to_end(&mut bytes)?;
+        Ok(bytes)
+    } else {
+        Ok(fs::read(source)?)
+    }
+}
+
+fn print_json(value: &Value) {
+    // Serializing a `Value` cannot fail.
+    println!(
+        "{}",
+        serde_json::to_string_pretty(value).unwrap_or_default()
+    );
+}
+
+/// Extracts the lowercase extension of a path.
+fn extension(path: &str) -> Option<String> {
+    Path::new(path)
+        .extension()
+        .map(|v| v.to_string_lossy().to_ascii_lowercase())
+}
+
+/// Extracts the file name of a path, without its extension.
+fn file_stem(path: &str) -> String {
+    Path::new(path)
+        .file_stem()
+        .map_or_else(|| path.into(), |v| v.to_string_lossy().into())
+}
+
+fn role_name(role: Role) -> &'static str {
+    match role {
+        Role::Author => "author",
+        Role::Editor => "editor",
+        Role::Translator => "translator",
+        Role::Illustrator => "illustrator",
+    }
+}
+
+fn identifier_value(identifier: &Identifier) -> String {
+    match identifier {
+        Identifier::Isbn(value) => format!("isbn:{}", value),
+        Identifier::Doi(value) => format!("doi:{}", value),
+        Identifier::Uuid(value) => format!("urn:uuid:{}", value),
+    }
+}
+
+fn issue_kind_name(kind: IssueKind) -> &'static str {
+    match kind {
+        IssueKind::DanglingEntry => "dangling_entry",
+        IssueKind::OrphanFile => "orphan_file",
+        IssueKind::DuplicatePath => "duplicate_path",
+        IssueKind::UnresolvedIndex => "unresolved_index",
+        IssueKind::UnresolvedCover => "unresolved_cover",
+    }
+}
+
+fn link_problem_name(problem: LinkProblem) -> &'static str {
+    match problem {
+        LinkProblem::MissingTarget => "missing_target",
+        LinkProblem::MissingFragment => "missing_fragment",
+        LinkProblem::OutsideArchive => "outside_archive",
+    }
+}
+
+fn removal_kind_name(kind: RemovalKind) -> &'static str {
+    match kind {
+        RemovalKind::Element => "element",
+        RemovalKind::Content => "content",
+        RemovalKind::Attribute => "attribute",
+        RemovalKind::Url => "url",
+        RemovalKind::Comment => "comment",
+    }
+}
+
+fn import_kind_name(kind: ImportIssueKind) -> &'static str {
+    match kind {
+        ImportIssueKind::UnmappedItem => "unmapped_item",
+        ImportIssueKind::MissingItem => "missing_item",
+        ImportIssueKind::UnmappedMetadata => "unmapped_metadata",
+        ImportIssueKind::UnresolvedLink => "unresolved_link",
+    }
+}
@@ -0,0 +1,230 @@
+use zip::{
+    write::{ExtendedFileOptions, FileOptions},
+    CompressionMethod,
+};
+
+use crate::error::{FobzError, Result};
+
+/// The compression method and level applied to an entry of the archive.
+///
+/// # Variants
+/// - `Stored`: The entry is stored as is.
+/// - `Deflated`: The entry is compressed with Deflate, at the given level (0-9) or the default one.
+/// - `Bzip2`: The entry is compressed with bzip2, at the given level (1-9) or the default one.
+/// - `Zstd`: The entry is compressed with Zstandard, at the given level (1-22) or the default one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Stored,
+    Deflated(Option<i64>),
+    Bzip2(Option<i64>),
+    Zstd(Option<i64>),
+}
+
+impl Compression {
+    /// Applies the method and level to a set of zip file options.
+    pub(crate) fn apply<'k>(
+        self,
+        options: FileOptions<'k, ExtendedFileOptions>,
+    ) -> FileOptions<'k, ExtendedFileOptions> {
+        let (method, level) = match self {
+            Compression::Stored => (CompressionMethod::Stored, None),
+            Compression::Deflated(level) => (CompressionMethod::Deflated, level),
+            Compression::Bzip2(level) => (CompressionMethod::Bzip2, level),
+            Compression::Zstd(level) => (CompressionMethod::Zstd, level),
+        };
+
+        options.compression_method(method).compression_level(level)
+    }
+
+    /// Checks that the level is within the range of the method.
+    ///
+    /// # Returns
+    /// A result indicating success, or `FobzError::InvalidCompressionLevel` if the level is out
+    /// of range.
+    pub(crate) fn check(self) -> Result<()> {
+        let (level, min, max) = match self {
+            Compression::Stored => return Ok(()),
+            Compression::Deflated(level) => (level, 0, 9),
+            Compression::Bzip2(level) => (level, 1, 9),
+            Compression::Zstd(level) => (level, 1, 22),
+        };
+
+        match level {
+            Some(level) if !(min..=max).contains(&level) => {
+                Err(FobzError::InvalidCompressionLevel {
+                    compression: self,
+                    min,
+                    max,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The kind of an entry of the archive.
+///
+/// # Variants
+/// - `Metadata`: `manifest.json`, the tables, and the stored search index.
+/// - `Content`: A section in `contents/`.
+/// - `Resource`: A resource in `resources/`.
+/// - `Style`: A stylesheet in `styles/`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    Metadata,
+    Content,
+    Resource,
+    Style,
+}
+
+/// Selects the entries a compression rule applies to.
+///
+/// # Variants
+/// - `Kind`: Every entry of the given kind.
+/// - `MediaType`: Entries of the given media type; a value ending with `/` (e.g., `image/`) matches
+///   the whole family.
+/// - `Pattern`: Entries whose path matches a glob, where `*` matches within a directory and `**`
+///   matches across directories (e.g., `contents/**.html`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EntryMatcher {
+    Kind(EntryKind),
+    MediaType(String),
+    Pattern(String),
+}
+
+impl EntryMatcher {
+    /// Checks whether an entry is selected by the matcher.
+    ///
+    /// # Parameters
+    /// - `path`: The path of the entry inside the archive.
+    /// - `kind`: The kind of the entry.
+    /// - `media_type`: The media type of the entry.
+    ///
+    /// # Returns
+    /// `true` if the entry is selected, otherwise `false`.
+    pub fn matches(&self, path: &str, kind: EntryKind, media_type: &str) -> bool {
+        match self {
+            EntryMatcher::Kind(v) => *v == kind,
+            EntryMatcher::MediaType(v) if v.ends_with('/') => media_type.starts_with(v.as_str()),
+            EntryMatcher::MediaType(v) => v == media_type,
+            EntryMatcher::Pattern(v) => glob(v.as_bytes(), path.as_bytes()),
+        }
+    }
+}
+
+/// Decides how each entry of the archive is compressed when saving.
+///
+/// When several rules match an entry, the one added last wins, so rules added to the default
+/// policy override its built-in ones. Entries matched by no rule use the fallback compression.
+///
+/// # Fields
+/// - `rules`: The rules, each pairing a matcher with a compression.
+/// - `fallback`: The compression used when no rule matches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompressionPolicy {
+    rules: Vec<(EntryMatcher, Compression)>,
+    fallback: Compression,
+}
+
+impl Default for CompressionPolicy {
+    /// Creates the default `CompressionPolicy`.
+    ///
+    /// Formats that are already compressed (images, fonts, audio, and video) are stored, and
+    /// every other entry is deflated at the default level.
+    fn default() -> Self {
+        let mut policy = CompressionPolicy::new(Compression::Deflated(None));
+
+        for media_type in [
+            "image/jpeg",
+            "image/png",
+            "image/gif",
+            "image/webp",
+            "image/avif",
+            "font/woff",
+            "font/woff2",
+            "audio/",
+            "video/",
+        ] {
+            policy.add_rule(
+                EntryMatcher::MediaType(media_type.into()),
+                Compression::Stored,
+            );
+        }
+
+        policy
+    }
+}
+
+impl CompressionPolicy {
+    /// Creates a new `CompressionPolicy` without rules.
+    ///
+    /// # Parameters
+    /// - `fallback`: The compression used for every entry until rules are added.
+    pub fn new(fallback: Compression) -> Self {
+        CompressionPolicy {
+            rules: vec![],
+            fallback,
+        }
+    }
+
+    /// Adds a rule, taking precedence over the existing ones.
+    ///
+    /// # Parameters
+    /// - `matcher`: The entries the rule applies to.
+    /// - `compression`: The compression used for those entries.
+    pub fn add_rule(&mut self, matcher: EntryMatcher, compression: Compression) {
+        self.rules.push((matcher, compression));
+    }
+
+    /// Sets the compression used when no rule matches.
+    ///
+    /// # Parameters
+    /// - `compression`: The new fallback compression.
+    pub fn set_fallback(&mut self, compression: Compression) {
+        self.fallback = compression;
+    }
+
+    /// Picks the compression of an entry.
+    ///
+    /// # Parameters
+    /// - `path`: The path of the entry inside the archive.
+    /// - `kind`: The kind of the entry.
+    /// - `media_type`: The media type of the entry.
+    ///
+    /// # Returns
+    /// The compression of the last matching rule, or the fallback one.
+    pub fn compression_for(&self, path: &str, kind: EntryKind, media_type: &str) -> Compression {
+        self.rules
+            .iter()
+            .rev()
+            .find(|(matcher, _)| matcher.matches(path, kind, media_type))
+            .map(|(_, compression)| *compression)
+            .unwrap_or(self.fallback)
+    }
+
+    /// Checks the level of every compression of the policy, used or not.
+    ///
+    /// # Returns
+    /// A result indicating success, or `FobzError::InvalidCompressionLevel` for the first level
+    /// out of range.
+    pub(crate) fn check(&self) -> Result<()> {
+        self.rules
+            .iter()
+            .map(|(_, compression)| *compression)
+            .chain([self.fallback])
+            .try_for_each(Compression::check)
+    }
+}
+
+/// Matches a path against a glob where `*` stops at `/` and `**` does not.
+fn glob(pattern: &[u8], path: &[u8]) -> bool {
+    match pattern {
+        [] => path.is_empty(),
+        [b'*', b'*', rest @ ..] => (0..=path.len()).any(|i| glob(rest, &path[i..])),
+        [b'*', rest @ ..] => {
+            let end = path.iter().position(|v| *v == b'/').unwrap_or(path.len());
+            (0..=end).any(|i| glob(rest, &path[i..]))
+        }
+        [c, rest @ ..] => path.first() == Some(c) && glob(rest, &path[1..]),
+    }
+}
@@ -0,0 +1,1264 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write as _,
+    io::{Read, Seek, Write},
+};
+
+use roxmltree::{Document, ParsingOptions};
+use zip::{write::SimpleFileOptions, CompressionMethod, DateTime, ZipArchive, ZipWriter};
+
+use crate::{
+    archive,
+    compression::{Compression, CompressionPolicy, EntryKind},
+    decode_binary, decode_text,
+    error::{FobzError, Result},
+    escape,
+    html::{self, Token},
+    import::{ImportIssueKind, ImportReport},
+    manifest::{Contributor, Identifier, Role, Series},
+    media,
+    options::ReadLimits,
+    refs,
+    toc::ContentInfo,
+    FobZ, CONTENT_EXTENSIONS, DEFAULT_COVER, DEFAULT_SECTION, NO_COVER, NO_SECTION,
+    STYLE_EXTENSIONS,
+};
+
+// Location of the package document, relative to which every item of the publication is stored.
+const PACKAGE_DIR: &str = "OEBPS/";
+const PACKAGE_PATH: &str = "OEBPS/content.opf";
+const NAV_PATH: &str = "nav.xhtml";
+
+// Points reading systems to the package document.
+const CONTAINER: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles>
+    <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+  </rootfiles>
+</container>
+"#;
+
+// Used for `dcterms:modified` when the manifest has no date, matching `SaveOptions::default()`.
+const DEFAULT_MODIFIED: &str = "1980-01-01T00:00:00Z";
+
+// 64-bit FNV-1a parameters, used to derive identifiers.
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+// HTML elements that never have content, and must be self-closed in XHTML.
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+/// An item of the EPUB package, stored at `OEBPS/{href}`.
+struct Item<'a> {
+    id: String,
+    kind: EntryKind,
+    href: &'a str,
+    media_type: &'a str,
+    properties: Option<&'static str>,
+    bytes: Vec<u8>,
+}
+
+impl FobZ {
+    /// Exports the document as an EPUB 3 publication.
+    ///
+    /// The manifest becomes the package metadata, the table of contents becomes both the spine
+    /// and the navigation document, and every content, resource, and stylesheet is listed as a
+    /// package item with its media type, keeping its path so that relative links still resolve.
+    /// Contents that are not complete documents are wrapped in an XHTML page linking every
+    /// stylesheet. The output only depends on the document.
+    ///
+    /// # Parameters
+    /// - `writer`: The destination of the publication (e.g., a file or an in-memory buffer).
+    ///
+    /// # Returns
+    /// A result containing the writer once the publication is finished, or an error if a payload
+    /// could not be read or the archive could not be written.
+    pub fn export_epub<W: Write + Seek>(&self, writer: W) -> Result<W> {
+        let mut styles: Vec<&str> = self.styles.keys().map(String::as_str).collect();
+        styles.sort_unstable();
+
+        // The defaults of a new document are only exported when they are actually needed.
+        let mut items = Vec::new();
+
+        let mut contents: Vec<&str> = self
+            .contents
+            .keys()
+            .map(String::as_str)
+            .filter(|v| !v.starts_with("default/"))
+            .collect();
+        contents.sort_unstable();
+        for (i, path) in contents.into_iter().enumerate() {
+            if let Some(content) = self.load(&self.contents, path, decode_text)? {
+                let title = self.toc.get(path).map_or(path, |v| v.title.as_str());
+                items.push(Item {
+                    id: format!("content-{}", i + 1),
+                    kind: EntryKind::Content,
+                    href: path,
+                    media_type: "application/xhtml+xml",
+                    properties: None,
+                    bytes: self.to_xhtml(path, title, content, &styles).into_bytes(),
+                });
+            }
+        }
+
+        // A publication needs at least one page to read.
+        if items.is_empty() {
+            items.push(Item {
+                id: "content-default".into(),
+                kind: EntryKind::Content,
+                href: DEFAULT_SECTION,
+                media_type: "application/xhtml+xml",
+                properties: None,
+                bytes: self
+                    .to_xhtml(
+                        DEFAULT_SECTION,
+                        self.manifest.get_title(),
+                        NO_SECTION,
+                        &styles,
+                    )
+                    .into_bytes(),
+            });
+        }
+
+        let cover = self.manifest.get_cover().as_str();
+        let mut resources: Vec<&str> = self
+            .resources
+            .keys()
+            .map(String::as_str)
+            .filter(|v| !v.starts_with("default/"))
+            .collect();
+        resources.sort_unstable();
+        for (i, path) in resources.into_iter().enumerate() {
+            if let Some(resource) = self.load(&self.resources, path, decode_binary)? {
+                items.push(Item {
+                    id: format!("resource-{}", i + 1),
+                    kind: EntryKind::Resource,
+                    href: path,
+                    media_type: match self.tor.get(path) {
+                        Some(info) if !info.media_type.is_empty() => &info.media_type,
+                        _ => media::from_extension(path).unwrap_or(media::OCTET_STREAM),
+                    },
+                    properties: (path == cover).then_some("cover-image"),
+                    bytes: resource.clone(),
+                });
+            }
+        }
+
+        if cover == DEFAULT_COVER {
+            items.push(Item {
+                id: "cover-default".into(),
+                kind: EntryKind::Resource,
+                href: DEFAULT_COVER,
+                media_type: "image/jpeg",
+                properties: Some("cover-image"),
+                bytes: NO_COVER.to_vec(),
+            });
+        }
+
+        for (i, path) in styles.iter().enumerate() {
+            if let Some(style) = self.load(&self.styles, path, decode_text)? {
+                items.push(Item {
+                    id: format!("style-{}", i + 1),
+                    kind: EntryKind::Style,
+                    href: path,
+                    media_type: "text/css",
+                    properties: None,
+                    bytes: style.clone().into_bytes(),
+                });
+            }
+        }
+
+        let mut zip = ZipWriter::new(writer);
+        let options = SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Deflated)
+            .last_modified_time(DateTime::default());
+
+        // The `mimetype` entry must come first and be stored, so that the file can be identified
+        // from its leading bytes.
+        zip.start_file(
+            "mimetype",
+            options.compression_method(CompressionMethod::Stored),
+        )?;
+        zip.write_all(b"application/epub+zip")?;
+
+        zip.start_file("META-INF/container.xml", options)?;
+        zip.write_all(CONTAINER.as_bytes())?;
+
+        zip.start_file(PACKAGE_PATH, options)?;
+        zip.write_all(self.package_document(&items).as_bytes())?;
+
+        zip.start_file(format!("{}{}", PACKAGE_DIR, NAV_PATH), options)?;
+        zip.write_all(self.navigation_document(&items).as_bytes())?;
+
+        // Already compressed formats are stored, as when saving a `.fobz` archive.
+        let policy = CompressionPolicy::default();
+        for item in &items {
+            let method = match policy.compression_for(item.href, item.kind, item.media_type) {
+                Compression::Stored => CompressionMethod::Stored,
+                _ => CompressionMethod::Deflated,
+            };
+            zip.start_file(
+                format!("{}{}", PACKAGE_DIR, item.href),
+                options.compression_method(method),
+            )?;
+            zip.write_all(&item.bytes)?;
+        }
+
+        Ok(zip.finish()?)
+    }
+
+    /// Builds the package document (`content.opf`) listing the metadata, items, and spine.
+    fn package_document(&self, items: &[Item]) -> String {
+        let manifest = &self.manifest;
+        let language = manifest.get_language().map_or("und", String::as_str);
+
+        let mut opf = String::new();
+        let _ = write!(
+            opf,
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <package xmlns=\"http://www.idpf.org/2007/opf\" version=\"3.0\" \
+             unique-identifier=\"pub-id\" xml:lang=\"{}\">\n  \
+             <metadata xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n",
+            escape(language)
+        );
+
+        // The first identifier is the unique one; without any, one is derived from the metadata.
+        let mut identifiers = manifest.get_identifiers().iter().map(identifier_urn);
+        let unique = identifiers
+            .next()
+            .unwrap_or_else(|| self.derived_identifier());
+        element(&mut opf, "dc:identifier", Some("pub-id"), &unique);
+        for identifier in identifiers {
+            element(&mut opf, "dc:identifier", None, &identifier);
+        }
+
+        element(&mut opf, "dc:title", None, manifest.get_title());
+        element(&mut opf, "dc:language", None, language);
+
+        let mut credits = vec![];
+        if !manifest.get_author().is_empty() {
+            credits.push((manifest.get_author().as_str(), Role::Author));
+        }
+        credits.extend(
+            manifest
+                .get_contributors()
+                .iter()
+                .map(|v| (v.name.as_str(), v.role)),
+        );
+        for (i, (name, role)) in credits.into_iter().enumerate() {
+            let id = format!("creator-{}", i + 1);
+            let (tag, relator) = match role {
+                Role::Author => ("dc:creator", "aut"),
+                Role::Editor => ("dc:contributor", "edt"),
+                Role::Translator => ("dc:contributor", "trl"),
+                Role::Illustrator => ("dc:contributor", "ill"),
+            };
+            element(&mut opf, tag, Some(&id), name);
+            let _ = writeln!(
+                opf,
+                "    <meta refines=\"#{}\" property=\"role\" scheme=\"marc:relators\">{}</meta>",
+                id, relator
+            );
+        }
+
+        if !manifest.get_description().is_empty() {
+            element(&mut opf, "dc:description", None, manifest.get_description());
+        }
+        for tag in manifest.get_tags() {
+            element(&mut opf, "dc:subject", None, tag);
+        }
+        if let Some(publisher) = manifest.get_publisher() {
+            element(&mut opf, "dc:publisher", None, publisher);
+        }
+        if let Some(published) = manifest.get_published() {
+            element(&mut opf, "dc:date", None, published);
+        }
+        if let Some(rights) = manifest.get_rights() {
+            element(&mut opf, "dc:rights", None, rights);
+        }
+
+        if let Some(series) = manifest.get_series() {
+            let _ = write!(
+                opf,
+                "    <meta property=\"belongs-to-collection\" id=\"series\">{}</meta>\n    \
+                 <meta refines=\"#series\" property=\"collection-type\">series</meta>\n    \
+                 <meta refines=\"#series\" property=\"group-position\">{}</meta>\n",
+                escape(&series.name),
+                series.position
+            );
+        }
+
+        let modified = manifest
+            .get_modified()
+            .or(manifest.get_published())
+            .map_or(DEFAULT_MODIFIED.into(), |v| date_time(v));
+        let _ = writeln!(
+            opf,
+            "    <meta property=\"dcterms:modified\">{}</meta>",
+            escape(&modified)
+        );
+
+        for (key, value) in manifest.get_extra() {
+            let _ = writeln!(
+                opf,
+                "    <meta name=\"{}\" content=\"{}\"/>",
+                escape(key),
+                escape(value)
+            );
+        }
+        if let Some(item) = items.iter().find(|v| v.properties == Some("cover-image")) {
+            let _ = writeln!(opf, "    <meta name=\"cover\" content=\"{}\"/>", item.id);
+        }
+
+        opf.push_str("  </metadata>\n  <manifest>\n");
+        let _ = writeln!(
+            opf,
+            "    <item id=\"nav\" href=\"{}\" media-type=\"application/xhtml+xml\" properties=\"nav\"/>",
+            NAV_PATH
+        );
+        for item in items {
+            let _ = write!(
+                opf,
+                "    <item id=\"{}\" href=\"{}\" media-type=\"{}\"",
+                item.id,
+                url(item.href),
+                escape(item.media_type)
+            );
+            if let Some(properties) = item.properties {
+                let _ = write!(opf, " properties=\"{}\"", properties);
+            }
+            opf.push_str("/>\n");
+        }
+
+        // The spine follows the table of contents; contents it does not list come last.
+        opf.push_str("  </manifest>\n  <spine>\n");
+        for (item, linear) in self.spine(items) {
+            let linear = if linear { "" } else { " linear=\"no\"" };
+            let _ = writeln!(opf, "    <itemref idref=\"{}\"{}/>", item.id, linear);
+        }
+        opf.push_str("  </spine>\n</package>\n");
+
+        opf
+    }
+
+    /// Orders the content items for reading, flagging those missing from the table of contents.
+    fn spine<'a>(&self, items: &'a [Item]) -> Vec<(&'a Item<'a>, bool)> {
+        let pages: Vec<&Item> = items
+            .iter()
+            .filter(|v| v.media_type == "application/xhtml+xml")
+            .collect();
+
+        let mut spine: Vec<(&Item, bool)> = vec![];
+        for info in self.toc.iter() {
+            if let Some(item) = pages.iter().find(|v| v.href == info.path) {
+                if spine.iter().all(|(v, _)| v.href != item.href) {
+                    spine.push((item, true));
+                }
+            }
+        }
+        for item in pages {
+            if spine.iter().all(|(v, _)| v.href != item.href) {
+                spine.push((item, spine.is_empty()));
+            }
+        }
+
+        spine
+    }
+
+    /// Builds the navigation document (`nav.xhtml`) from the table of contents.
+    fn navigation_document(&self, items: &[Item]) -> String {
+        let hrefs: HashMap<&str, &str> = items.iter().map(|v| (v.href, v.id.as_str())).collect();
+        let title = escape(self.manifest.get_title());
+
+        let mut nav = String::new();
+        let _ = write!(
+            nav,
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <!DOCTYPE html>\n\
+             <html xmlns=\"http://www.w3.org/1999/xhtml\" xmlns:epub=\"http://www.idpf.org/2007/ops\">\n\
+             <head>\n<title>{}</title>\n</head>\n<body>\n\
+             <nav epub:type=\"toc\" id=\"toc\">\n<h1>{}</h1>\n",
+            title, title
+        );
+
+        if self.toc.is_empty() {
+            let (item, _) = self.spine(items)[0];
+            let _ = writeln!(
+                nav,
+                "<ol>\n<li><a href=\"{}\">{}</a></li>\n</ol>",
+                url(item.href),
+                title
+            );
+        } else {
+            navigation_list(&mut nav, self.toc.sections(), &hrefs);
+        }
+        nav.push_str("</nav>\n");
+
+        let index = self.manifest.get_index().as_str();
+        if hrefs.contains_key(index) {
+            let _ = write!(
+                nav,
+                "<nav epub:type=\"landmarks\" hidden=\"hidden\">\n<ol>\n\
+                 <li><a epub:type=\"bodymatter\" href=\"{}\">{}</a></li>\n</ol>\n</nav>\n",
+                url(index),
+                title
+            );
+        }
+
+        nav.push_str("</body>\n</html>\n");
+        nav
+    }
+
+    /// Turns a content into an XHTML page, wrapping fragments in a document linking every style.
+    fn to_xhtml(&self, path: &str, title: &str, content: &str, styles: &[&str]) -> String {
+        let mut content = to_well_formed(content);
+        let tokens = html::tokenize_spans(&content);
+        let doctype = tokens.iter().any(|(_, v)| {
+            matches!(v, Token::Declaration(v) if v.starts_with("<?xml") || v.starts_with("<!DOCTYPE"))
+        });
+        let root = tokens
+            .iter()
+            .find(|(_, v)| matches!(v, Token::Start { name, .. } if name == "html"));
+        if doctype || root.is_some() {
+            // Complete documents are kept, but XHTML requires the namespace on the root element.
+            if let Some((span, token)) = root {
+                if token.attribute("xmlns").is_none() {
+                    let start = span.start + "<html".len();
+                    content.insert_str(start, " xmlns=\"http://www.w3.org/1999/xhtml\"");
+                }
+            }
+            return content;
+        }
+
+        let language = escape(self.manifest.get_language().map_or("und", String::as_str));
+        let mut page = String::new();
+        let _ = write!(
+            page,
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <!DOCTYPE html>\n\
+             <html xmlns=\"http://www.w3.org/1999/xhtml\" xmlns:epub=\"http://www.idpf.org/2007/ops\" \
+             xml:lang=\"{}\" lang=\"{}\">\n<head>\n<title>{}</title>\n",
+            language,
+            language,
+            escape(title)
+        );
+        for style in styles {
+            let _ = writeln!(
+                page,
+                "<link rel=\"stylesheet\" type=\"text/css\" href=\"{}\"/>",
+                url(&refs::relative(path, style))
+            );
+        }
+        let _ = write!(page, "</head>\n<body>\n{}\n</body>\n</html>\n", content);
+
+        page
+    }
+
+    /// Derives a stable `urn:uuid:` identifier from the title, author, and description.
+    ///
+    /// FNV-1a is used rather than the standard hasher, whose output may change between Rust releases.
+    fn derived_identifier(&self) -> String {
+        let mut halves = [0u64; 2];
+        for (i, half) in halves.iter_mut().enumerate() {
+            let mut hash = FNV_OFFSET ^ i as u64;
+            let fields = [
+                self.manifest.get_title(),
+                self.manifest.get_author(),
+                self.manifest.get_description(),
+            ];
+            // `0xFF` never occurs in UTF-8, so it keeps the fields apart.
+            for byte in fields.iter().flat_map(|v| v.bytes().chain([0xFF])) {
+                hash = (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME);
+            }
+            *half = hash;
+        }
+
+        // Shaped as a version 8 (custom) UUID.
+        let [high, low] = halves;
+        let high = (high & !0xF000) | 0x8000;
+        let low = (low & !(0b11 << 62)) | (0b10 << 62);
+        format!(
+            "urn:uuid:{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+            high >> 32,
+            (high >> 16) & 0xFFFF,
+            high & 0xFFFF,
+            low >> 48,
+            low & 0xFFFF_FFFF_FFFF
+        )
+    }
+}
+
+/// Writes the nested list of the navigation document, one level per call.
+fn navigation_list(nav: &mut String, sections: &[ContentInfo], hrefs: &HashMap<&str, &str>) {
+    nav.push_str("<ol>\n");
+    for info in sections {
+        let title = escape(&info.title);
+        if hrefs.contains_key(info.path.as_str()) {
+            let mut href = url(&info.path);
+            if let Some(anchor) = &info.anchor {
+                let _ = write!(href, "#{}", escape(anchor));
+            }
+            let _ = write!(nav, "<li><a href=\"{}\">{}</a>", href, title);
+        } else {
+            let _ = write!(nav, "<li><span>{}</span>", title);
+        }
+        if !info.children.is_empty() {
+            nav.push('\n');
+            navigation_list(nav, &info.children, hrefs);
+        }
+        nav.push_str("</li>\n");
+    }
+    nav.push_str("</ol>\n");
+}
+
+/// Turns the path of an item into a URL that can be written in an attribute, relative to the
+/// package document or to another item.
+fn url(path: &str) -> String {
+    escape(&refs::percent_encode(path))
+}
+
+/// Writes a metadata element, with an optional `id`.
+fn element(opf: &mut String, tag: &str, id: Option<&str>, value: &str) {
+    let id = id.map(|v| format!(" id=\"{}\"", v)).unwrap_or_default();
+    let _ = writeln!(opf, "    <{}{}>{}</{}>", tag, id, escape(value), tag);
+}
+
+/// Formats an identifier as a URN, as expected in `dc:identifier`.
+fn identifier_urn(identifier: &Identifier) -> String {
+    match identifier {
+        Identifier::Isbn(v) => format!("urn:isbn:{}", v),
+        Identifier::Doi(v) => format!("urn:doi:{}", v),
+        Identifier::Uuid(v) => format!("urn:uuid:{}", v),
+    }
+}
+
+/// Completes an ISO 8601 date into the `CCYY-MM-DDThh:mm:ssZ` form required by `dcterms:modified`.
+fn date_time(date: &str) -> String {
+    match date.len() {
+        4 => format!("{}-01-01T00:00:00Z", date),
+        7 => format!("{}-01T00:00:00Z", date),
+        10 => format!("{}T00:00:00Z", date),
+        _ => date.into(),
+    }
+}
+
+/// Rewrites an HTML document as well-formed XHTML, as far as its markup allows.
+///
+/// Void elements are self-closed (e.g., `<br>` becomes `<br/>`), attributes are double-quoted and
+/// given a value when they have none (e.g., `disabled` becomes `disabled="disabled"`), and named
+/// character references that XML does not know (e.g., `&nbsp;`) become numeric ones. The text of
+/// `<script>` and `<style>` elements is kept as it is. Doctypes become `<!DOCTYPE html>`, comments
+/// are rewritten so that they no longer contain `--`, and other declarations XML does not accept
+/// are dropped.
+fn to_well_formed(html: &str) -> String {
+    let mut output = String::with_capacity(html.len());
+    let mut raw = false;
+
+    for (span, token) in html::tokenize_spans(html) {
+        match token {
+            Token::Text(text) if raw => output.push_str(text),
+            Token::Text(text) => output.push_str(&html::to_xml(text)),
+            Token::Start {
+                name, self_closing, ..
+            } => {
+                let void = VOID_ELEMENTS.contains(&name.as_str());
+                output.push_str(&well_formed_start_tag(&html[span], self_closing || void));
+                raw = !self_closing && matches!(name.as_str(), "script" | "style");
+                continue;
+            }
+            // Void elements were closed along with their start tag.
+            Token::End(name) if VOID_ELEMENTS.contains(&name.as_str()) => {}
+            Token::Comment(text) => {
+                let mut text = text.to_string();
+                while text.contains("--") {
+                    text = text.replace("--", "- -");
+                }
+                let space = if text.ends_with('-') { " " } else { "" };
+                let _ = write!(output, "<!--{}{}-->", text, space);
+            }
+            Token::Declaration(text) => {
+                if text
+                    .get(..9)
+                    .is_some_and(|v| v.eq_ignore_ascii_case("<!doctype"))
+                {
+                    output.push_str("<!DOCTYPE html>");
+                } else if text.starts_with("<?") && text.ends_with("?>")
+                    || text.starts_with("<![CDATA[") && text.ends_with("]]>")
+                {
+                    output.push_str(text);
+                }
+            }
+            _ => output.push_str(&html[span]),
+        }
+        raw = false;
+    }
+
+    output
+}
+
+/// Rewrites a start tag (from `<` to `>`) with double-quoted values, giving attributes without one
+/// their own name, and dropping repeated attributes and names XML does not accept.
+fn well_formed_start_tag(tag: &str, close: bool) -> String {
+    let inner = &tag[1..tag.len() - 1];
+    let inner = inner.strip_suffix('/').unwrap_or(inner);
+    let bytes = inner.as_bytes();
+
+    let mut i = inner
+        .find(|c: char| c.is_ascii_whitespace() || c == '/')
+        .unwrap_or(inner.len());
+    let mut output = format!("<{}", &inner[..i]);
+    let mut seen = HashSet::new();
+
+    while i < bytes.len() {
+        while i < bytes.len() && (bytes[i].is_ascii_whitespace() || bytes[i] == b'/') {
+            i += 1;
+        }
+        let name_start = i;
+        while i < bytes.len() && !bytes[i].is_ascii_whitespace() && !b"=/".contains(&bytes[i]) {
+            i += 1;
+        }
+        let name = &inner[name_start..i];
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+
+        let value = if bytes.get(i) == Some(&b'=') {
+            i += 1;
+            while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+                i += 1;
+            }
+            let (start, end) = match bytes.get(i) {
+                Some(&quote @ (b'"' | b'\'')) => {
+                    let end = inner[i + 1..]
+                        .find(quote as char)
+                        .map_or(inner.len(), |v| i + 1 + v);
+                    (i + 1, end)
+                }
+                _ => {
+                    let end = inner[i..]
+                        .find(|c: char| c.is_ascii_whitespace())
+                        .map_or(inner.len(), |v| i + v);
+                    (i, end)
+                }
+            };
+            i = end + 1;
+            &inner[start..end.max(start)]
+        } else if name.is_empty() {
+            i += 1;
+            continue;
+        } else {
+            name
+        };
+
+        let valid = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':' | '.'));
+        if valid && seen.insert(name.to_ascii_lowercase()) {
+            let _ = write!(
+                output,
+                " {}=\"{}\"",
+                name,
+                html::to_xml(value).replace('"', "&quot;")
+            );
+        }
+    }
+
+    output.push_str(if close { "/>" } else { ">" });
+    output
+}
+
+// Namespaces of the package document and of its Dublin Core metadata.
+const OPF_NS: &str = "http://www.idpf.org/2007/opf";
+const DC_NS: &str = "http://purl.org/dc/elements/1.1/";
+const OPS_NS: &str = "http://www.idpf.org/2007/ops";
+
+const CONTAINER_PATH: &str = "META-INF/container.xml";
+
+/// A file listed in the package document of an imported publication.
+///
+/// # Fields
+/// - `id`: The identifier of the item in the package document.
+/// - `path`: The path of the file inside the publication.
+/// - `media_type`: The declared media type of the file.
+/// - `properties`: The declared properties of the item (e.g., `nav`, `cover-image`).
+struct SourceItem {
+    id: String,
+    path: String,
+    media_type: String,
+    properties: String,
+}
+
+impl FobZ {
+    /// Imports an EPUB 2 or EPUB 3 publication as a new document.
+    ///
+    /// Spine items become contents, in reading order, titled after the navigation document (or
+    /// the NCX of EPUB 2); images, fonts, audio, and video become resources; stylesheets become
+    /// styles. Every file keeps its path relative to the package document under `contents/`,
+    /// `resources/`, or `styles/`, and the internal references of contents and stylesheets are
+    /// rewritten to match. The Dublin Core metadata fills the `Manifest`, and the first spine item
+    /// becomes the index.
+    ///
+    /// Parts that have no equivalent in a `.fobz` document are dropped and listed in the returned
+    /// report instead of failing the import.
+    ///
+    /// Entry names are checked as in [`FobZ::from_reader`], and sizes against the default
+    /// [`ReadLimits`].
+    ///
+    /// # Parameters
+    /// - `reader`: The source of the publication (e.g., a file or an in-memory buffer).
+    ///
+    /// # Returns
+    /// A result containing the new `FobZ` and an `ImportReport`, or an error if the publication is
+    /// not a readable zip archive or its container or package document is missing or malformed.
+    pub fn import_epub<R: Read + Seek>(reader: R) -> Result<(FobZ, ImportReport)> {
+        Self::import_epub_with(reader, &ReadLimits::default())
+    }
+
+    /// Imports an EPUB 2 or EPUB 3 publication as a new document, within the given limits.
+    ///
+    /// See [`FobZ::import_epub`] for how the publication is converted.
+    ///
+    /// # Parameters
+    /// - `reader`: The source of the publication (e.g., a file or an in-memory buffer).
+    /// - `limits`: The bounds the publication must stay within.
+    ///
+    /// # Returns
+    /// A result containing the new `FobZ` and an `ImportReport`, `FobzError::LimitExceeded` if the
+    /// publication is too large, an error if an entry name is unsafe, or another error as
+    /// described in [`FobZ::import_epub`].
+    pub fn import_epub_with<R: Read + Seek>(
+        reader: R,
+        limits: &ReadLimits,
+    ) -> Result<(FobZ, ImportReport)> {
+        let mut archive = archive::open(reader, limits)?;
+        let mut report = ImportReport::default();
+
+        let container = read_text(&mut archive, CONTAINER_PATH)?;
+        let container = parse_xml(CONTAINER_PATH, &container)?;
+        let package_path = container
+            .descendants()
+            .find(|v| v.tag_name().name() == "rootfile")
+            .and_then(|v| v.attribute("full-path"))
+            .ok_or_else(|| FobzError::InvalidEpub("no package document is listed".into()))?
+            .to_string();
+
+        let package = read_text(&mut archive, &package_path)?;
+        let package = parse_xml(&package_path, &package)?;
+
+        // Gather the items that are actually present in the publication.
+        let files: HashSet<String> = archive.file_names().map(String::from).collect();
+        let mut items = vec![];
+        for node in package
+            .descendants()
+            .filter(|v| v.has_tag_name((OPF_NS, "item")))
+        {
+            let (Some(id), Some(href)) = (node.attribute("id"), node.attribute("href")) else {
+                continue;
+            };
+            let path = refs::resolve(&package_path, &refs::split_reference(href).0);
+            match path.filter(|v| files.contains(v)) {
+                Some(path) => items.push(SourceItem {
+                    id: id.into(),
+                    path,
+                    media_type: node.attribute("media-type").unwrap_or_default().into(),
+                    properties: node.attribute("properties").unwrap_or_default().into(),
+                }),
+                None => report.push(
+                    ImportIssueKind::MissingItem,
+                    href,
+                    "listed in the package document but missing from the publication".into(),
+                ),
+            }
+        }
+
+        let spine: Vec<&SourceItem> = package
+            .descendants()
+            .filter(|v| v.has_tag_name((OPF_NS, "itemref")))
+            .filter_map(|v| v.attribute("idref"))
+            .filter_map(|id| items.iter().find(|v| v.id == id))
+            .collect();
+
+        // The navigation documents only provide titles, unless they are also part of the spine.
+        let ncx_id = package
+            .descendants()
+            .find(|v| v.has_tag_name((OPF_NS, "spine")))
+            .and_then(|v| v.attribute("toc"));
+        let navigation: Vec<&SourceItem> = items
+            .iter()
+            .filter(|v| {
+                v.properties.split_whitespace().any(|v| v == "nav")
+                    || Some(v.id.as_str()) == ncx_id
+                    || v.media_type == "application/x-dtbncx+xml"
+            })
+            .collect();
+        let mut titles = HashMap::new();
+        for item in &navigation {
+            if let Ok(text) = read_text(&mut archive, &item.path) {
+                if let Ok(document) = parse_xml(&item.path, &text) {
+                    navigation_titles(&document, &item.path, &mut titles);
+                }
+            }
+        }
+
+        // Decide where every item goes, spine items first so that they keep the reading order.
+        let mut order: Vec<&SourceItem> = spine.clone();
+        order.extend(items.iter().filter(|v| spine.iter().all(|s| s.id != v.id)));
+
+        let base = &package_path[..package_path.rfind('/').map_or(0, |v| v + 1)];
+        let mut targets: Vec<(&SourceItem, EntryKind, String)> = vec![];
+        for item in order {
+            let in_spine = spine.iter().any(|v| v.id == item.id);
+            if !in_spine && navigation.iter().any(|v| v.id == item.id) {
+                continue;
+            }
+
+            let Some(kind) = item_kind(&item.media_type) else {
+                report.push(
+                    ImportIssueKind::UnmappedItem,
+                    &item.path,
+                    format!(
+                        "media type '{}' has no place in a document",
+                        item.media_type
+                    ),
+                );
+                continue;
+            };
+            let relative = item.path.strip_prefix(base).unwrap_or(&item.path);
+            targets.push((item, kind, target_path(kind, relative)));
+        }
+
+        // Files outside the package document are not part of the publication's content.
+        let mut unlisted: Vec<&String> = files
+            .iter()
+            .filter(|v| !v.ends_with('/') && *v != "mimetype" && !v.starts_with("META-INF/"))
+            .filter(|v| **v != package_path && items.iter().all(|i| i.path != **v))
+            .collect();
+        unlisted.sort_unstable();
+        for path in unlisted {
+            report.push(
+                ImportIssueKind::UnmappedItem,
+                path,
+                "not listed in the package document".into(),
+            );
+        }
+
+        let locations: HashMap<&str, &str> = targets
+            .iter()
+            .map(|(item, _, target)| (item.path.as_str(), target.as_str()))
+            .collect();
+
+        let mut fobz = FobZ::new(String::new(), String::new(), String::new(), vec![]);
+        import_metadata(&package, &items, &locations, &mut fobz, &mut report);
+
+        for (item, kind, target) in &targets {
+            let bytes = read_bytes(&mut archive, &item.path)?;
+            let result = match kind {
+                EntryKind::Content | EntryKind::Style => {
+                    let Ok(text) = String::from_utf8(bytes) else {
+                        report.push(
+                            ImportIssueKind::UnmappedItem,
+                            &item.path,
+                            "not valid UTF-8".into(),
+                        );
+                        continue;
+                    };
+                    let references = match kind {
+                        EntryKind::Content => refs::html_references(&text),
+                        _ => refs::css_references(&text),
+                    };
+                    let text = refs::rewrite(&text, &references, |href| {
+                        relocate(href, &item.path, target, &locations, &mut report)
+                    });
+
+                    if *kind == EntryKind::Content {
+                        let title = titles
+                            .get(&item.path)
+                            .cloned()
+                            .or_else(|| html::document_title(&text))
+                            .unwrap_or_else(|| file_stem(&item.path).into());
+                        fobz.add_content(target.clone(), title, text)
+                    } else {
+                        fobz.add_style(target.clone(), text)
+                    }
+                }
+                _ => {
+                    let media_type = if item.media_type.is_empty() {
+                        media::detect(&item.path, &bytes)
+                    } else {
+                        item.media_type.clone()
+                    };
+                    let name = file_stem(&item.path).into();
+                    fobz.add_resource_with_type(target.clone(), name, media_type, bytes)
+                }
+            };
+
+            if let Err(err) = result {
+                report.push(ImportIssueKind::UnmappedItem, &item.path, err.to_string());
+            }
+        }
+
+        if let Some((_, _, index)) = targets.iter().find(|(item, kind, _)| {
+            *kind == EntryKind::Content && spine.iter().any(|v| v.id == item.id)
+        }) {
+            fobz.manifest.set_index(index.clone());
+        }
+
+        Ok((fobz, report))
+    }
+}
+
+/// Sorts an imported item into the part of the document it belongs to, from its media type.
+fn item_kind(media_type: &str) -> Option<EntryKind> {
+    match media_type {
+        "application/xhtml+xml" | "text/html" => Some(EntryKind::Content),
+        "text/css" => Some(EntryKind::Style),
+        v if ["image/", "font/", "audio/", "video/"]
+            .iter()
+            .any(|family| v.starts_with(family))
+            || v.starts_with("application/font-")
+            || v.starts_with("application/x-font-")
+            || v == "application/vnd.ms-opentype" =>
+        {
+            Some(EntryKind::Resource)
+        }
+        _ => None,
+    }
+}
+
+/// Builds the path of an imported file from its path relative to the package document, placing it
+/// in the directory of its kind with an extension the document accepts.
+///
+/// Files already laid out like a `.fobz` document (e.g., exported ones) keep their path.
+fn target_path(kind: EntryKind, relative: &str) -> String {
+    let (directory, extensions, default) = match kind {
+        EntryKind::Content => ("contents/", CONTENT_EXTENSIONS, ".xhtml"),
+        EntryKind::Style => ("styles/", STYLE_EXTENSIONS, ".css"),
+        _ => ("resources/", &[][..], ""),
+    };
+
+    let mut path = match relative.strip_prefix(directory) {
+        Some(_) => relative.to_string(),
+        None => format!("{}{}", directory, relative),
+    };
+    if let Some(stem) = path
+        .strip_suffix(".htm")
+        .filter(|_| kind == EntryKind::Content)
+    {
+        path = format!("{}.html", stem);
+    }
+    if !extensions.is_empty() && !extensions.iter().any(|v| path.ends_with(v)) {
+        path.push_str(default);
+    }
+
+    path
+}
+
+/// Rewrites a reference of the imported file `source`, now stored at `target`, so that it points
+/// to where the referenced file was imported.
+///
+/// # Returns
+/// The new reference, or `None` to leave it as is (external links, fragments of the same file,
+/// and references to files that were not imported, which are reported).
+fn relocate(
+    href: &str,
+    source: &str,
+    target: &str,
+    locations: &HashMap<&str, &str>,
+    report: &mut ImportReport,
+) -> Option<String> {
+    if href.is_empty() || href.starts_with('#') || refs::is_external(href) {
+        return None;
+    }
+
+    let (path, fragment) = refs::split_reference(href);
+    let location = refs::resolve(source, &path).and_then(|v| locations.get(v.as_str()).copied());
+    let Some(location) = location else {
+        report.push(
+            ImportIssueKind::UnresolvedLink,
+            source,
+            format!("'{}' does not point to an imported file", href),
+        );
+        return None;
+    };
+
+    let mut relocated = refs::percent_encode(&refs::relative(target, location));
+    if let Some(fragment) = fragment {
+        relocated.push('#');
+        relocated.push_str(fragment);
+    }
+    Some(relocated)
+}
+
+/// Fills the manifest of an imported document from the metadata of its package document.
+fn import_metadata(
+    package: &Document,
+    items: &[SourceItem],
+    locations: &HashMap<&str, &str>,
+    fobz: &mut FobZ,
+    report: &mut ImportReport,
+) {
+    let Some(metadata) = package
+        .descendants()
+        .find(|v| v.tag_name().name() == "metadata")
+    else {
+        return;
+    };
+    let manifest = &mut fobz.manifest;
+    let source = "metadata";
+
+    // EPUB 3 attaches properties to other elements through `refines`.
+    let refinement = |id: Option<&str>, property: &str| {
+        let id = format!("#{}", id?);
+        metadata
+            .descendants()
+            .find(|v| {
+                v.attribute("refines") == Some(&id) && v.attribute("property") == Some(property)
+            })
+            .and_then(|v| v.text())
+            .map(str::trim)
+    };
+
+    let mut cover = items
+        .iter()
+        .find(|v| v.properties.split_whitespace().any(|v| v == "cover-image"));
+    let mut series_name = None;
+    let mut series_position = None;
+
+    for node in metadata.descendants().filter(|v| v.is_element()) {
+        let name = node.tag_name().name();
+        let text = node.text().map(str::trim).unwrap_or_default();
+
+        if node.tag_name().namespace() == Some(DC_NS) {
+            match name {
+                "title" if manifest.get_title().is_empty() => manifest.set_title(text.into()),
+                "creator" | "contributor" => {
+                    let role = node
+                        .attribute((OPF_NS, "role"))
+                        .or_else(|| refinement(node.attribute("id"), "role"))
+                        .or((name == "creator").then_some("aut"));
+                    let role = match role {
+                        Some("aut") => Role::Author,
+                        Some("edt") => Role::Editor,
+                        Some("trl") => Role::Translator,
+                        Some("ill") => Role::Illustrator,
+                        role => {
+                            report.push(
+                                ImportIssueKind::UnmappedMetadata,
+                                source,
+                                format!(
+                                    "{} '{}' has no supported role ({})",
+                                    name,
+                                    text,
+                                    role.unwrap_or("none")
+                                ),
+                            );
+                            continue;
+                        }
+                    };
+                    if role == Role::Author && manifest.get_author().is_empty() {
+                        manifest.set_author(text.into());
+                    } else {
+                        manifest.add_contributor(Contributor::new(text.into(), role));
+                    }
+                }
+                "description" if manifest.get_description().is_empty() => {
+                    manifest.set_description(text.into())
+                }
+                "subject" => manifest.add_tags(vec![text.into()]),
+                "language" if manifest.get_language().is_none() => {
+                    manifest.set_language(Some(text.into()))
+                }
+                "publisher" if manifest.get_publisher().is_none() => {
+                    manifest.set_publisher(Some(text.into()))
+                }
+                "date" => match node.attribute((OPF_NS, "event")) {
+                    Some("modification") => manifest.set_modified(Some(text.into())),
+                    None | Some("publication") if manifest.get_published().is_none() => {
+                        manifest.set_published(Some(text.into()))
+                    }
+                    _ => report.push(
+                        ImportIssueKind::UnmappedMetadata,
+                        source,
+                        format!("additional date '{}'", text),
+                    ),
+                },
+                "identifier" => match parse_identifier(text, node.attribute((OPF_NS, "scheme"))) {
+                    Some(identifier) => manifest.add_identifier(identifier),
+                    None => report.push(
+                        ImportIssueKind::UnmappedMetadata,
+                        source,
+                        format!("identifier '{}' is not an ISBN, DOI, or UUID", text),
+                    ),
+                },
+                "rights" if manifest.get_rights().is_none() => {
+                    manifest.set_rights(Some(text.into()))
+                }
+                _ => report.push(
+                    ImportIssueKind::UnmappedMetadata,
+                    source,
+                    format!("dc:{} '{}'", name, text),
+                ),
+            }
+        } else if name == "meta" {
+            match (node.attribute("property"), node.attribute("name")) {
+                (Some("dcterms:modified"), _) => manifest.set_modified(Some(text.into())),
+                (Some("belongs-to-collection"), _) => {
+                    series_name = Some(text.to_string());
+                    series_position = refinement(node.attribute("id"), "group-position");
+                }
+                (Some("role" | "collection-type" | "group-position"), _)
+                    if node.has_attribute("refines") => {}
+                (Some(property), _) => report.push(
+                    ImportIssueKind::UnmappedMetadata,
+                    source,
+                    format!("meta property '{}' ('{}')", property, text),
+                ),
+                (None, Some(name)) => {
+                    let content = node.attribute("content").unwrap_or_default();
+                    match name {
+                        "cover" => cover = cover.or_else(|| items.iter().find(|v| v.id == content)),
+                        "calibre:series" => series_name = Some(content.into()),
+                        "calibre:series_index" => series_position = Some(content),
+                        name => {
+                            manifest.set_extra(name.into(), content.into());
+                        }
+                    }
+                }
+                (None, None) => {}
+            }
+        }
+    }
+
+    if let Some(name) = series_name {
+        let position = series_position
+            .and_then(|v| v.parse::<f64>().ok())
+            .map_or(1, |v| v.max(0.0) as u32);
+        manifest.set_series(Some(Series::new(name, position)));
+    }
+
+    if let Some(location) = cover.and_then(|v| locations.get(v.path.as_str())) {
+        manifest.set_cover(location.to_string());
+    }
+}
+
+/// Recognizes an ISBN, a DOI, or a UUID, written as a URN or with an EPUB 2 `opf:scheme`.
+fn parse_identifier(text: &str, scheme: Option<&str>) -> Option<Identifier> {
+    let lower = text.to_ascii_lowercase();
+    let prefixed = |prefixes: &[&str]| {
+        prefixes
+            .iter()
+            .find(|v| lower.starts_with(*v))
+            .map(|v| text[v.len()..].to_string())
+    };
+
+    if let Some(value) = prefixed(&["urn:isbn:", "isbn:"]) {
+        return Some(Identifier::Isbn(value));
+    }
+    if let Some(value) = prefixed(&["urn:doi:", "doi:", "https://doi.org/", "http://dx.doi.org/"]) {
+        return Some(Identifier::Doi(value));
+    }
+    if let Some(value) = prefixed(&["urn:uuid:", "uuid:"]) {
+        return Some(Identifier::Uuid(value));
+    }
+
+    match scheme.map(str::to_ascii_lowercase).as_deref() {
+        Some("isbn") => Some(Identifier::Isbn(text.into())),
+        Some("doi") => Some(Identifier::Doi(text.into())),
+        Some("uuid") => Some(Identifier::Uuid(text.into())),
+        _ => None,
+    }
+}
+
+/// Collects the titles given to files by a navigation document (EPUB 3) or an NCX (EPUB 2).
+///
+/// The first entry pointing to a file wins, preferring those without a fragment.
+fn navigation_titles(document: &Document, path: &str, titles: &mut HashMap<String, String>) {
+    let mut entries = vec![];
+
+    if document.root_element().tag_name().name() == "ncx" {
+        for point in document
+            .descendants()
+            .filter(|v| v.tag_name().name() == "navPoint")
+        {
+            let label = point
+                .children()
+                .find(|v| v.tag_name().name() == "navLabel")
+                .map(text_content);
+            let src = point
+                .children()
+                .find(|v| v.tag_name().name() == "content")
+                .and_then(|v| v.attribute("src"));
+            if let (Some(label), Some(src)) = (label, src) {
+                entries.push((src, label));
+            }
+        }
+    } else {
+        let toc = document
+            .descendants()
+            .filter(|v| v.tag_name().name() == "nav")
+            .find(|v| v.attribute((OPS_NS, "type")) == Some("toc"))
+            .or_else(|| {
+                document
+                    .descendants()
+                    .find(|v| v.tag_name().name() == "nav")
+            });
+        for link in toc.iter().flat_map(|v| v.descendants()) {
+            if let (true, Some(href)) = (link.tag_name().name() == "a", link.attribute("href")) {
+                entries.push((href, text_content(link)));
+            }
+        }
+    }
+
+    // Entries without a fragment name the whole file, so they are considered first.
+    entries.sort_by_key(|(href, _)| href.contains('#'));
+    for (href, title) in entries {
+        let (file, _) = refs::split_reference(href);
+        if let Some(file) = refs::resolve(path, &file) {
+            if !title.is_empty() {
+                titles.entry(file).or_insert(title);
+            }
+        }
+    }
+}
+
+/// Concatenates the text of a node and its descendants, collapsing whitespace.
+fn text_content(node: roxmltree::Node) -> String {
+    let text: String = node
+        .descendants()
+        .filter(|v| v.is_text())
+        .filter_map(|v| v.text())
+        .collect();
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Extracts the file name of a path, without its extension.
+fn file_stem(path: &str) -> &str {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    name.rsplit_once('.').map_or(name, |(v, _)| v)
+}
+
+/// Parses an XML document, allowing the DTDs used by EPUB 2.
+fn parse_xml<'a>(entry: &str, text: &'a str) -> Result<Document<'a>> {
+    let options = ParsingOptions {
+        allow_dtd: true,
+        ..ParsingOptions::default()
+    };
+    Document::parse_with_options(text, options).map_err(|err| FobzError::xml(entry, err))
+}
+
+/// Reads the raw bytes of an entry of the publication.
+fn read_bytes<R: Read + Seek>(archive: &mut ZipArchive<R>, entry: &str) -> Result<Vec<u8>> {
+    let mut file = archive
+        .by_name(entry)
+        .map_err(|err| FobzError::entry(entry, err))?;
+    archive::read_file(&mut file)
+}
+
+/// Reads an entry of the publication as UTF-8 text.
+fn read_text<R: Read + Seek>(archive: &mut ZipArchive<R>, entry: &str) -> Result<String> {
+    decode_text(entry, read_bytes(archive, entry)?)
+}
@@ -0,0 +1,266 @@
+use std::{fmt, io};
+
+use zip::result::ZipError;
+
+use crate::{compression::Compression, options::Limit};
+
+/// Represents every error that can occur while reading, writing, or editing a `.fobz` document.
+///
+/// # Variants
+/// - `MissingEntry`: A required entry is missing (e.g., `manifest.json` from the archive, or a
+///   section from a new order).
+/// - `InvalidJson`: A JSON entry could not be parsed or serialized.
+/// - `InvalidUtf8`: A text entry (content or style) is not valid UTF-8.
+/// - `InvalidXml`: An XML document of an imported file could not be parsed.
+/// - `InvalidEpub`: An imported EPUB file lacks a part required to read it (e.g., its package document).
+/// - `InvalidSearchIndex`: A stored search index could not be decoded.
+/// - `UnsupportedVersion`: The archive was written with a format version this library cannot read,
+///   or a document read from a newer format version is being written back.
+/// - `InvalidPath`: A path is not acceptable for the requested operation.
+/// - `DuplicatePath`: An entry already exists at the given path.
+/// - `UnknownPath`: No entry exists at the given path.
+/// - `UnlistedEntry`: A file of an unpacked document is not listed in any table.
+/// - `IndexOutOfBounds`: A position is past the end of a table.
+/// - `RejectedMediaType`: The resource policy does not accept the media type of a resource.
+/// - `AbsoluteEntryPath`: An entry of the archive has an absolute name (e.g., `/etc/x` or `C:x`).
+/// - `EntryPathTraversal`: An entry of the archive has a `..` segment in its name.
+/// - `BackslashInEntryPath`: An entry of the archive has a backslash in its name.
+/// - `DuplicateEntry`: Several entries of the archive have the same name.
+/// - `LimitExceeded`: The archive does not stay within the limits it is read with. `entry` is
+///   `None` for limits on the whole archive.
+/// - `InvalidCompressionLevel`: A compression of the policy used to save has a level outside of
+///   the range of its method, from `min` to `max`.
+/// - `Zip`: The archive itself is malformed or uses an unsupported feature.
+/// - `Io`: An I/O error occurred while reading or writing.
+#[derive(Debug)]
+pub enum FobzError {
+    MissingEntry(String),
+    InvalidJson {
+        entry: String,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    InvalidUtf8(String),
+    InvalidXml {
+        entry: String,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    InvalidEpub(String),
+    InvalidSearchIndex(String),
+    UnsupportedVersion(String),
+    InvalidPath(String),
+    DuplicatePath(String),
+    UnknownPath(String),
+    UnlistedEntry(String),
+    IndexOutOfBounds {
+        index: usize,
+        len: usize,
+    },
+    RejectedMediaType {
+        path: String,
+        media_type: String,
+    },
+    AbsoluteEntryPath(String),
+    EntryPathTraversal(String),
+    BackslashInEntryPath(String),
+    DuplicateEntry(String),
+    LimitExceeded {
+        limit: Limit,
+        entry: Option<String>,
+        value: u64,
+        max: u64,
+    },
+    InvalidCompressionLevel {
+        compression: Compression,
+        min: i64,
+        max: i64,
+    },
+    Zip(ZipError),
+    Io(io::Error),
+}
+
+/// A specialized `Result` type for `.fobz` operations.
+pub type Result<T> = std::result::Result<T, FobzError>;
+
+impl FobzError {
+    /// Converts a JSON error raised while handling `entry` into a `FobzError`.
+    ///
+    /// # Parameters
+    /// - `entry`: The name of the JSON entry (e.g., `toc.json`).
+    /// - `err`: The error reported by `serde_json`.
+    ///
+    /// # Returns
+    /// `FobzError::Io` if the failure came from the underlying reader, `FobzError::InvalidJson` otherwise.
+    pub(crate) fn json(entry: &str, err: serde_json::Error) -> Self {
+        if err.is_io() {
+            return FobzError::Io(err.into());
+        }
+
+        FobzError::InvalidJson {
+            entry: entry.into(),
+            line: err.line(),
+            column: err.column(),
+            message: err.to_string(),
+        }
+    }
+
+    /// Converts an XML error raised while parsing `entry` into a `FobzError`.
+    ///
+    /// # Parameters
+    /// - `entry`: The name of the XML entry (e.g., `META-INF/container.xml`).
+    /// - `err`: The error reported by `roxmltree`.
+    ///
+    /// # Returns
+    /// A `FobzError::InvalidXml` locating the error.
+    pub(crate) fn xml(entry: &str, err: roxmltree::Error) -> Self {
+        let position = err.pos();
+
+        FobzError::InvalidXml {
+            entry: entry.into(),
+            line: position.row as usize,
+            column: position.col as usize,
+            message: err.to_string(),
+        }
+    }
+
+    /// Converts a zip error raised while looking up `entry` into a `FobzError`.
+    ///
+    /// # Parameters
+    /// - `entry`: The name of the entry being accessed.
+    /// - `err`: The error reported by `zip`.
+    ///
+    /// # Returns
+    /// `FobzError::MissingEntry` if the entry does not exist, otherwise the converted error.
+    pub(crate) fn entry(entry: &str, err: ZipError) -> Self {
+        match err {
+            ZipError::FileNotFound => FobzError::MissingEntry(entry.into()),
+            err => err.into(),
+        }
+    }
+}
+
+impl fmt::Display for FobzError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FobzError::MissingEntry(entry) => write!(f, "missing entry '{}'", entry),
+            FobzError::InvalidJson {
+                entry,
+                line,
+                column,
+                message,
+            } => write!(
+                f,
+                "invalid JSON in '{}' at line {}, column {}: {}",
+                entry, line, column, message
+            ),
+            FobzError::InvalidUtf8(entry) => write!(f, "entry '{}' is not valid UTF-8", entry),
+            FobzError::InvalidXml {
+                entry,
+                line,
+                column,
+                message,
+            } => write!(
+                f,
+                "invalid XML in '{}' at line {}, column {}: {}",
+                entry, line, column, message
+            ),
+            FobzError::InvalidEpub(message) => write!(f, "invalid EPUB: {}", message),
+            FobzError::InvalidSearchIndex(message) => {
+                write!(f, "invalid search index: {}", message)
+            }
+            FobzError::UnsupportedVersion(version) => {
+                write!(f, "unsupported format version '{}'", version)
+            }
+            FobzError::InvalidPath(path) => write!(f, "invalid path '{}'", path),
+            FobzError::DuplicatePath(path) => write!(f, "an entry already exists at '{}'", path),
+            FobzError::UnknownPath(path) => write!(f, "no entry exists at '{}'", path),
+            FobzError::UnlistedEntry(path) => {
+                write!(f, "'{}' is not listed in any table", path)
+            }
+            FobzError::IndexOutOfBounds { index, len } => {
+                write!(f, "index {} is out of bounds for {} entries", index, len)
+            }
+            FobzError::RejectedMediaType { path, media_type } => write!(
+                f,
+                "media type '{}' of '{}' is not allowed by the resource policy",
+                media_type, path
+            ),
+            FobzError::AbsoluteEntryPath(entry) => {
+                write!(f, "entry '{}' has an absolute path", entry)
+            }
+            FobzError::EntryPathTraversal(entry) => {
+                write!(f, "entry '{}' climbs out of the archive", entry)
+            }
+            FobzError::BackslashInEntryPath(entry) => {
+                write!(f, "entry '{}' has a backslash in its path", entry)
+            }
+            FobzError::DuplicateEntry(entry) => {
+                write!(f, "entry '{}' appears more than once", entry)
+            }
+            FobzError::LimitExceeded {
+                limit,
+                entry,
+                value,
+                max,
+            } => {
+                let limit = match limit {
+                    Limit::TotalSize => "total uncompressed size",
+                    Limit::EntrySize => "uncompressed size",
+                    Limit::EntryCount => "number of entries",
+                    Limit::CompressionRatio => "compression ratio",
+                };
+                match entry {
+                    Some(entry) => write!(
+                        f,
+                        "{} of entry '{}' is {}, over the limit of {}",
+                        limit, entry, value, max
+                    ),
+                    None => write!(
+                        f,
+                        "{} of the archive is {}, over the limit of {}",
+                        limit, value, max
+                    ),
+                }
+            }
+            FobzError::InvalidCompressionLevel {
+                compression,
+                min,
+                max,
+            } => write!(
+                f,
+                "compression {:?} has a level outside of the range {} to {}",
+                compression, min, max
+            ),
+            FobzError::Zip(err) => write!(f, "zip error: {}", err),
+            FobzError::Io(err) => write!(f, "I/O error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for FobzError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FobzError::Zip(err) => Some(err),
+            FobzError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for FobzError {
+    fn from(err: io::Error) -> Self {
+        FobzError::Io(err)
+    }
+}
+
+impl From<ZipError> for FobzError {
+    fn from(err: ZipError) -> Self {
+        match err {
+            ZipError::Io(err) => FobzError::Io(err),
+            err => FobzError::Zip(err),
+        }
+    }
+}
@@ -0,0 +1,325 @@
+use std::{collections::HashMap, ops::Range, sync::OnceLock};
+
+use crate::refs;
+
+// Elements whose text is not HTML, kept as a single text token up to their end tag. Browsers read
+// them this way even when their start tag ends with `/>`, as long as an end tag follows.
+const RAW_TEXT_ELEMENTS: &[&str] = &[
+    "iframe",
+    "noembed",
+    "noframes",
+    "plaintext",
+    "script",
+    "style",
+    "textarea",
+    "title",
+    "xmp",
+];
+
+// The longest name of a named character reference, `&CounterClockwiseContourIntegral;`.
+const MAX_ENTITY_LENGTH: usize = 32;
+
+// Named character references that XML knows as well.
+const XML_ENTITIES: &[&str] = &["amp", "lt", "gt", "quot", "apos"];
+
+/// A piece of an HTML document, as returned by [`tokenize`].
+///
+/// # Variants
+/// - `Text`: Text between tags, with its character references still escaped.
+/// - `Start`: A start tag, with its lowercase name, its attributes, and whether it ends with `/>`.
+/// - `End`: An end tag, with its lowercase name.
+/// - `Comment`: The text of a comment, without its `<!--` and `-->`.
+/// - `Declaration`: A doctype or a bogus comment (e.g., a processing instruction, a CDATA section,
+///   or `</` not followed by a letter), as written, up to its first `>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Token<'a> {
+    Text(&'a str),
+    Start {
+        name: String,
+        attributes: Vec<Attribute<'a>>,
+        self_closing: bool,
+    },
+    End(String),
+    Comment(&'a str),
+    Declaration(&'a str),
+}
+
+/// An attribute of a start tag.
+///
+/// # Fields
+/// - `name`: The lowercase name of the attribute.
+/// - `value`: The value as written, with its character references still escaped, or `None` for
+///   attributes without a value (e.g., `hidden`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Attribute<'a> {
+    pub(crate) name: String,
+    pub(crate) value: Option<&'a str>,
+}
+
+impl Token<'_> {
+    /// Retrieves the raw value of an attribute of a start tag.
+    pub(crate) fn attribute(&self, name: &str) -> Option<&str> {
+        match self {
+            Token::Start { attributes, .. } => attributes
+                .iter()
+                .find(|v| v.name == name)
+                .and_then(|v| v.value),
+            _ => None,
+        }
+    }
+}
+
+/// Splits an HTML document into tags, text, comments, and declarations.
+///
+/// The document does not need to be well-formed: a `<` that does not start a tag is kept as text,
+/// and an unterminated tag ends the document as text.
+pub(crate) fn tokenize(html: &str) -> Vec<Token<'_>> {
+    tokenize_spans(html).into_iter().map(|(_, v)| v).collect()
+}
+
+/// Splits an HTML document as [`tokenize`] does, along with the byte range of each token.
+pub(crate) fn tokenize_spans(html: &str) -> Vec<(Range<usize>, Token<'_>)> {
+    let mut tokens = vec![];
+    let mut i = 0;
+
+    while i < html.len() {
+        let Some(offset) = html[i..].find('<') else {
+            tokens.push((i..html.len(), Token::Text(&html[i..])));
+            break;
+        };
+        if offset > 0 {
+            tokens.push((i..i + offset, Token::Text(&html[i..i + offset])));
+        }
+        let start = i + offset;
+        let rest = &html[start..];
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            let (text_end, end) = refs::comment_end(comment);
+            i = start + 4 + end;
+            tokens.push((start..i, Token::Comment(&comment[..text_end])));
+            continue;
+        }
+
+        let next = rest[1..].chars().next();
+        let bogus = match next {
+            Some('!' | '?') => true,
+            Some('/') => rest[2..].starts_with(|c: char| !c.is_ascii_alphabetic()),
+            _ => false,
+        };
+        if bogus {
+            // HTML ends doctypes and bogus comments (processing instructions, CDATA sections, ...)
+            // at the first `>`, whatever the quotes or brackets before it.
+            let end = rest.find('>').map_or(rest.len(), |v| v + 1);
+            i = start + end;
+            tokens.push((start..i, Token::Declaration(&rest[..end])));
+            continue;
+        }
+        if !next.is_some_and(|c| c == '/' && rest.len() > 2 || c.is_ascii_alphabetic()) {
+            tokens.push((start..start + 1, Token::Text(&rest[..1])));
+            i = start + 1;
+            continue;
+        }
+        let Some(end) = refs::tag_end(rest) else {
+            tokens.push((start..html.len(), Token::Text(rest)));
+            break;
+        };
+        let tag = &rest[1..end];
+        i = start + end + 1;
+
+        match next {
+            Some('/') => tokens.push((start..i, Token::End(tag_name(&tag[1..])))),
+            _ => {
+                let name = tag_name(tag);
+                let self_closing = tag.ends_with('/');
+                let attributes = refs::attributes(tag, 0)
+                    .into_iter()
+                    .map(|(name, value)| Attribute {
+                        name,
+                        value: value.map(|(start, end)| &tag[start..end]),
+                    })
+                    .collect();
+
+                // The text of these elements is not HTML, so look for their end tag directly.
+                let raw = RAW_TEXT_ELEMENTS.contains(&name.as_str());
+                tokens.push((
+                    start..i,
+                    Token::Start {
+                        name,
+                        attributes,
+                        self_closing,
+                    },
+                ));
+                if raw {
+                    let close = format!("</{}", tag_name(tag));
+                    let body_end = html[i..].to_ascii_lowercase().find(&close).map(|v| i + v);
+                    // Browsers ignore `/>` here, but XHTML contents use it for empty elements: it
+                    // only counts when no end tag follows, and nothing is then hidden as text.
+                    let body_end = match body_end {
+                        Some(end) => end,
+                        None if self_closing => continue,
+                        None => html.len(),
+                    };
+                    if body_end > i {
+                        tokens.push((i..body_end, Token::Text(&html[i..body_end])));
+                    }
+                    i = body_end;
+                }
+            }
+        }
+    }
+
+    tokens
+}
+
+/// Decodes the character references of a text or attribute value, keeping unknown ones as they
+/// are.
+pub(crate) fn unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        match reference(rest) {
+            Some((decoded, length)) => {
+                unescaped.push_str(&decoded);
+                rest = &rest[length..];
+            }
+            None => {
+                unescaped.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    unescaped.push_str(rest);
+    unescaped
+}
+
+/// Rewrites a text or attribute value so that XML accepts it: named character references other
+/// than XML's own become numeric ones, and a `&` that does not start a known reference or a `<`
+/// is escaped.
+pub(crate) fn to_xml(text: &str) -> String {
+    let mut xml = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find(['&', '<']) {
+        xml.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        if rest.starts_with('<') {
+            xml.push_str("&lt;");
+            rest = &rest[1..];
+            continue;
+        }
+        match reference(rest) {
+            Some((decoded, length)) => {
+                let name = &rest[1..length - 1];
+                if rest[..length].ends_with(';') && XML_ENTITIES.contains(&name) {
+                    xml.push_str(&rest[..length]);
+                } else {
+                    for c in decoded.chars() {
+                        xml.push_str(&format!("&#{};", c as u32));
+                    }
+                }
+                rest = &rest[length..];
+            }
+            None => {
+                xml.push_str("&amp;");
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    xml.push_str(rest);
+    xml
+}
+
+/// Decodes the character reference starting `text` (at its `&`) as browsers do in attribute
+/// values: numeric references may omit their `;`, and so may the named references HTML allows
+/// without one, unless a letter, a digit, or `=` follows.
+///
+/// # Returns
+/// The decoded text along with the length of the reference, or `None` if `text` does not start
+/// with a reference.
+fn reference(text: &str) -> Option<(String, usize)> {
+    let rest = &text[1..];
+
+    if let Some(number) = rest.strip_prefix('#') {
+        let (digits, radix, prefix) = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => (hex, 16, 2),
+            None => (number, 10, 1),
+        };
+        let end = digits
+            .find(|c: char| !c.is_digit(radix))
+            .unwrap_or(digits.len());
+        if end == 0 {
+            return None;
+        }
+        // Out of range and invalid code points become the replacement character.
+        let c = u32::from_str_radix(&digits[..end], radix)
+            .ok()
+            .filter(|v| *v != 0)
+            .and_then(char::from_u32)
+            .unwrap_or('\u{fffd}');
+        let semicolon = usize::from(digits[end..].starts_with(';'));
+        return Some((c.into(), 1 + prefix + end + semicolon));
+    }
+
+    let end = rest
+        .find(|c: char| !c.is_ascii_alphanumeric())
+        .unwrap_or(rest.len());
+    if end == 0 || end > MAX_ENTITY_LENGTH {
+        return None;
+    }
+    let name = &rest[..end];
+    let entities = entities();
+
+    if rest[end..].starts_with(';') {
+        if let Some(decoded) = entities.get(&rest[..end + 1]) {
+            return Some(((*decoded).into(), end + 2));
+        }
+    }
+    // Only the whole name counts without a `;`, as a letter or digit may not follow.
+    if rest[end..].starts_with('=') {
+        return None;
+    }
+    entities
+        .get(name)
+        .map(|decoded| ((*decoded).into(), end + 1))
+}
+
+/// Maps the names of the HTML named character references (without their `&`, and with their `;`
+/// when they have one) to the text they stand for.
+fn entities() -> &'static HashMap<&'static str, &'static str> {
+    static ENTITIES: OnceLock<HashMap<&'static str, &'static str>> = OnceLock::new();
+    ENTITIES.get_or_init(|| {
+        entities::ENTITIES
+            .iter()
+            .map(|v| (&v.entity[1..], v.characters))
+            .collect()
+    })
+}
+
+/// Finds the text of the `<title>` element of an HTML document.
+pub(crate) fn document_title(html: &str) -> Option<String> {
+    let lower = html.to_ascii_lowercase();
+    let start = lower.find("<title")?;
+    let start = start + lower[start..].find('>')? + 1;
+    let end = start + lower[start..].find("</title")?;
+    let title = html[start..end]
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    (!title.is_empty()).then_some(title)
+}
+
+/// Extracts the lowercase element name at the start of a tag (without its `<`).
+fn tag_name(tag: &str) -> String {
+    let end = tag
+        .find(|c: char| c.is_ascii_whitespace() || c == '/')
+        .unwrap_or(tag.len());
+    tag[..end].to_ascii_lowercase()
+}
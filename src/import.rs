@@ -0,0 +1,68 @@
+use std::fmt;
+
+/// Identifies the kind of part that could not be carried over by an import.
+///
+/// # Variants
+/// - `UnmappedItem`: A file has no place in the `.fobz` layout (e.g., a script) and was dropped.
+/// - `MissingItem`: A file referenced by the source is missing from it.
+/// - `UnmappedMetadata`: A metadata entry has no equivalent in the `Manifest` and was dropped.
+/// - `UnresolvedLink`: A reference points to a file that was not imported, and was left as is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportIssueKind {
+    UnmappedItem,
+    MissingItem,
+    UnmappedMetadata,
+    UnresolvedLink,
+}
+
+/// Represents a single part of the source that an import could not carry over.
+///
+/// # Fields
+/// - `kind`: The kind of problem.
+/// - `source`: The path of the file in the source the issue refers to.
+/// - `message`: A human-readable description of what was dropped or left as is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportIssue {
+    pub kind: ImportIssueKind,
+    pub source: String,
+    pub message: String,
+}
+
+impl fmt::Display for ImportIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.source, self.message)
+    }
+}
+
+/// Collects every part of the source that an import could not carry over.
+///
+/// # Fields
+/// - `issues`: The issues, in the order they were found.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    issues: Vec<ImportIssue>,
+}
+
+impl ImportReport {
+    /// Returns every issue found.
+    pub fn issues(&self) -> &[ImportIssue] {
+        &self.issues
+    }
+
+    /// Checks whether everything was carried over.
+    ///
+    /// # Returns
+    /// `true` if no issue was found, otherwise `false`.
+    pub fn is_complete(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Records a new issue.
+    pub(crate) fn push(&mut self, kind: ImportIssueKind, source: &str, message: String) {
+        self.issues.push(ImportIssue {
+            kind,
+            source: source.into(),
+            message,
+        });
+    }
+}
@@ -14,6 +14,7 @@
 //! - `contents/`
 //! - `resources/`
 //! - `styles/`
+//! - `search/index.bin` (optional)
 //!
 //! ### `manifest.json`
 //!
@@ -21,7 +22,7 @@
 //!
 //! ```json
 //! {
-//!     "version": "1.0",
+//!     "version": "1.3",
 //!     "title": "Sample Document",
 //!     "author": "John Doe",
 //!     "description": "A short description of the document.",
@@ -32,24 +33,49 @@
 //!     ],
 //!     "index": "contents/introduction.html"
 //!     "cover": "resource1",
+//!     "contributors": [
+//!         { "name": "Jane Roe", "role": "translator" }
+//!     ],
+//!     "language": "en",
+//!     "publisher": "Sample Press",
+//!     "published": "2024-05-01",
+//!     "modified": "2024-06-12T09:30:00Z",
+//!     "identifiers": [
+//!         { "scheme": "isbn", "value": "978-3-16-148410-0" }
+//!     ],
+//!     "series": { "name": "Samples", "position": 2 },
+//!     "rights": "All rights reserved.",
+//!     "extra": { "edition": "second" }
 //! }
 //! ```
 //!
-//! - `version`: A string specifying the version of the document format.
+//! - `version`: A string specifying the version of the document format, as `major.minor`. Older
+//!   versions are migrated when read, newer minor versions are read but cannot be written back,
+//!   and unknown major versions are refused.
 //! - `title`: A string representing the title of the document.
 //! - `author`: The name of the author.
 //! - `description`: A brief description or synopsis of the document.
 //! - `tags`: An array containing the tags categorizing the document.
 //! - `index`: The starting point of the document.
 //! - `cover`: The cover image of the document.
+//! - `contributors`: Optional. The people credited in the document, each with a `role` among
+//!   `author`, `editor`, `translator`, and `illustrator`.
+//! - `language`: Optional. The language of the document, as a BCP 47 tag.
+//! - `publisher`: Optional. The name of the publisher.
+//! - `published`, `modified`: Optional. The publication and last modification dates, in ISO 8601.
+//! - `identifiers`: Optional. The identifiers of the document, with a `scheme` among `isbn`,
+//!   `doi`, and `uuid`.
+//! - `series`: Optional. The series the document belongs to, and its position in it.
+//! - `rights`: Optional. A statement about the rights held over the document.
+//! - `extra`: Optional. Free-form string metadata.
 //!
 //! ### `toc.json` (Table of Contents)
 //!
-//! This file lists the contents in the document. Each section is defined by a `Section` object:
+//! This file lists the contents in the document as a tree. Each section is defined by a `Section` object:
 //!
 //! ```json
 //! {
-//!     "contents": [
+//!     "sections": [
 //!         {
 //!             "path": "contents/introduction.html"
 //!             "title": "Introduction",
@@ -57,6 +83,13 @@
 //!         {
 //!             "path": "contents/chapter1.html"
 //!             "title": "Chapter 1",
+//!             "children": [
+//!                 {
+//!                     "path": "contents/chapter1.html"
+//!                     "title": "The Journey Begins",
+//!                     "anchor": "journey"
+//!                 }
+//!             ]
 //!         }
 //!     ]
 //! }
@@ -64,6 +97,8 @@
 //!
 //! - `path`: The file path where the section content is stored.
 //! - `title`: The title of the section.
+//! - `anchor`: The optional `id` of the element the section starts at, inside its file.
+//! - `children`: The optional nested sections.
 //!
 //! ### `tor.json` (Table of Resources)
 //!
@@ -75,10 +110,12 @@
 //!         {
 //!             "path": "resources/cover.png"
 //!             "name": "Cover Image",
+//!             "media_type": "image/png"
 //!         },
 //!         {
 //!             "path": "resources/image.jpg"
 //!             "name": "Cat Image",
+//!             "media_type": "image/jpeg"
 //!         }
 //!     ]
 //! }
@@ -86,6 +123,7 @@
 //!
 //! - `path`: The file path to the resource.
 //! - `name`: A human-readable name for the resource.
+//! - `media_type`: The MIME type of the resource (e.g., `image/png`, `font/woff2`, `video/mp4`).
 //!
 //! ### `tos.json` (Table of Styles)
 //!
@@ -112,13 +150,18 @@
 //!
 //! ### `resources/` Directory
 //!
-//! This directory stores additional resources like images. Each resource is referenced in `tor.json` and stored as a file inside this directory.
+//! This directory stores additional resources like images, fonts, audio, and video. Each resource is referenced in `tor.json` and stored as a file inside this directory.
 //!
 //! ### `styles/` Directory
 //!
 //! Stores CSS stylesheets that apply styling to the document. Each stylesheet is referenced in `tos.json`
 //! and can be linked dynamically to contents as needed.
 //!
+//! ### `search/index.bin`
+//!
+//! An optional full-text index of the contents, written by [`FobZ::store_search_index`]. It is a
+//! cache: readers that do not use it can ignore it, and it is dropped when it cannot be decoded.
+//!
 //! ## Example File Structure
 //!
 //! ```plaintext
@@ -147,12 +190,23 @@
 
 use std::{
     collections::HashMap,
-    fs::File,
-    io::{Read, Write},
+    fs::{self, File},
+    io::{Cursor, Read, Seek, Write},
+    path::Path,
+    sync::OnceLock,
 };
 
+use archive::{ArchiveSource, ReadSeek};
+use compression::{Compression, EntryKind};
+use error::{FobzError, Result};
 use manifest::Manifest;
-use toc::{ContentInfo, TableOfContents};
+use media::ResourcePolicy;
+use migration::Layout;
+use options::{LoadMode, OpenOptions, ReadLimits, SaveOptions};
+use sanitize::{SanitizePolicy, SanitizeReport};
+use search::SearchIndex;
+use serde::Serialize;
+use toc::{split_href, ContentInfo, TableOfContents};
 use tor::{ResourceInfo, TableOfResources};
 use tos::{StyleInfo, TableOfStyles};
 use zip::{
@@ -160,19 +214,133 @@ use zip::{
     ZipArchive, ZipWriter,
 };
 
+mod archive;
+mod atomic;
+/// Module deciding how each entry is compressed when saving.
+pub mod compression;
+mod epub;
+/// Module defining the errors returned by every fallible operation.
+pub mod error;
+mod html;
+/// Module reporting what an import could not carry over.
+pub mod import;
+/// Module checking the references between contents, resources, and styles.
+pub mod links;
 /// Module handling the manifest containing the metadata.
 pub mod manifest;
+mod markdown;
+/// Module detecting the media types of resources.
+pub mod media;
+/// Module checking format versions and migrating older archives.
+pub mod migration;
+/// Module containing the options used when opening a `.fobz` archive.
+pub mod options;
+mod refs;
+mod rename;
+/// Module removing unwanted markup from the HTML of contents.
+pub mod sanitize;
+/// Module providing full-text search over the document sections.
+pub mod search;
+mod site;
+mod text;
 /// Module handling the table of contents for document contents.
 pub mod toc;
 /// Module for managing the table of resources (e.g., images).
 pub mod tor;
 /// Module dedicated to managing stylesheets used by the document.
 pub mod tos;
+mod unpack;
+/// Module checking the structural consistency of a document.
+pub mod validate;
 
 // Constants representing default resources included in the library.
+const DEFAULT_COVER: &str = "default/no_cover.jpg"; // Path of the default cover image
+const DEFAULT_SECTION: &str = "default/no_section.html"; // Path of the default section HTML
 const NO_COVER: &[u8] = include_bytes!("../default/no_cover.jpg"); // Default cover image
 const NO_SECTION: &str = include_str!("../default/no_section.html"); // Default section HTML
 
+// Extensions accepted for contents and styles added to a document.
+const CONTENT_EXTENSIONS: &[&str] = &[".html", ".xhtml"];
+const STYLE_EXTENSIONS: &[&str] = &[".css"];
+
+/// Checks that a path added to the document is a normalized relative path inside `directory`,
+/// ending with one of `extensions` (any extension if empty). Paths may not hold a `#` or a `?`,
+/// which would be read as the start of a fragment or a query in references.
+fn check_path(path: &str, directory: &str, extensions: &[&str]) -> Result<()> {
+    let valid = path.len() > directory.len()
+        && path.starts_with(directory)
+        && (extensions.is_empty() || extensions.iter().any(|v| path.ends_with(v)))
+        && !path.contains(['\\', '#', '?'])
+        && path
+            .split('/')
+            .all(|v| !v.is_empty() && v != "." && v != "..");
+
+    if valid {
+        Ok(())
+    } else {
+        Err(FobzError::InvalidPath(path.into()))
+    }
+}
+
+/// Decodes the raw bytes of a text entry (contents and styles) as UTF-8.
+fn decode_text(entry: &str, bytes: Vec<u8>) -> Result<String> {
+    String::from_utf8(bytes).map_err(|_| FobzError::InvalidUtf8(entry.into()))
+}
+
+/// Keeps the raw bytes of a binary entry (resources) as they are.
+fn decode_binary(_entry: &str, bytes: Vec<u8>) -> Result<Vec<u8>> {
+    Ok(bytes)
+}
+
+/// Escapes the characters with a special meaning in XML and HTML text and attribute values.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Writes a file exported under `dir`, creating its parent directories.
+///
+/// Paths that could leave `dir` are refused with `FobzError::InvalidPath`.
+fn write_file(dir: &Path, path: &str, bytes: &[u8]) -> Result<()> {
+    let escapes = path.contains('\\')
+        || path
+            .split('/')
+            .any(|v| v.is_empty() || v == "." || v == "..");
+    if escapes {
+        return Err(FobzError::InvalidPath(path.into()));
+    }
+
+    let path = dir.join(path);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, bytes)?;
+    Ok(())
+}
+
+/// Serializes a value as a pretty-printed JSON entry of the archive.
+fn write_json<T: Serialize, W: Write + Seek>(
+    zip: &mut ZipWriter<W>,
+    entry: &str,
+    value: &T,
+    options: FileOptions<'_, ExtendedFileOptions>,
+) -> Result<()> {
+    let json = serde_json::to_string_pretty(value).map_err(|err| FobzError::json(entry, err))?;
+    zip.start_file(entry, options)?;
+    zip.write_all(json.as_bytes())?;
+    Ok(())
+}
+
 /// Represents a `.fobz` document, which includes metadata, contents, resources, and styles.
 ///
 /// # Fields
@@ -183,15 +351,25 @@ const NO_SECTION: &str = include_str!("../default/no_section.html"); // Default
 /// - `contents`: A hashmap storing the contents (HTML) of the document sections.
 /// - `resources`: A hashmap storing binary resources (e.g., images).
 /// - `styles`: A hashmap storing the styles (CSS) for the document.
+/// - `policy`: The policy deciding which resources the document accepts.
+/// - `source`: The archive backing entries that have not been read yet, when opened lazily.
+/// - `search`: The search index saved with the archive, if any.
+/// - `sanitize_policy`: The policy applied to the HTML of contents added to the document.
+/// - `sanitize_report`: What the sanitization policy removed since it was last taken.
 #[derive(Debug)]
 pub struct FobZ {
     manifest: Manifest,
     toc: TableOfContents,
     tor: TableOfResources,
     tos: TableOfStyles,
-    contents: HashMap<String, String>,
-    resources: HashMap<String, Vec<u8>>,
-    styles: HashMap<String, String>,
+    contents: HashMap<String, OnceLock<String>>,
+    resources: HashMap<String, OnceLock<Vec<u8>>>,
+    styles: HashMap<String, OnceLock<String>>,
+    policy: ResourcePolicy,
+    source: Option<ArchiveSource>,
+    search: Option<SearchIndex>,
+    sanitize_policy: SanitizePolicy,
+    sanitize_report: SanitizeReport,
 }
 
 impl FobZ {
@@ -211,9 +389,17 @@ impl FobZ {
             toc: TableOfContents::new(),
             tor: TableOfResources::new(),
             tos: TableOfStyles::new(),
-            contents: HashMap::from([("default/no_section.html".into(), NO_SECTION.into())]),
-            resources: HashMap::from([("default/no_cover.jpg".into(), NO_COVER.to_vec())]),
+            contents: HashMap::from([(
+                DEFAULT_SECTION.into(),
+                OnceLock::from(NO_SECTION.to_string()),
+            )]),
+            resources: HashMap::from([(DEFAULT_COVER.into(), OnceLock::from(NO_COVER.to_vec()))]),
             styles: HashMap::new(),
+            policy: ResourcePolicy::default(),
+            source: None,
+            search: None,
+            sanitize_policy: SanitizePolicy::default(),
+            sanitize_report: SanitizeReport::default(),
         }
     }
 
@@ -224,114 +410,425 @@ impl FobZ {
     ///
     /// # Returns
     /// A result containing the `FobZ` instance if successful, or an error if any issue occurs.
-    pub fn open(path: &str) -> anyhow::Result<Self> {
-        let file = File::open(&path)?;
-        let mut archive = ZipArchive::new(file)?;
+    pub fn open(path: &str) -> Result<Self> {
+        let file = File::open(path)?;
+        Self::from_reader(file)
+    }
 
-        // Deserialize the JSON files in the archive into their respective structs.
-        let manifest: Manifest = serde_json::from_reader(archive.by_name("manifest.json")?)?;
-        let toc: TableOfContents = serde_json::from_reader(archive.by_name("toc.json")?)?;
-        let tor: TableOfResources = serde_json::from_reader(archive.by_name("tor.json")?)?;
-        let tos: TableOfStyles = serde_json::from_reader(archive.by_name("tos.json")?)?;
+    /// Opens an existing `.fobz` file using the given options.
+    ///
+    /// In [`LoadMode::Lazy`] the file stays open for the lifetime of the instance, and entries are
+    /// only decompressed the first time they are requested.
+    ///
+    /// # Parameters
+    /// - `path`: The file path to the `.fobz` archive.
+    /// - `options`: The options controlling how the archive is read.
+    ///
+    /// # Returns
+    /// A result containing the `FobZ` instance if successful, or an error if any issue occurs.
+    pub fn open_with(path: &str, options: &OpenOptions) -> Result<Self> {
+        let file = File::open(path)?;
+        Self::from_reader_with(file, options)
+    }
 
-        let mut contents = HashMap::new();
-        let mut resources = HashMap::new();
-        let mut styles = HashMap::new();
+    /// Reads a `.fobz` archive held in memory into a `FobZ` instance.
+    ///
+    /// # Parameters
+    /// - `bytes`: The raw bytes of the `.fobz` archive.
+    ///
+    /// # Returns
+    /// A result containing the `FobZ` instance if successful, or an error if any issue occurs.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        Self::from_reader(Cursor::new(bytes))
+    }
 
-        // Read the files in the archive and categorize them into contents, resources, and styles.
-        for i in 0..archive.len() {
-            let mut file = archive.by_index(i)?;
-            let file_name = file.name().to_string();
+    /// Reads a `.fobz` archive from any seekable reader into a `FobZ` instance.
+    ///
+    /// Every entry is read into memory before returning. The archive is refused if an entry name
+    /// is unsafe to extract (absolute, climbing with `..`, holding a backslash, or duplicated), or
+    /// if the archive exceeds the default [`ReadLimits`].
+    ///
+    /// # Parameters
+    /// - `reader`: The source of the `.fobz` archive (e.g., a file or an in-memory buffer).
+    ///
+    /// # Returns
+    /// A result containing the `FobZ` instance if successful, or an error if any issue occurs.
+    pub fn from_reader<R: Read + Seek>(reader: R) -> Result<Self> {
+        let mut archive = archive::open(reader, &ReadLimits::default())?;
+        let mut fobz = Self::read_tables(&mut archive)?;
+        fobz.read_entries(&mut archive, LoadMode::Eager)?;
+        Ok(fobz)
+    }
 
-            if file_name.starts_with("contents/") && file_name.ends_with(".html")
-                || file_name.ends_with(".xhtml")
-            {
-                let mut content = String::new();
-                file.read_to_string(&mut content)?;
-                contents.insert(file_name, content);
-            } else if file_name.starts_with("resources/")
-                && (file_name.ends_with(".jpg") || file_name.ends_with(".png"))
-            {
-                let mut resource = Vec::new();
-                file.read_to_end(&mut resource)?;
-                resources.insert(file_name, resource);
-            } else if file_name.starts_with("styles/") && file_name.ends_with(".css") {
-                let mut style = String::new();
-                file.read_to_string(&mut style)?;
-                styles.insert(file_name, style);
-            }
+    /// Reads a `.fobz` archive from any seekable reader using the given options.
+    ///
+    /// Entry names are checked as in [`FobZ::from_reader`], and sizes against `options.limits`.
+    ///
+    /// # Parameters
+    /// - `reader`: The source of the `.fobz` archive (e.g., a file or an in-memory buffer).
+    /// - `options`: The options controlling how the archive is read.
+    ///
+    /// # Returns
+    /// A result containing the `FobZ` instance if successful, or an error if any issue occurs.
+    pub fn from_reader_with<R: Read + Seek + Send + 'static>(
+        reader: R,
+        options: &OpenOptions,
+    ) -> Result<Self> {
+        let reader: Box<dyn ReadSeek> = Box::new(reader);
+        let mut archive = archive::open(reader, &options.limits)?;
+        let mut fobz = Self::read_tables(&mut archive)?;
+        fobz.policy = options.resource_policy.clone();
+        fobz.sanitize_policy = options.sanitize_policy.clone();
+        fobz.read_entries(&mut archive, options.mode)?;
+
+        if options.mode == LoadMode::Lazy {
+            fobz.source = Some(ArchiveSource::new(archive));
         }
 
+        Ok(fobz)
+    }
+
+    /// Deserializes the manifest and the tables of an archive into an otherwise empty `FobZ`.
+    ///
+    /// Archives written with an older format version are migrated to the current layout.
+    fn read_tables<R: Read + Seek>(archive: &mut ZipArchive<R>) -> Result<Self> {
+        let Layout {
+            manifest,
+            toc,
+            tor,
+            tos,
+        } = migration::read_layout(archive)?;
+
         Ok(FobZ {
             manifest,
             toc,
             tor,
             tos,
-            contents,
-            resources,
-            styles,
+            contents: HashMap::new(),
+            resources: HashMap::new(),
+            styles: HashMap::new(),
+            policy: ResourcePolicy::default(),
+            source: None,
+            search: None,
+            sanitize_policy: SanitizePolicy::default(),
+            sanitize_report: SanitizeReport::default(),
         })
     }
 
+    /// Categorizes the files of an archive into contents, resources, and styles.
+    ///
+    /// In [`LoadMode::Lazy`] only the entry names are recorded, leaving the payloads unread.
+    fn read_entries<R: Read + Seek>(
+        &mut self,
+        archive: &mut ZipArchive<R>,
+        mode: LoadMode,
+    ) -> Result<()> {
+        for i in 0..archive.len() {
+            let mut file = archive.by_index(i)?;
+            let file_name = file.name().to_string();
+
+            // The search index is small and only useful decoded, so it is read in every mode.
+            if file_name == search::INDEX_ENTRY {
+                let bytes = archive::read_file(&mut file)?;
+                self.search = SearchIndex::from_bytes(&bytes).ok();
+                continue;
+            }
+
+            if file_name.starts_with("contents/")
+                && (file_name.ends_with(".html") || file_name.ends_with(".xhtml"))
+            {
+                // Contents are sanitized when opening, so they cannot wait until requested.
+                let cell = OnceLock::new();
+                if mode == LoadMode::Eager || self.sanitize_policy != SanitizePolicy::Off {
+                    let content = decode_text(&file_name, archive::read_file(&mut file)?)?;
+                    let _ = cell.set(self.sanitize_content(&file_name, content));
+                }
+                self.contents.insert(file_name, cell);
+            } else if file_name.starts_with("resources/") && !file.is_dir() {
+                let mut resource = None;
+                if mode == LoadMode::Eager {
+                    resource = Some(archive::read_file(&mut file)?);
+                }
+
+                // Prefer the declared media type, then the content, then the extension.
+                let media_type = match self.tor.get_mut(&file_name) {
+                    Some(info) if !info.media_type.is_empty() => info.media_type.clone(),
+                    info => {
+                        let media_type = match &resource {
+                            Some(bytes) => media::detect(&file_name, bytes),
+                            None => media::from_extension(&file_name)
+                                .unwrap_or(media::OCTET_STREAM)
+                                .into(),
+                        };
+                        if let Some(info) = info {
+                            info.media_type = media_type.clone();
+                        }
+                        media_type
+                    }
+                };
+
+                // Rejected resources are dropped from the table as well.
+                if !self.policy.allows(&media_type) {
+                    self.tor.remove(&file_name);
+                    continue;
+                }
+
+                let cell = OnceLock::new();
+                if let Some(resource) = resource {
+                    let _ = cell.set(resource);
+                }
+                self.resources.insert(file_name, cell);
+            } else if file_name.starts_with("styles/") && file_name.ends_with(".css") {
+                let cell = OnceLock::new();
+                if mode == LoadMode::Eager {
+                    let style = archive::read_file(&mut file)?;
+                    let _ = cell.set(decode_text(&file_name, style)?);
+                }
+                self.styles.insert(file_name, cell);
+            } else if file_name.starts_with("default/") && !file.is_dir() {
+                // Defaults are not listed in any table, and are told apart as `FobZ::new` does.
+                let bytes = archive::read_file(&mut file)?;
+                if file_name.ends_with(".html") {
+                    let content = decode_text(&file_name, bytes)?;
+                    self.contents.insert(file_name, OnceLock::from(content));
+                } else {
+                    self.resources.insert(file_name, OnceLock::from(bytes));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reads every entry that has not been loaded yet and releases the underlying archive.
+    ///
+    /// Call this before the file a lazily opened document was read from is modified or removed.
+    ///
+    /// # Returns
+    /// A result indicating success or an error if any entry could not be read.
+    pub fn load_all(&mut self) -> Result<()> {
+        for path in self.contents.keys() {
+            self.load(&self.contents, path, decode_text)?;
+        }
+        for path in self.resources.keys() {
+            self.load(&self.resources, path, decode_binary)?;
+        }
+        for path in self.styles.keys() {
+            self.load(&self.styles, path, decode_text)?;
+        }
+
+        self.source = None;
+        Ok(())
+    }
+
+    /// Returns the payload stored at `path`, decompressing it from the archive on first access.
+    ///
+    /// # Returns
+    /// A result containing the payload, `None` if no entry exists at `path`, or an error if the
+    /// entry could not be read or decoded.
+    fn load<'a, T>(
+        &'a self,
+        map: &'a HashMap<String, OnceLock<T>>,
+        path: &str,
+        decode: fn(&str, Vec<u8>) -> Result<T>,
+    ) -> Result<Option<&'a T>> {
+        let Some(cell) = map.get(path) else {
+            return Ok(None);
+        };
+
+        if let Some(value) = cell.get() {
+            return Ok(Some(value));
+        }
+
+        let source = self
+            .source
+            .as_ref()
+            .ok_or_else(|| FobzError::MissingEntry(path.into()))?;
+        let _ = cell.set(decode(path, source.read(path)?)?);
+        Ok(cell.get())
+    }
+
     /// Saves the current `FobZ` instance to a specified file path as a `.fobz` archive.
     ///
+    /// The archive is written to a temporary file next to `path` and renamed over it once
+    /// complete, so an existing file is left untouched if saving fails.
+    ///
+    /// # Parameters
+    /// - `path`: The file path to save the `.fobz` archive.
+    ///
+    /// # Returns
+    /// A result indicating success or an error if any issue occurs during saving.
+    pub fn save_to(&self, path: &str) -> Result<()> {
+        self.save_to_with(path, &SaveOptions::default())
+    }
+
+    /// Saves the current `FobZ` instance to a specified file path using the given options.
+    ///
     /// # Parameters
     /// - `path`: The file path to save the `.fobz` archive.
+    /// - `options`: The options controlling how the archive is written.
     ///
     /// # Returns
     /// A result indicating success or an error if any issue occurs during saving.
-    pub fn save_to(&self, path: &str) -> anyhow::Result<()> {
+    pub fn save_to_with(&self, path: &str, options: &SaveOptions) -> Result<()> {
         let path = if path.ends_with(".fobz") {
             path.into()
         } else {
             format!("{}.fobz", path)
         };
 
-        let file = File::create(path)?;
-        let mut zip = ZipWriter::new(file);
+        self.overwrite_with(&path, options)
+    }
 
-        let options: FileOptions<'_, ExtendedFileOptions> =
-            FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    /// Saves the current `FobZ` instance to exactly `path`, whatever its extension.
+    ///
+    /// Unlike [`FobZ::save_to`], no `.fobz` extension is appended, so a document opened from any
+    /// file can be written back to it. The file is replaced atomically, as with `save_to`.
+    ///
+    /// # Parameters
+    /// - `path`: The file to create or replace.
+    ///
+    /// # Returns
+    /// A result indicating success or an error if any issue occurs during saving.
+    pub fn overwrite(&self, path: &str) -> Result<()> {
+        self.overwrite_with(path, &SaveOptions::default())
+    }
 
-        // Write the metadata files to the archive.
-        zip.start_file("manifest.json", options.clone())?;
-        zip.write_all(serde_json::to_string_pretty(&self.manifest)?.as_bytes())?;
+    /// Saves the current `FobZ` instance to exactly `path` using the given options.
+    ///
+    /// # Parameters
+    /// - `path`: The file to create or replace.
+    /// - `options`: The options controlling how the archive is written.
+    ///
+    /// # Returns
+    /// A result indicating success or an error if any issue occurs during saving.
+    pub fn overwrite_with(&self, path: &str, options: &SaveOptions) -> Result<()> {
+        atomic::write_atomically(Path::new(path), |file| {
+            self.write_to_with(file, options)?;
+            Ok(())
+        })
+    }
 
-        zip.start_file("toc.json", options.clone())?;
-        zip.write_all(serde_json::to_string_pretty(&self.toc)?.as_bytes())?;
+    /// Serializes the current `FobZ` instance into an in-memory `.fobz` archive.
+    ///
+    /// # Returns
+    /// A result containing the bytes of the archive, or an error if any issue occurs during writing.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        Ok(self.write_to(Cursor::new(Vec::new()))?.into_inner())
+    }
+
+    /// Writes the current `FobZ` instance as a `.fobz` archive to any seekable writer.
+    ///
+    /// # Parameters
+    /// - `writer`: The destination of the archive (e.g., a file or an in-memory buffer).
+    ///
+    /// # Returns
+    /// A result containing the writer once the archive is finished, or an error if any issue occurs.
+    pub fn write_to<W: Write + Seek>(&self, writer: W) -> Result<W> {
+        self.write_to_with(writer, &SaveOptions::default())
+    }
+
+    /// Writes the current `FobZ` instance as a `.fobz` archive to any seekable writer using the
+    /// given options.
+    ///
+    /// The output only depends on the document and the options: the manifest and the tables come
+    /// first, followed by every other entry sorted by path, all with the same modification time.
+    ///
+    /// # Parameters
+    /// - `writer`: The destination of the archive (e.g., a file or an in-memory buffer).
+    /// - `options`: The options controlling how the archive is written.
+    ///
+    /// # Returns
+    /// A result containing the writer once the archive is finished,
+    /// `FobzError::UnsupportedVersion` if the document was read from a newer format version,
+    /// `FobzError::InvalidCompressionLevel` if the compression policy has a level out of range,
+    /// or an error if any issue occurs.
+    pub fn write_to_with<W: Write + Seek>(&self, writer: W, options: &SaveOptions) -> Result<W> {
+        migration::check_writable(self.manifest.get_version())?;
+        options.compression.check()?;
 
-        zip.start_file("tor.json", options.clone())?;
-        zip.write_all(serde_json::to_string_pretty(&self.tor)?.as_bytes())?;
+        let mut zip = ZipWriter::new(writer);
 
-        zip.start_file("tos.json", options.clone())?;
-        zip.write_all(serde_json::to_string_pretty(&self.tos)?.as_bytes())?;
+        let file_options: FileOptions<'_, ExtendedFileOptions> = FileOptions::default()
+            .last_modified_time(options.last_modified)
+            .unix_permissions(0o644);
+        let compression = &options.compression;
+
+        // Write the metadata files to the archive.
+        let json_options = |path| {
+            compression
+                .compression_for(path, EntryKind::Metadata, "application/json")
+                .apply(file_options.clone())
+        };
+        write_json(
+            &mut zip,
+            "manifest.json",
+            &self.manifest,
+            json_options("manifest.json"),
+        )?;
+        write_json(&mut zip, "toc.json", &self.toc, json_options("toc.json"))?;
+        write_json(&mut zip, "tor.json", &self.tor, json_options("tor.json"))?;
+        write_json(&mut zip, "tos.json", &self.tos, json_options("tos.json"))?;
 
         // Create directories in the archive.
-        zip.add_directory("contents", options.clone())?;
-        zip.add_directory("resources", options.clone())?;
-        zip.add_directory("styles", options.clone())?;
-        zip.add_directory("default", options.clone())?;
-
-        // Write content files to the archive.
-        for (path, content) in self.contents.iter() {
-            zip.start_file(path, options.clone())?;
-            zip.write_all(content.as_bytes())?;
+        let dir_options = Compression::Stored
+            .apply(file_options.clone())
+            .unix_permissions(0o755);
+        zip.add_directory("contents", dir_options.clone())?;
+        zip.add_directory("resources", dir_options.clone())?;
+        zip.add_directory("styles", dir_options.clone())?;
+        zip.add_directory("default", dir_options)?;
+
+        // Gather contents, resources, styles, and the search index so they can be written sorted
+        // by path.
+        let index = self.search.as_ref().map(SearchIndex::to_bytes);
+        let mut entries: Vec<(&str, EntryKind, &str, &[u8])> = Vec::new();
+
+        if let Some(index) = &index {
+            entries.push((
+                search::INDEX_ENTRY,
+                EntryKind::Metadata,
+                media::OCTET_STREAM,
+                index,
+            ));
         }
 
-        // Write resource files to the archive.
-        for (path, resource) in self.resources.iter() {
-            zip.start_file(path, options.clone())?;
-            zip.write_all(resource)?;
+        for path in self.contents.keys() {
+            if let Some(content) = self.load(&self.contents, path, decode_text)? {
+                let media_type = if path.ends_with(".xhtml") {
+                    "application/xhtml+xml"
+                } else {
+                    "text/html"
+                };
+                entries.push((path, EntryKind::Content, media_type, content.as_bytes()));
+            }
         }
 
-        // Write style files to the archive.
-        for (path, style) in self.styles.iter() {
-            zip.start_file(path, options.clone())?;
-            zip.write_all(style.as_bytes())?;
+        for path in self.resources.keys() {
+            if let Some(resource) = self.load(&self.resources, path, decode_binary)? {
+                let media_type = match self.tor.get(path) {
+                    Some(info) => info.media_type.as_str(),
+                    None => media::from_extension(path).unwrap_or(media::OCTET_STREAM),
+                };
+                entries.push((path, EntryKind::Resource, media_type, resource));
+            }
         }
 
-        zip.finish()?;
-        Ok(())
+        for path in self.styles.keys() {
+            if let Some(style) = self.load(&self.styles, path, decode_text)? {
+                entries.push((path, EntryKind::Style, "text/css", style.as_bytes()));
+            }
+        }
+
+        entries.sort_unstable_by_key(|(path, ..)| *path);
+
+        for (path, kind, media_type, bytes) in entries {
+            let method = compression.compression_for(path, kind, media_type);
+            zip.start_file(path, method.apply(file_options.clone()))?;
+            zip.write_all(bytes)?;
+        }
+
+        Ok(zip.finish()?)
     }
 }
 
@@ -339,40 +836,334 @@ impl FobZ {
     /// Adds a new content section to the document.
     ///
     /// # Parameters
-    /// - `path`: The file path of the content (must end with `.html`).
+    /// - `path`: The file path of the content (must be in `contents/` and end with `.html` or `.xhtml`).
     /// - `title`: The title of the content section.
     /// - `content`: The HTML content of the section.
-    pub fn add_content(&mut self, path: String, title: String, content: String) {
-        if !path.ends_with(".html") {
-            return;
+    ///
+    /// # Returns
+    /// A result indicating success, `FobzError::InvalidPath` if the path is not acceptable, or
+    /// `FobzError::DuplicatePath` if a section already exists at this path.
+    pub fn add_content(&mut self, path: String, title: String, content: String) -> Result<()> {
+        self.check_new_content(&path)?;
+
+        let content = self.sanitize_content(&path, content);
+        self.contents.insert(path.clone(), OnceLock::from(content));
+        self.toc.add(ContentInfo::new(path, title));
+        self.search = None;
+        Ok(())
+    }
+
+    /// Adds a content section, or replaces the title and content of the existing one at `path`.
+    ///
+    /// A replaced section keeps its position in the table of contents.
+    ///
+    /// # Parameters
+    /// - `path`: The file path of the content (must be in `contents/` and end with `.html` or `.xhtml`).
+    /// - `title`: The title of the content section.
+    /// - `content`: The HTML content of the section.
+    ///
+    /// # Returns
+    /// A result indicating success, or `FobzError::InvalidPath` if the path is not acceptable.
+    pub fn upsert_content(&mut self, path: String, title: String, content: String) -> Result<()> {
+        check_path(&path, "contents/", CONTENT_EXTENSIONS)?;
+
+        let content = self.sanitize_content(&path, content);
+        self.contents.insert(path.clone(), OnceLock::from(content));
+        match self.toc.get_mut(&path) {
+            Some(info) => info.title = title,
+            None => self.toc.add(ContentInfo::new(path, title)),
+        }
+        self.search = None;
+        Ok(())
+    }
+
+    /// Inserts a new content section at the given position in the table of contents.
+    ///
+    /// # Parameters
+    /// - `index`: The position of the new section (at most the number of sections).
+    /// - `path`: The file path of the content (must be in `contents/` and end with `.html` or `.xhtml`).
+    /// - `title`: The title of the content section.
+    /// - `content`: The HTML content of the section.
+    ///
+    /// # Returns
+    /// A result indicating success, `FobzError::InvalidPath` if the path is not acceptable,
+    /// `FobzError::DuplicatePath` if a section already exists at this path, or
+    /// `FobzError::IndexOutOfBounds` if `index` is past the end.
+    pub fn insert_content_at(
+        &mut self,
+        index: usize,
+        path: String,
+        title: String,
+        content: String,
+    ) -> Result<()> {
+        self.check_new_content(&path)?;
+
+        self.toc
+            .insert_at(index, ContentInfo::new(path.clone(), title))?;
+        let content = self.sanitize_content(&path, content);
+        self.contents.insert(path, OnceLock::from(content));
+        self.search = None;
+        Ok(())
+    }
+
+    /// Checks that a new content section can be stored at `path`.
+    fn check_new_content(&self, path: &str) -> Result<()> {
+        check_path(path, "contents/", CONTENT_EXTENSIONS)?;
+        if self.toc.get(path).is_some() || self.contents.contains_key(path) {
+            return Err(FobzError::DuplicatePath(path.into()));
         }
+        Ok(())
+    }
 
-        self.contents.insert(path.clone(), content);
-        self.toc.add(ContentInfo { path, title });
+    /// Moves a content section to the given position in the table of contents.
+    ///
+    /// # Parameters
+    /// - `path`: The file path of the content section to move.
+    /// - `index`: The position of the section once moved.
+    ///
+    /// # Returns
+    /// A result indicating success or an error as described in [`TableOfContents::move_to`].
+    pub fn move_content(&mut self, path: &str, index: usize) -> Result<()> {
+        self.toc.move_to(path, index)?;
+        self.search = None;
+        Ok(())
+    }
+
+    /// Swaps the positions of two content sections in the table of contents.
+    ///
+    /// # Parameters
+    /// - `a`: The file path of the first content section.
+    /// - `b`: The file path of the second content section.
+    ///
+    /// # Returns
+    /// A result indicating success or an error as described in [`TableOfContents::swap`].
+    pub fn swap_contents(&mut self, a: &str, b: &str) -> Result<()> {
+        self.toc.swap(a, b)?;
+        self.search = None;
+        Ok(())
+    }
+
+    /// Reorders every content section to follow the given list of paths.
+    ///
+    /// # Parameters
+    /// - `paths`: The file paths of all the content sections, in their new order.
+    ///
+    /// # Returns
+    /// A result indicating success or an error as described in [`TableOfContents::reorder`].
+    pub fn reorder_contents(&mut self, paths: &[String]) -> Result<()> {
+        self.toc.reorder(paths)?;
+        self.search = None;
+        Ok(())
+    }
+
+    /// Adds a new content section nested under an existing section.
+    ///
+    /// # Parameters
+    /// - `parent`: The reference of the parent section (`path` or `path#anchor`).
+    /// - `path`: The file path of the content (must be in `contents/` and end with `.html` or `.xhtml`).
+    /// - `title`: The title of the content section.
+    /// - `content`: The HTML content of the section.
+    ///
+    /// # Returns
+    /// A result indicating success, `FobzError::InvalidPath` if the path is not acceptable,
+    /// `FobzError::DuplicatePath` if a section already exists at this path, or
+    /// `FobzError::UnknownPath` if the parent does not exist.
+    pub fn add_child_content(
+        &mut self,
+        parent: &str,
+        path: String,
+        title: String,
+        content: String,
+    ) -> Result<()> {
+        self.check_new_content(&path)?;
+
+        self.toc
+            .add_child(parent, ContentInfo::new(path.clone(), title))?;
+        let content = self.sanitize_content(&path, content);
+        self.contents.insert(path, OnceLock::from(content));
+        self.search = None;
+        Ok(())
+    }
+
+    /// Adds a section pointing at a fragment of an existing content file.
+    ///
+    /// # Parameters
+    /// - `parent`: The reference of the parent section, or `None` to add a top-level section.
+    /// - `path`: The file path of the existing content.
+    /// - `anchor`: The `id` of the element the section starts at.
+    /// - `title`: The title of the section.
+    ///
+    /// # Returns
+    /// A result indicating success, `FobzError::UnknownPath` if the content or the parent does
+    /// not exist, or `FobzError::DuplicatePath` if a section already points at this fragment.
+    pub fn add_anchor(
+        &mut self,
+        parent: Option<&str>,
+        path: String,
+        anchor: String,
+        title: String,
+    ) -> Result<()> {
+        if !self.contents.contains_key(&path) {
+            return Err(FobzError::UnknownPath(path));
+        }
+
+        let info = ContentInfo::with_anchor(path, anchor, title);
+        match parent {
+            Some(parent) => self.toc.add_child(parent, info)?,
+            None => {
+                let len = self.toc.len();
+                self.toc.insert_at(len, info)?
+            }
+        }
+        self.search = None;
+        Ok(())
     }
 
     /// Removes a content section from the document.
     ///
+    /// Given a file path, the content is deleted along with every section pointing into it.
+    /// Given a reference with a fragment (`path#anchor`), only that section is removed. Nested
+    /// sections take the place of removed ones.
+    ///
     /// # Parameters
-    /// - `path`: The file path of the content section to remove.
+    /// - `path`: The file path of the content section to remove, or the reference of a section.
     pub fn remove_content(&mut self, path: String) {
-        self.contents.remove_entry(&path);
+        if let (path, None) = split_href(&path) {
+            self.contents.remove_entry(path);
+        }
         self.toc.remove(&path);
+        self.search = None;
+    }
+
+    /// Adds a new resource to the document, detecting its media type from its content.
+    ///
+    /// # Parameters
+    /// - `path`: The file path of the resource (must be in `resources/`).
+    /// - `name`: The descriptive name of the resource.
+    /// - `resource`: The binary data of the resource.
+    ///
+    /// # Returns
+    /// A result indicating success, or an error as described in [`FobZ::add_resource_with_type`].
+    pub fn add_resource(&mut self, path: String, name: String, resource: Vec<u8>) -> Result<()> {
+        let media_type = media::detect(&path, &resource);
+        self.add_resource_with_type(path, name, media_type, resource)
+    }
+
+    /// Adds a new resource to the document with an explicit media type.
+    ///
+    /// # Parameters
+    /// - `path`: The file path of the resource (must be in `resources/`).
+    /// - `name`: The descriptive name of the resource.
+    /// - `media_type`: The MIME type of the resource (must be allowed by the resource policy).
+    /// - `resource`: The binary data of the resource.
+    ///
+    /// # Returns
+    /// A result indicating success, `FobzError::InvalidPath` if the path is not acceptable,
+    /// `FobzError::RejectedMediaType` if the resource policy refuses the media type, or
+    /// `FobzError::DuplicatePath` if a resource already exists at this path.
+    pub fn add_resource_with_type(
+        &mut self,
+        path: String,
+        name: String,
+        media_type: String,
+        resource: Vec<u8>,
+    ) -> Result<()> {
+        self.check_resource(&path, &media_type)?;
+        if self.tor.get(&path).is_some() || self.resources.contains_key(&path) {
+            return Err(FobzError::DuplicatePath(path));
+        }
+
+        self.resources
+            .insert(path.clone(), OnceLock::from(resource));
+        self.tor.add(ResourceInfo {
+            path,
+            name,
+            media_type,
+        });
+        Ok(())
     }
 
-    /// Adds a new resource to the document.
+    /// Adds a resource, or replaces the name and data of the existing one at `path`, detecting
+    /// its media type from its content.
+    ///
+    /// # Parameters
+    /// - `path`: The file path of the resource (must be in `resources/`).
+    /// - `name`: The descriptive name of the resource.
+    /// - `resource`: The binary data of the resource.
+    ///
+    /// # Returns
+    /// A result indicating success, or an error as described in [`FobZ::upsert_resource_with_type`].
+    pub fn upsert_resource(&mut self, path: String, name: String, resource: Vec<u8>) -> Result<()> {
+        let media_type = media::detect(&path, &resource);
+        self.upsert_resource_with_type(path, name, media_type, resource)
+    }
+
+    /// Adds a resource, or replaces the name, media type, and data of the existing one at `path`.
+    ///
+    /// A replaced resource keeps its position in the table of resources.
     ///
     /// # Parameters
-    /// - `path`: The file path of the resource (must end with `.jpg` or `.png`).
+    /// - `path`: The file path of the resource (must be in `resources/`).
     /// - `name`: The descriptive name of the resource.
+    /// - `media_type`: The MIME type of the resource (must be allowed by the resource policy).
     /// - `resource`: The binary data of the resource.
-    pub fn add_resource(&mut self, path: String, name: String, resource: Vec<u8>) {
-        if !path.ends_with(".jpg") && !path.ends_with(".png") {
-            return;
+    ///
+    /// # Returns
+    /// A result indicating success, `FobzError::InvalidPath` if the path is not acceptable, or
+    /// `FobzError::RejectedMediaType` if the resource policy refuses the media type.
+    pub fn upsert_resource_with_type(
+        &mut self,
+        path: String,
+        name: String,
+        media_type: String,
+        resource: Vec<u8>,
+    ) -> Result<()> {
+        self.check_resource(&path, &media_type)?;
+
+        self.resources
+            .insert(path.clone(), OnceLock::from(resource));
+        match self.tor.get_mut(&path) {
+            Some(info) => {
+                info.name = name;
+                info.media_type = media_type;
+            }
+            None => self.tor.add(ResourceInfo {
+                path,
+                name,
+                media_type,
+            }),
+        }
+        Ok(())
+    }
+
+    /// Checks that a resource can be stored at `path` under the current resource policy.
+    fn check_resource(&self, path: &str, media_type: &str) -> Result<()> {
+        check_path(path, "resources/", &[])?;
+        if !self.policy.allows(media_type) {
+            return Err(FobzError::RejectedMediaType {
+                path: path.into(),
+                media_type: media_type.into(),
+            });
         }
+        Ok(())
+    }
 
-        self.resources.insert(path.clone(), resource);
-        self.tor.add(ResourceInfo { path, name });
+    /// Retrieves the policy deciding which resources the document accepts.
+    ///
+    /// # Returns
+    /// A reference to the `ResourcePolicy`.
+    pub fn get_resource_policy(&self) -> &ResourcePolicy {
+        &self.policy
+    }
+
+    /// Sets the policy deciding which resources the document accepts.
+    ///
+    /// Resources already in the document are kept.
+    ///
+    /// # Parameters
+    /// - `policy`: The new resource policy.
+    pub fn set_resource_policy(&mut self, policy: ResourcePolicy) {
+        self.policy = policy;
     }
 
     /// Removes a resource from the document.
@@ -387,15 +1178,39 @@ impl FobZ {
     /// Adds a new stylesheet to the document.
     ///
     /// # Parameters
-    /// - `path`: The file path of the stylesheet (must end with `.css`).
+    /// - `path`: The file path of the stylesheet (must be in `styles/` and end with `.css`).
     /// - `style`: The CSS content of the stylesheet.
-    pub fn add_style(&mut self, path: String, style: String) {
-        if !path.ends_with(".css") {
-            return;
+    ///
+    /// # Returns
+    /// A result indicating success, `FobzError::InvalidPath` if the path is not acceptable, or
+    /// `FobzError::DuplicatePath` if a stylesheet already exists at this path.
+    pub fn add_style(&mut self, path: String, style: String) -> Result<()> {
+        check_path(&path, "styles/", STYLE_EXTENSIONS)?;
+        if self.tos.get(&path).is_some() || self.styles.contains_key(&path) {
+            return Err(FobzError::DuplicatePath(path));
         }
 
-        self.styles.insert(path.clone(), style);
+        self.styles.insert(path.clone(), OnceLock::from(style));
         self.tos.add(StyleInfo { path });
+        Ok(())
+    }
+
+    /// Adds a stylesheet, or replaces the content of the existing one at `path`.
+    ///
+    /// # Parameters
+    /// - `path`: The file path of the stylesheet (must be in `styles/` and end with `.css`).
+    /// - `style`: The CSS content of the stylesheet.
+    ///
+    /// # Returns
+    /// A result indicating success, or `FobzError::InvalidPath` if the path is not acceptable.
+    pub fn upsert_style(&mut self, path: String, style: String) -> Result<()> {
+        check_path(&path, "styles/", STYLE_EXTENSIONS)?;
+
+        self.styles.insert(path.clone(), OnceLock::from(style));
+        if self.tos.get(&path).is_none() {
+            self.tos.add(StyleInfo { path });
+        }
+        Ok(())
     }
 
     /// Removes a stylesheet from the document.
@@ -417,32 +1232,77 @@ impl FobZ {
         &self.manifest
     }
 
+    /// Retrieves a mutable reference to the document's manifest.
+    ///
+    /// # Returns
+    /// A mutable reference to the `Manifest`.
+    pub fn get_manifest_mut(&mut self) -> &mut Manifest {
+        &mut self.manifest
+    }
+
+    /// Retrieves a reference to the document's table of contents.
+    ///
+    /// # Returns
+    /// A reference to the `TableOfContents`, listing the sections in reading order.
+    pub fn get_toc(&self) -> &TableOfContents {
+        &self.toc
+    }
+
+    /// Retrieves a reference to the document's table of resources.
+    ///
+    /// # Returns
+    /// A reference to the `TableOfResources`.
+    pub fn get_tor(&self) -> &TableOfResources {
+        &self.tor
+    }
+
+    /// Retrieves a reference to the document's table of styles.
+    ///
+    /// # Returns
+    /// A reference to the `TableOfStyles`.
+    pub fn get_tos(&self) -> &TableOfStyles {
+        &self.tos
+    }
+
     /// Retrieves information about a specific content section.
     ///
     /// # Parameters
-    /// - `path`: The file path of the content section.
+    /// - `path`: The file path of the content section, or a reference with a fragment (`path#anchor`).
     ///
     /// # Returns
     /// An optional reference to `ContentInfo` if found, otherwise `None`.
-    pub fn get_content_info(&self, path: &String) -> Option<&ContentInfo> {
+    pub fn get_content_info(&self, path: &str) -> Option<&ContentInfo> {
         self.toc.get(path)
     }
 
     /// Retrieves both the `ContentInfo` and the content string for a specific path.
     ///
     /// # Parameters
-    /// - `path`: The file path of the content section.
+    /// - `path`: The file path of the content section, or a reference with a fragment (`path#anchor`).
     ///
     /// # Returns
     /// An optional tuple containing `ContentInfo` and content string if found, otherwise `None`.
-    pub fn get_content(&self, path: &String) -> Option<(&ContentInfo, &String)> {
-        match self.get_content_info(&path) {
-            Some(content_info) => match self.contents.get(path) {
-                Some(content) => Some((content_info, content)),
-                None => None,
-            },
-            None => None,
-        }
+    /// A content that could not be read from a lazily opened archive is `None` as well; see
+    /// [`FobZ::try_get_content`].
+    pub fn get_content(&self, path: &str) -> Option<(&ContentInfo, &String)> {
+        self.try_get_content(path).ok().flatten()
+    }
+
+    /// Retrieves both the `ContentInfo` and the content string for a specific path, reporting
+    /// read errors.
+    ///
+    /// # Parameters
+    /// - `path`: The file path of the content section, or a reference with a fragment (`path#anchor`).
+    ///
+    /// # Returns
+    /// A result containing the `ContentInfo` and content string if found, `None` otherwise, or an
+    /// error if the content could not be read or decoded.
+    pub fn try_get_content(&self, path: &str) -> Result<Option<(&ContentInfo, &String)>> {
+        let Some(content_info) = self.get_content_info(path) else {
+            return Ok(None);
+        };
+        let content = self.load(&self.contents, &content_info.path, decode_text)?;
+        Ok(content.map(|content| (content_info, content)))
     }
 
     /// Retrieves information about a specific resource.
@@ -452,8 +1312,8 @@ impl FobZ {
     ///
     /// # Returns
     /// An optional reference to `ResourceInfo` if found, otherwise `None`.
-    pub fn get_resource_info(&self, path: &String) -> Option<&ResourceInfo> {
-        self.tor.get(&path)
+    pub fn get_resource_info(&self, path: &str) -> Option<&ResourceInfo> {
+        self.tor.get(path)
     }
 
     /// Retrieves both the `ResourceInfo` and the resource data for a specific path.
@@ -463,14 +1323,27 @@ impl FobZ {
     ///
     /// # Returns
     /// An optional tuple containing `ResourceInfo` and resource data if found, otherwise `None`.
-    pub fn get_resource(&self, path: &String) -> Option<(&ResourceInfo, &Vec<u8>)> {
-        match self.get_resource_info(&path) {
-            Some(content_info) => match self.resources.get(path) {
-                Some(resource) => Some((content_info, resource)),
-                None => None,
-            },
-            None => None,
-        }
+    /// A resource that could not be read from a lazily opened archive is `None` as well; see
+    /// [`FobZ::try_get_resource`].
+    pub fn get_resource(&self, path: &str) -> Option<(&ResourceInfo, &Vec<u8>)> {
+        self.try_get_resource(path).ok().flatten()
+    }
+
+    /// Retrieves both the `ResourceInfo` and the resource data for a specific path, reporting
+    /// read errors.
+    ///
+    /// # Parameters
+    /// - `path`: The file path of the resource.
+    ///
+    /// # Returns
+    /// A result containing the `ResourceInfo` and resource data if found, `None` otherwise, or an
+    /// error if the resource could not be read.
+    pub fn try_get_resource(&self, path: &str) -> Result<Option<(&ResourceInfo, &Vec<u8>)>> {
+        let Some(resource_info) = self.get_resource_info(path) else {
+            return Ok(None);
+        };
+        let resource = self.load(&self.resources, path, decode_binary)?;
+        Ok(resource.map(|resource| (resource_info, resource)))
     }
 
     /// Retrieves information about a specific stylesheet.
@@ -480,8 +1353,8 @@ impl FobZ {
     ///
     /// # Returns
     /// An optional reference to `StyleInfo` if found, otherwise `None`.
-    pub fn get_style_info(&self, path: &String) -> Option<&StyleInfo> {
-        self.tos.get(&path)
+    pub fn get_style_info(&self, path: &str) -> Option<&StyleInfo> {
+        self.tos.get(path)
     }
 
     /// Retrieves both the `StyleInfo` and the stylesheet content for a specific path.
@@ -491,13 +1364,26 @@ impl FobZ {
     ///
     /// # Returns
     /// An optional tuple containing `StyleInfo` and stylesheet content if found, otherwise `None`.
-    pub fn get_style(&self, path: &String) -> Option<(&StyleInfo, &String)> {
-        match self.get_style_info(&path) {
-            Some(content_info) => match self.styles.get(path) {
-                Some(content) => Some((content_info, content)),
-                None => None,
-            },
-            None => None,
-        }
+    /// A stylesheet that could not be read from a lazily opened archive is `None` as well; see
+    /// [`FobZ::try_get_style`].
+    pub fn get_style(&self, path: &str) -> Option<(&StyleInfo, &String)> {
+        self.try_get_style(path).ok().flatten()
+    }
+
+    /// Retrieves both the `StyleInfo` and the stylesheet content for a specific path, reporting
+    /// read errors.
+    ///
+    /// # Parameters
+    /// - `path`: The file path of the stylesheet.
+    ///
+    /// # Returns
+    /// A result containing the `StyleInfo` and stylesheet content if found, `None` otherwise, or
+    /// an error if the stylesheet could not be read or decoded.
+    pub fn try_get_style(&self, path: &str) -> Result<Option<(&StyleInfo, &String)>> {
+        let Some(style_info) = self.get_style_info(path) else {
+            return Ok(None);
+        };
+        let style = self.load(&self.styles, path, decode_text)?;
+        Ok(style.map(|style| (style_info, style)))
     }
 }
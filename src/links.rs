@@ -0,0 +1,255 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use crate::{
+    decode_text,
+    error::Result,
+    html::{self, Token},
+    refs::{self, Reference},
+    FobZ,
+};
+
+/// Identifies why a reference is broken.
+///
+/// # Variants
+/// - `MissingTarget`: The referenced file is not in the document.
+/// - `MissingFragment`: The referenced content has no element with the `#fragment` id.
+/// - `OutsideArchive`: The reference climbs above the root of the archive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkProblem {
+    MissingTarget,
+    MissingFragment,
+    OutsideArchive,
+}
+
+/// Represents a reference of a content or stylesheet that does not lead anywhere.
+///
+/// # Fields
+/// - `source`: The path of the content or stylesheet containing the reference.
+/// - `line`: The line of the reference in its source, starting at 1.
+/// - `reference`: The reference as written, with HTML character references decoded.
+/// - `target`: The path of the file the reference resolves to, or `None` if it leaves the archive.
+/// - `problem`: Why the reference is broken.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrokenLink {
+    pub source: String,
+    pub line: usize,
+    pub reference: String,
+    pub target: Option<String>,
+    pub problem: LinkProblem,
+}
+
+impl fmt::Display for BrokenLink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self.problem {
+            LinkProblem::MissingTarget => "no such file",
+            LinkProblem::MissingFragment => "no element with this id",
+            LinkProblem::OutsideArchive => "points outside the archive",
+        };
+        write!(
+            f,
+            "{}:{}: broken reference '{}': {}",
+            self.source, self.line, self.reference, message
+        )
+    }
+}
+
+/// Represents a reference of a content or stylesheet pointing outside the document.
+///
+/// # Fields
+/// - `source`: The path of the content or stylesheet containing the reference.
+/// - `line`: The line of the reference in its source, starting at 1.
+/// - `url`: The reference as written, with HTML character references decoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalLink {
+    pub source: String,
+    pub line: usize,
+    pub url: String,
+}
+
+/// Collects the results of a link check.
+///
+/// # Fields
+/// - `broken`: The broken references, by source path and then by position.
+/// - `external`: The references pointing outside the document, in the same order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LinkReport {
+    broken: Vec<BrokenLink>,
+    external: Vec<ExternalLink>,
+}
+
+impl LinkReport {
+    /// Returns every broken reference found.
+    pub fn broken(&self) -> &[BrokenLink] {
+        &self.broken
+    }
+
+    /// Returns every reference pointing outside the document.
+    pub fn external(&self) -> &[ExternalLink] {
+        &self.external
+    }
+
+    /// Checks whether every reference inside the document leads to an existing file and element.
+    ///
+    /// # Returns
+    /// `true` if no reference is broken, otherwise `false`. External links are not checked.
+    pub fn is_ok(&self) -> bool {
+        self.broken.is_empty()
+    }
+}
+
+impl FobZ {
+    /// Checks the references of every content and stylesheet of the document.
+    ///
+    /// Link attributes (`href`, `src`, ...), `url()`s, and `@import`s are resolved against the
+    /// layout of the archive, and must lead to a content, resource, or stylesheet of the
+    /// document. A `#fragment` pointing into a content must match the `id` of one of its elements
+    /// (or the `name` of an `<a>`). References with a scheme (e.g., `https:` or `mailto:`) are
+    /// listed as external links without being checked, except `data:` URLs, which embed their
+    /// target.
+    ///
+    /// # Returns
+    /// A result containing the `LinkReport`, or an error if a content or stylesheet could not be
+    /// read.
+    pub fn check_links(&self) -> Result<LinkReport> {
+        let mut sources = vec![];
+        for path in self.contents.keys() {
+            if let Some(content) = self.load(&self.contents, path, decode_text)? {
+                sources.push((path.as_str(), content.as_str(), true));
+            }
+        }
+        for path in self.styles.keys() {
+            if let Some(style) = self.load(&self.styles, path, decode_text)? {
+                sources.push((path.as_str(), style.as_str(), false));
+            }
+        }
+        sources.sort_unstable_by_key(|(path, ..)| *path);
+
+        // The contents by path, and the ids of those a fragment points into.
+        let texts: HashMap<&str, &str> = sources
+            .iter()
+            .filter(|(.., is_html)| *is_html)
+            .map(|(path, text, _)| (*path, *text))
+            .collect();
+        let mut ids: HashMap<&str, HashSet<String>> = HashMap::new();
+
+        let mut report = LinkReport::default();
+
+        for (source, text, is_html) in sources {
+            let references = if is_html {
+                refs::html_references(text)
+            } else {
+                refs::css_references(text)
+            };
+            let mut lines = Lines::new(text);
+
+            for reference in references {
+                let value = if is_html {
+                    html::unescape(reference.value(text))
+                } else {
+                    reference.value(text).to_string()
+                };
+                let line = lines.line_of(&reference);
+
+                if value.is_empty() || value.starts_with("data:") {
+                    continue;
+                }
+                if refs::is_external(&value) {
+                    report.external.push(ExternalLink {
+                        source: source.into(),
+                        line,
+                        url: value,
+                    });
+                    continue;
+                }
+
+                let (path, fragment) = refs::split_reference(&value);
+                let target = if path.is_empty() {
+                    Some(source.to_string())
+                } else {
+                    refs::resolve(source, &path)
+                };
+                let problem = match &target {
+                    None => Some(LinkProblem::OutsideArchive),
+                    Some(target) if self.contents.contains_key(target) => {
+                        fragment.filter(|v| !v.is_empty()).and_then(|fragment| {
+                            let (target, content) = texts.get_key_value(target.as_str())?;
+                            let ids = ids.entry(target).or_insert_with(|| element_ids(content));
+                            let decoded = refs::percent_decode(fragment);
+                            (!ids.contains(fragment) && !ids.contains(&decoded))
+                                .then_some(LinkProblem::MissingFragment)
+                        })
+                    }
+                    Some(target)
+                        if self.resources.contains_key(target)
+                            || self.styles.contains_key(target) =>
+                    {
+                        None
+                    }
+                    Some(_) => Some(LinkProblem::MissingTarget),
+                };
+
+                if let Some(problem) = problem {
+                    report.broken.push(BrokenLink {
+                        source: source.into(),
+                        line,
+                        reference: value,
+                        target,
+                        problem,
+                    });
+                }
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+/// Tracks the line numbers of increasing byte offsets of a text.
+struct Lines<'a> {
+    text: &'a str,
+    offset: usize,
+    line: usize,
+}
+
+impl<'a> Lines<'a> {
+    fn new(text: &'a str) -> Self {
+        Lines {
+            text,
+            offset: 0,
+            line: 1,
+        }
+    }
+
+    /// Returns the line where a reference starts; references must come in order.
+    fn line_of(&mut self, reference: &Reference) -> usize {
+        self.line += self.text[self.offset..reference.start]
+            .matches('\n')
+            .count();
+        self.offset = reference.start;
+        self.line
+    }
+}
+
+/// Lists the `id`s of the elements of an HTML document, and the `name`s of its `<a>` elements.
+fn element_ids(content: &str) -> HashSet<String> {
+    let mut ids = HashSet::new();
+
+    for token in html::tokenize(content) {
+        let Token::Start { name, .. } = &token else {
+            continue;
+        };
+        if let Some(id) = token.attribute("id") {
+            ids.insert(html::unescape(id));
+        }
+        if name == "a" {
+            if let Some(id) = token.attribute("name") {
+                ids.insert(html::unescape(id));
+            }
+        }
+    }
+
+    ids
+}
@@ -1,15 +1,116 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
+use crate::migration::CURRENT_VERSION;
+
+/// The part a person took in the making of the document.
+///
+/// # Variants
+/// - `Author`: Wrote the document.
+/// - `Editor`: Edited the document.
+/// - `Translator`: Translated the document from another language.
+/// - `Illustrator`: Drew the illustrations of the document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Author,
+    Editor,
+    Translator,
+    Illustrator,
+}
+
+/// A person credited in the document's metadata.
+///
+/// # Fields
+/// - `name`: The name of the person.
+/// - `role`: The part the person took in the making of the document.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Contributor {
+    pub name: String,
+    pub role: Role,
+}
+
+impl Contributor {
+    /// Creates a new `Contributor`.
+    ///
+    /// # Parameters
+    /// - `name`: The name of the person.
+    /// - `role`: The part the person took in the making of the document.
+    pub fn new(name: String, role: Role) -> Self {
+        Contributor { name, role }
+    }
+}
+
+/// A unique identifier of the document, stored as `{ "scheme": "isbn", "value": "..." }`.
+///
+/// # Variants
+/// - `Isbn`: An International Standard Book Number.
+/// - `Doi`: A Digital Object Identifier.
+/// - `Uuid`: A Universally Unique Identifier.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "scheme", content = "value", rename_all = "lowercase")]
+pub enum Identifier {
+    Isbn(String),
+    Doi(String),
+    Uuid(String),
+}
+
+impl Identifier {
+    /// Retrieves the value of the identifier, whatever its scheme.
+    ///
+    /// # Returns
+    /// A reference to the value string.
+    pub fn value(&self) -> &String {
+        match self {
+            Identifier::Isbn(v) | Identifier::Doi(v) | Identifier::Uuid(v) => v,
+        }
+    }
+}
+
+/// The series a document belongs to.
+///
+/// # Fields
+/// - `name`: The name of the series.
+/// - `position`: The position of the document in the series, starting from 1.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Series {
+    pub name: String,
+    pub position: u32,
+}
+
+impl Series {
+    /// Creates a new `Series`.
+    ///
+    /// # Parameters
+    /// - `name`: The name of the series.
+    /// - `position`: The position of the document in the series.
+    pub fn new(name: String, position: u32) -> Self {
+        Series { name, position }
+    }
+}
+
 /// Represents the document's metadata in the `.fobz` format.
 ///
+/// Every field after `cover` is optional, so manifests written before they existed still load.
+///
 /// # Fields
-/// - `version`: The version of the document format (e.g., "1.0").
+/// - `version`: The version of the document format (e.g., "1.3").
 /// - `title`: The title of the document.
 /// - `author`: The name of the author of the document.
 /// - `description`: A brief summary or description of the document.
 /// - `tags`: A list of tags classifying the document's genre or themes.
 /// - `index`: The relative path of the starting page.
 /// - `cover`: The relative path of the cover image.
+/// - `contributors`: The people credited in the document, with their role.
+/// - `language`: The language of the document, as a BCP 47 tag (e.g., "en-US").
+/// - `publisher`: The name of the publisher.
+/// - `published`: The publication date, as an ISO 8601 date (e.g., "2024-05-01").
+/// - `modified`: The date of the last modification, as an ISO 8601 date or date-time.
+/// - `identifiers`: The unique identifiers of the document.
+/// - `series`: The series the document belongs to.
+/// - `rights`: A statement about the rights held over the document.
+/// - `extra`: Free-form metadata, sorted by key.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Manifest {
     version: String,
@@ -19,29 +120,40 @@ pub struct Manifest {
     tags: Vec<String>,
     index: String,
     cover: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    contributors: Vec<Contributor>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    language: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    publisher: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    published: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    modified: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    identifiers: Vec<Identifier>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    series: Option<Series>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rights: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    extra: BTreeMap<String, String>,
 }
 
 impl Default for Manifest {
     /// Creates a default instance of `Manifest`.
     ///
     /// The default values are:
-    /// - `version`: "1.0"
+    /// - `version`: `CURRENT_VERSION`
     /// - `title`: ""
     /// - `author`: ""
     /// - `description`: ""
     /// - `tags`: []
     /// - `index`: "default/no_section.html"
     /// - `cover`: "default/no_cover.jpg"
+    /// - every optional field: empty
     fn default() -> Self {
-        Self {
-            version: "1.0".into(),
-            title: "".into(),
-            author: "".into(),
-            description: "".into(),
-            tags: vec![],
-            index: "default/no_section.html".into(),
-            cover: "default/no_cover.jpg".into(),
-        }
+        Manifest::new("".into(), "".into(), "".into(), vec![])
     }
 }
 
@@ -58,70 +170,79 @@ impl Manifest {
     /// A new `Manifest` instance initialized with the provided values and default values for other fields.
     pub fn new(title: String, author: String, description: String, tags: Vec<String>) -> Self {
         Manifest {
-            version: "1.0".into(),
+            version: CURRENT_VERSION.into(),
             title,
             author,
             description,
             tags,
             index: "default/no_section.html".into(),
             cover: "default/no_cover.jpg".into(),
+            contributors: vec![],
+            language: None,
+            publisher: None,
+            published: None,
+            modified: None,
+            identifiers: vec![],
+            series: None,
+            rights: None,
+            extra: BTreeMap::new(),
         }
     }
 
-    /// Retrieves a mutable reference to the document's version.
+    /// Retrieves a reference to the document's version.
     ///
     /// # Returns
-    /// A mutable reference to the version string.
-    pub fn get_version(&mut self) -> &String {
-        &mut self.version
+    /// A reference to the version string.
+    pub fn get_version(&self) -> &String {
+        &self.version
     }
 
-    /// Retrieves a mutable reference to the document's title.
+    /// Retrieves a reference to the document's title.
     ///
     /// # Returns
-    /// A mutable reference to the title string.
-    pub fn get_title(&mut self) -> &String {
-        &mut self.title
+    /// A reference to the title string.
+    pub fn get_title(&self) -> &String {
+        &self.title
     }
 
-    /// Retrieves a mutable reference to the document's author.
+    /// Retrieves a reference to the document's author.
     ///
     /// # Returns
-    /// A mutable reference to the author string.
-    pub fn get_author(&mut self) -> &String {
-        &mut self.author
+    /// A reference to the author string.
+    pub fn get_author(&self) -> &String {
+        &self.author
     }
 
-    /// Retrieves a mutable reference to the document's description.
+    /// Retrieves a reference to the document's description.
     ///
     /// # Returns
-    /// A mutable reference to the description string.
-    pub fn get_description(&mut self) -> &String {
-        &mut self.description
+    /// A reference to the description string.
+    pub fn get_description(&self) -> &String {
+        &self.description
     }
 
-    /// Retrieves a mutable reference to the list of tags associated with the document.
+    /// Retrieves a reference to the list of tags associated with the document.
     ///
     /// # Returns
-    /// A mutable reference to the vector of tags.
-    pub fn get_tags(&mut self) -> &Vec<String> {
-        &mut self.tags
+    /// A reference to the vector of tags.
+    pub fn get_tags(&self) -> &Vec<String> {
+        &self.tags
     }
 
-    /// Retrieves a mutable reference to the document's index path.
+    /// Retrieves a reference to the document's index path.
     ///
     /// # Returns
-    /// A mutable reference to the index path string.
-    pub fn get_index(&mut self) -> &String {
-        &mut self.index
+    /// A reference to the index path string.
+    pub fn get_index(&self) -> &String {
+        &self.index
     }
 
-    /// Retrieves a mutable reference to the document's cover image path.
+    /// Retrieves a reference to the document's cover image path.
     ///
     /// # Returns
-    /// A mutable reference to the cover image path string.
-    pub fn get_cover(&mut self) -> &String {
-        &mut self.cover
+    /// A reference to the cover image path string.
+    pub fn get_cover(&self) -> &String {
+        &self.cover
     }
 
     /// Sets the title of the document.
@@ -179,4 +300,194 @@ impl Manifest {
     pub fn set_cover(&mut self, path: String) {
         self.cover = path;
     }
+
+    /// Retrieves a reference to the list of contributors of the document.
+    ///
+    /// # Returns
+    /// A reference to the vector of contributors.
+    pub fn get_contributors(&self) -> &Vec<Contributor> {
+        &self.contributors
+    }
+
+    /// Retrieves the contributors of the document having a given role.
+    ///
+    /// # Parameters
+    /// - `role`: The role to look for.
+    ///
+    /// # Returns
+    /// An iterator over the matching contributors, in the order they were added.
+    pub fn get_contributors_by_role(&self, role: Role) -> impl Iterator<Item = &Contributor> {
+        self.contributors.iter().filter(move |v| v.role == role)
+    }
+
+    /// Retrieves a reference to the document's language.
+    ///
+    /// # Returns
+    /// An optional reference to the language tag.
+    pub fn get_language(&self) -> Option<&String> {
+        self.language.as_ref()
+    }
+
+    /// Retrieves a reference to the document's publisher.
+    ///
+    /// # Returns
+    /// An optional reference to the publisher string.
+    pub fn get_publisher(&self) -> Option<&String> {
+        self.publisher.as_ref()
+    }
+
+    /// Retrieves a reference to the document's publication date.
+    ///
+    /// # Returns
+    /// An optional reference to the publication date string.
+    pub fn get_published(&self) -> Option<&String> {
+        self.published.as_ref()
+    }
+
+    /// Retrieves a reference to the document's modification date.
+    ///
+    /// # Returns
+    /// An optional reference to the modification date string.
+    pub fn get_modified(&self) -> Option<&String> {
+        self.modified.as_ref()
+    }
+
+    /// Retrieves a reference to the list of identifiers of the document.
+    ///
+    /// # Returns
+    /// A reference to the vector of identifiers.
+    pub fn get_identifiers(&self) -> &Vec<Identifier> {
+        &self.identifiers
+    }
+
+    /// Retrieves a reference to the series the document belongs to.
+    ///
+    /// # Returns
+    /// An optional reference to the `Series`.
+    pub fn get_series(&self) -> Option<&Series> {
+        self.series.as_ref()
+    }
+
+    /// Retrieves a reference to the document's rights statement.
+    ///
+    /// # Returns
+    /// An optional reference to the rights string.
+    pub fn get_rights(&self) -> Option<&String> {
+        self.rights.as_ref()
+    }
+
+    /// Retrieves a reference to the document's free-form metadata.
+    ///
+    /// # Returns
+    /// A reference to the map of extra metadata.
+    pub fn get_extra(&self) -> &BTreeMap<String, String> {
+        &self.extra
+    }
+
+    /// Adds a contributor to the document.
+    ///
+    /// # Parameters
+    /// - `contributor`: The contributor to add.
+    pub fn add_contributor(&mut self, contributor: Contributor) {
+        self.contributors.push(contributor);
+    }
+
+    /// Removes every contributor with the given name and role.
+    ///
+    /// # Parameters
+    /// - `name`: The name of the contributor to remove.
+    /// - `role`: The role of the contributor to remove.
+    pub fn remove_contributor(&mut self, name: &str, role: Role) {
+        self.contributors
+            .retain(|v| !(v.name == name && v.role == role));
+    }
+
+    /// Sets the language of the document.
+    ///
+    /// # Parameters
+    /// - `language`: The new language tag, or `None` to clear it.
+    pub fn set_language(&mut self, language: Option<String>) {
+        self.language = language;
+    }
+
+    /// Sets the publisher of the document.
+    ///
+    /// # Parameters
+    /// - `publisher`: The new publisher, or `None` to clear it.
+    pub fn set_publisher(&mut self, publisher: Option<String>) {
+        self.publisher = publisher;
+    }
+
+    /// Sets the publication date of the document.
+    ///
+    /// # Parameters
+    /// - `date`: The new publication date, or `None` to clear it.
+    pub fn set_published(&mut self, date: Option<String>) {
+        self.published = date;
+    }
+
+    /// Sets the modification date of the document.
+    ///
+    /// # Parameters
+    /// - `date`: The new modification date, or `None` to clear it.
+    pub fn set_modified(&mut self, date: Option<String>) {
+        self.modified = date;
+    }
+
+    /// Adds an identifier to the document, unless it is already listed.
+    ///
+    /// # Parameters
+    /// - `identifier`: The identifier to add.
+    pub fn add_identifier(&mut self, identifier: Identifier) {
+        if !self.identifiers.contains(&identifier) {
+            self.identifiers.push(identifier);
+        }
+    }
+
+    /// Removes an identifier from the document.
+    ///
+    /// # Parameters
+    /// - `identifier`: The identifier to remove.
+    pub fn remove_identifier(&mut self, identifier: &Identifier) {
+        self.identifiers.retain(|v| v != identifier);
+    }
+
+    /// Sets the series the document belongs to.
+    ///
+    /// # Parameters
+    /// - `series`: The new series, or `None` to clear it.
+    pub fn set_series(&mut self, series: Option<Series>) {
+        self.series = series;
+    }
+
+    /// Sets the rights statement of the document.
+    ///
+    /// # Parameters
+    /// - `rights`: The new rights statement, or `None` to clear it.
+    pub fn set_rights(&mut self, rights: Option<String>) {
+        self.rights = rights;
+    }
+
+    /// Sets a free-form metadata entry, replacing any previous value.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry.
+    /// - `value`: The value of the entry.
+    ///
+    /// # Returns
+    /// The previous value, if there was one.
+    pub fn set_extra(&mut self, key: String, value: String) -> Option<String> {
+        self.extra.insert(key, value)
+    }
+
+    /// Removes a free-form metadata entry.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry.
+    ///
+    /// # Returns
+    /// The removed value, if there was one.
+    pub fn remove_extra(&mut self, key: &str) -> Option<String> {
+        self.extra.remove(key)
+    }
 }
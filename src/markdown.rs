@@ -0,0 +1,547 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+use pulldown_cmark::{html, Event, Options, Parser, Tag, TagEnd};
+
+use crate::{
+    decode_text,
+    error::Result,
+    import::{ImportIssueKind, ImportReport},
+    manifest::{Contributor, Identifier, Role, Series},
+    media, refs, FobZ,
+};
+
+// Extensions of the files picked up when importing a directory.
+const MARKDOWN_EXTENSIONS: &[&str] = &["md", "markdown"];
+
+/// A value of a front-matter block: a single string or a list of strings.
+enum FrontValue {
+    Text(String),
+    List(Vec<String>),
+}
+
+impl FrontValue {
+    /// Returns every string of the value, a single string being a list of one.
+    fn items(&self) -> Vec<String> {
+        match self {
+            FrontValue::Text(v) => vec![v.clone()],
+            FrontValue::List(v) => v.clone(),
+        }
+    }
+
+    /// Returns the value as a single string, joining lists with commas.
+    fn text(&self) -> String {
+        match self {
+            FrontValue::Text(v) => v.clone(),
+            FrontValue::List(v) => v.join(", "),
+        }
+    }
+}
+
+/// The state shared by every file of a Markdown import.
+///
+/// # Fields
+/// - `fobz`: The document being built.
+/// - `report`: The parts that could not be carried over.
+/// - `base`: The directory the paths inside the document are relative to.
+/// - `sections`: The content path of every imported Markdown file, by file path.
+/// - `resources`: The resource path of every file copied so far, by file path.
+struct MarkdownImport {
+    fobz: FobZ,
+    report: ImportReport,
+    base: PathBuf,
+    sections: HashMap<PathBuf, String>,
+    resources: HashMap<PathBuf, String>,
+}
+
+impl FobZ {
+    /// Imports every Markdown file (`.md` or `.markdown`) of a directory and its subdirectories as
+    /// a new document, in path order (e.g., `01-introduction.md` before `02-setup.md`).
+    ///
+    /// See [`FobZ::import_markdown`] for how the files are converted.
+    ///
+    /// # Parameters
+    /// - `dir`: The directory containing the Markdown files.
+    ///
+    /// # Returns
+    /// A result containing the new `FobZ` and an `ImportReport`, or an error if the directory or
+    /// one of the files could not be read.
+    pub fn import_markdown_dir(dir: &str) -> Result<(FobZ, ImportReport)> {
+        let mut files = vec![];
+        collect_markdown(Path::new(dir), &mut files)?;
+        files.sort();
+
+        MarkdownImport::new(normalize(Path::new(dir)), &files).run(&files)
+    }
+
+    /// Imports a list of Markdown files as a new document, one section per file, in order.
+    ///
+    /// Each file is rendered to HTML and stored under `contents/` with the `.html` extension,
+    /// keeping its path relative to the directory shared by all the files; its first heading
+    /// becomes the section title. Local images, audio, and video files referenced by links and
+    /// images are copied under `resources/` when they lie inside that directory; other local
+    /// references are left as is and reported. Links between the imported files are rewritten to
+    /// point to their sections, and the first section becomes the index.
+    ///
+    /// The first file may start with a front-matter block, between two `---` lines, holding
+    /// `key: value` pairs and lists (`[a, b]` or `- a` lines). The keys `title`, `author`
+    /// (or `authors`), `description`, `tags`, `language`, `publisher`, `date` (or `published`),
+    /// `modified`, `rights`, `isbn`, `doi`, `uuid`, `series`, `series_position`, `editors`,
+    /// `translators`, `illustrators`, and `cover` fill the `Manifest`; any other key is kept in
+    /// its extra metadata.
+    ///
+    /// # Parameters
+    /// - `paths`: The Markdown files to import.
+    ///
+    /// # Returns
+    /// A result containing the new `FobZ` and an `ImportReport`, or an error if one of the files
+    /// could not be read or is not valid UTF-8.
+    pub fn import_markdown(paths: &[String]) -> Result<(FobZ, ImportReport)> {
+        let files: Vec<PathBuf> = paths.iter().map(|v| normalize(Path::new(v))).collect();
+
+        // Keep the layout of the files below the deepest directory they share.
+        let mut base = files
+            .first()
+            .and_then(|v| v.parent())
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        while !files.iter().all(|v| v.starts_with(&base)) {
+            if !base.pop() {
+                break;
+            }
+        }
+
+        MarkdownImport::new(base, &files).run(&files)
+    }
+}
+
+impl MarkdownImport {
+    /// Prepares the import of `files`, assigning the content path of each one.
+    fn new(base: PathBuf, files: &[PathBuf]) -> Self {
+        let sections = files
+            .iter()
+            .map(|file| {
+                let relative = relative_to(&base, file);
+                let stem = relative
+                    .rsplit_once('.')
+                    .map_or(relative.as_str(), |(v, _)| v);
+                (normalize(file), format!("contents/{}.html", stem))
+            })
+            .collect();
+
+        MarkdownImport {
+            fobz: FobZ::new(String::new(), String::new(), String::new(), vec![]),
+            report: ImportReport::default(),
+            base,
+            sections,
+            resources: HashMap::new(),
+        }
+    }
+
+    /// Imports every file in order and sets the index to the first section.
+    fn run(mut self, files: &[PathBuf]) -> Result<(FobZ, ImportReport)> {
+        for (i, file) in files.iter().enumerate() {
+            let file = normalize(file);
+            let source = file.to_string_lossy().to_string();
+            let text = decode_text(&source, fs::read(&file)?)?;
+
+            let (front, body) = split_front_matter(&text);
+            if let Some(front) = front {
+                if i == 0 {
+                    self.apply_front_matter(&file, parse_front_matter(front));
+                } else {
+                    self.report.push(
+                        ImportIssueKind::UnmappedMetadata,
+                        &source,
+                        "front matter is only read from the first file".into(),
+                    );
+                }
+            }
+
+            let (content, heading, alts) = render(body);
+            let target = self.sections[&file].clone();
+            let references = refs::html_references(&content);
+            let content = refs::rewrite(&content, &references, |href| {
+                self.relocate(href, &file, &target, &alts)
+            });
+
+            let title = heading.unwrap_or_else(|| file_stem(&file));
+            if let Err(err) = self.fobz.add_content(target.clone(), title, content) {
+                self.report
+                    .push(ImportIssueKind::UnmappedItem, &source, err.to_string());
+            } else if i == 0 {
+                self.fobz.manifest.set_index(target);
+            }
+        }
+
+        Ok((self.fobz, self.report))
+    }
+
+    /// Rewrites a reference of the Markdown file `source`, rendered at `target`, so that it
+    /// points inside the document, copying the referenced file when needed.
+    ///
+    /// # Returns
+    /// The new reference, or `None` to leave it as is.
+    fn relocate(
+        &mut self,
+        href: &str,
+        source: &Path,
+        target: &str,
+        alts: &HashMap<String, String>,
+    ) -> Option<String> {
+        if href.is_empty() || href.starts_with('#') || refs::is_external(href) {
+            return None;
+        }
+
+        let (path, fragment) = refs::split_reference(href);
+        let file = normalize(&source.parent().unwrap_or(Path::new("")).join(&path));
+
+        let location = match self.sections.get(&file) {
+            Some(location) => location.clone(),
+            None => self.copy_resource(&file, alts.get(href), source)?,
+        };
+
+        let mut relocated = refs::percent_encode(&refs::relative(target, &location));
+        if let Some(fragment) = fragment {
+            relocated.push('#');
+            relocated.push_str(fragment);
+        }
+        Some(relocated)
+    }
+
+    /// Copies a local image, audio, or video file of the imported directory under `resources/`,
+    /// once, named after its alternative text if any.
+    ///
+    /// # Returns
+    /// The path of the resource, or `None` if the file is not copied, which is reported.
+    fn copy_resource(
+        &mut self,
+        file: &Path,
+        alt: Option<&String>,
+        source: &Path,
+    ) -> Option<String> {
+        if let Some(location) = self.resources.get(file) {
+            return Some(location.clone());
+        }
+
+        let source = source.to_string_lossy();
+        let relative = relative_to(&self.base, file);
+        let problem = if !is_inside(&self.base, file) {
+            Some("is outside of the imported directory")
+        } else if !media::from_extension(&relative).is_some_and(is_media) {
+            Some("is not an image, audio, or video file")
+        } else {
+            None
+        };
+        if let Some(problem) = problem {
+            self.report.push(
+                ImportIssueKind::UnresolvedLink,
+                &source,
+                format!("'{}' {}", file.to_string_lossy(), problem),
+            );
+            return None;
+        }
+
+        let Ok(bytes) = fs::read(file) else {
+            self.report.push(
+                ImportIssueKind::UnresolvedLink,
+                &source,
+                format!("'{}' could not be read", file.to_string_lossy()),
+            );
+            return None;
+        };
+
+        let location = format!("resources/{}", relative);
+        let name = alt
+            .filter(|v| !v.is_empty())
+            .cloned()
+            .unwrap_or_else(|| file_stem(file));
+
+        match self.fobz.add_resource(location.clone(), name, bytes) {
+            Ok(()) => {
+                self.resources.insert(file.to_path_buf(), location.clone());
+                Some(location)
+            }
+            Err(err) => {
+                self.report
+                    .push(ImportIssueKind::UnmappedItem, &source, err.to_string());
+                None
+            }
+        }
+    }
+
+    /// Fills the manifest from the front matter of the Markdown file `source`.
+    fn apply_front_matter(&mut self, source: &Path, entries: Vec<(String, FrontValue)>) {
+        for (key, value) in entries {
+            let manifest = &mut self.fobz.manifest;
+            let role = match key.as_str() {
+                "editor" | "editors" => Some(Role::Editor),
+                "translator" | "translators" => Some(Role::Translator),
+                "illustrator" | "illustrators" => Some(Role::Illustrator),
+                _ => None,
+            };
+            if let Some(role) = role {
+                for name in value.items() {
+                    manifest.add_contributor(Contributor::new(name, role));
+                }
+                continue;
+            }
+
+            match key.as_str() {
+                "title" => manifest.set_title(value.text()),
+                "author" | "authors" => {
+                    for (i, name) in value.items().into_iter().enumerate() {
+                        if i == 0 && manifest.get_author().is_empty() {
+                            manifest.set_author(name);
+                        } else {
+                            manifest.add_contributor(Contributor::new(name, Role::Author));
+                        }
+                    }
+                }
+                "description" => manifest.set_description(value.text()),
+                "tags" | "keywords" => manifest.add_tags(match value {
+                    FrontValue::Text(v) => v.split(',').map(|v| v.trim().into()).collect(),
+                    FrontValue::List(v) => v,
+                }),
+                "language" | "lang" => manifest.set_language(Some(value.text())),
+                "publisher" => manifest.set_publisher(Some(value.text())),
+                "date" | "published" => manifest.set_published(Some(value.text())),
+                "modified" => manifest.set_modified(Some(value.text())),
+                "rights" => manifest.set_rights(Some(value.text())),
+                "isbn" | "doi" | "uuid" => {
+                    for item in value.items() {
+                        manifest.add_identifier(match key.as_str() {
+                            "isbn" => Identifier::Isbn(item),
+                            "doi" => Identifier::Doi(item),
+                            _ => Identifier::Uuid(item),
+                        });
+                    }
+                }
+                "series" => {
+                    let position = manifest.get_series().map_or(1, |v| v.position);
+                    manifest.set_series(Some(Series::new(value.text(), position)));
+                }
+                "series_position" | "series_index" => {
+                    let name = manifest
+                        .get_series()
+                        .map_or(String::new(), |v| v.name.clone());
+                    let position = value.text().parse::<f64>().map_or(1, |v| v.max(0.0) as u32);
+                    manifest.set_series(Some(Series::new(name, position)));
+                }
+                "cover" => {
+                    let path = value.text();
+                    let file = normalize(&source.parent().unwrap_or(Path::new("")).join(&path));
+                    if let Some(location) = self.copy_resource(&file, None, source) {
+                        self.fobz.manifest.set_cover(location);
+                    }
+                }
+                _ => {
+                    manifest.set_extra(key, value.text());
+                }
+            }
+        }
+    }
+}
+
+/// Renders Markdown to HTML.
+///
+/// # Returns
+/// A tuple containing the HTML, the text of the first heading, and the alternative text of every
+/// image by destination.
+fn render(markdown: &str) -> (String, Option<String>, HashMap<String, String>) {
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_FOOTNOTES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_HEADING_ATTRIBUTES;
+    let events: Vec<Event> = Parser::new_ext(markdown, options).collect();
+
+    let mut heading: Option<String> = None;
+    let mut in_heading = false;
+    let mut alts = HashMap::new();
+    let mut image: Option<(String, String)> = None;
+
+    for event in &events {
+        match event {
+            Event::Start(Tag::Heading { .. }) if heading.is_none() => {
+                in_heading = true;
+                heading = Some(String::new());
+            }
+            Event::End(TagEnd::Heading(_)) => in_heading = false,
+            Event::Start(Tag::Image { dest_url, .. }) => {
+                image = Some((dest_url.to_string(), String::new()));
+            }
+            Event::End(TagEnd::Image) => {
+                if let Some((url, alt)) = image.take() {
+                    alts.entry(url).or_insert(alt);
+                }
+            }
+            Event::Text(text) | Event::Code(text) => {
+                if let Some((_, alt)) = &mut image {
+                    alt.push_str(text);
+                }
+                if let (true, Some(heading)) = (in_heading, &mut heading) {
+                    heading.push_str(text);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut content = String::new();
+    html::push_html(&mut content, events.into_iter());
+
+    let heading = heading
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty());
+    (content, heading, alts)
+}
+
+/// Splits a leading front-matter block, between two `---` lines, from the rest of a file.
+fn split_front_matter(text: &str) -> (Option<&str>, &str) {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let Some(rest) = text
+        .strip_prefix("---\n")
+        .or_else(|| text.strip_prefix("---\r\n"))
+    else {
+        return (None, text);
+    };
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if matches!(line.trim_end(), "---" | "...") {
+            return (Some(&rest[..offset]), &rest[offset + line.len()..]);
+        }
+        offset += line.len();
+    }
+
+    (None, text)
+}
+
+/// Parses the `key: value` pairs of a front-matter block, in order.
+///
+/// Values may be plain or quoted strings, inline lists (`[a, b]`), or lists written as `- item`
+/// lines below an empty value. Keys are lowercased.
+fn parse_front_matter(block: &str) -> Vec<(String, FrontValue)> {
+    let mut entries: Vec<(String, FrontValue)> = vec![];
+
+    for line in block.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        if let Some(item) = trimmed.strip_prefix('-') {
+            if let Some((_, FrontValue::List(items))) = entries.last_mut() {
+                items.push(unquote(item.trim()));
+            }
+            continue;
+        }
+
+        let Some((key, value)) = trimmed.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        let value = if value.is_empty() {
+            FrontValue::List(vec![])
+        } else if let Some(list) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+            FrontValue::List(
+                list.split(',')
+                    .map(|v| unquote(v.trim()))
+                    .filter(|v| !v.is_empty())
+                    .collect(),
+            )
+        } else {
+            FrontValue::Text(unquote(value))
+        };
+        entries.push((key.trim().to_ascii_lowercase(), value));
+    }
+
+    entries
+}
+
+/// Removes the quotes surrounding a front-matter string, if any.
+fn unquote(value: &str) -> String {
+    for quote in ['"', '\''] {
+        if let Some(inner) = value
+            .strip_prefix(quote)
+            .and_then(|v| v.strip_suffix(quote))
+        {
+            return inner.into();
+        }
+    }
+    value.into()
+}
+
+/// Collects the Markdown files of a directory and its subdirectories, skipping hidden entries.
+fn collect_markdown(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let hidden = path
+            .file_name()
+            .is_some_and(|v| v.to_string_lossy().starts_with('.'));
+
+        if hidden {
+            continue;
+        } else if path.is_dir() {
+            collect_markdown(&path, files)?;
+        } else if path
+            .extension()
+            .is_some_and(|v| MARKDOWN_EXTENSIONS.contains(&v.to_string_lossy().as_ref()))
+        {
+            files.push(normalize(&path));
+        }
+    }
+
+    Ok(())
+}
+
+/// Normalizes a path lexically, removing `.` segments and resolving `..` ones.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir if normalized.file_name().is_some() => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+
+    normalized
+}
+
+/// Checks whether `file`, once normalized, lies below the directory `base`.
+fn is_inside(base: &Path, file: &Path) -> bool {
+    file.strip_prefix(base)
+        .is_ok_and(|v| v.components().all(|v| matches!(v, Component::Normal(_))))
+}
+
+/// Checks whether a media type is the one of an image, audio, or video file.
+fn is_media(media_type: &str) -> bool {
+    ["image/", "audio/", "video/"]
+        .iter()
+        .any(|v| media_type.starts_with(v))
+}
+
+/// Builds the `/`-separated path of `file` relative to `base`.
+fn relative_to(base: &Path, file: &Path) -> String {
+    let relative = file.strip_prefix(base).unwrap_or(file);
+    let segments: Vec<_> = relative
+        .components()
+        .map(|v| v.as_os_str().to_string_lossy())
+        .collect();
+    segments.join("/")
+}
+
+/// Extracts the file name of a path, without its extension.
+fn file_stem(path: &Path) -> String {
+    path.file_stem()
+        .map(|v| v.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
@@ -0,0 +1,169 @@
+/// Media type used when the kind of a resource cannot be determined.
+pub const OCTET_STREAM: &str = "application/octet-stream";
+
+// Known file signatures, checked in order against the start of a resource. Signatures too short
+// to tell a format apart from text (e.g., `BM` for bitmaps) are left to the extension.
+const SIGNATURES: &[(&[u8], &str)] = &[
+    (b"\xFF\xD8\xFF", "image/jpeg"),
+    (b"\x89PNG\r\n\x1A\n", "image/png"),
+    (b"GIF87a", "image/gif"),
+    (b"GIF89a", "image/gif"),
+    (b"\x00\x00\x01\x00", "image/x-icon"),
+    (b"wOFF", "font/woff"),
+    (b"wOF2", "font/woff2"),
+    (b"OTTO", "font/otf"),
+    (b"\x00\x01\x00\x00", "font/ttf"),
+    (b"ID3", "audio/mpeg"),
+    (b"OggS", "audio/ogg"),
+    (b"fLaC", "audio/flac"),
+    (b"\x1A\x45\xDF\xA3", "video/webm"),
+    (b"%PDF-", "application/pdf"),
+];
+
+// Known file extensions, trusted over the content.
+const EXTENSIONS: &[(&str, &str)] = &[
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("png", "image/png"),
+    ("gif", "image/gif"),
+    ("webp", "image/webp"),
+    ("svg", "image/svg+xml"),
+    ("avif", "image/avif"),
+    ("bmp", "image/bmp"),
+    ("ico", "image/x-icon"),
+    ("woff", "font/woff"),
+    ("woff2", "font/woff2"),
+    ("otf", "font/otf"),
+    ("ttf", "font/ttf"),
+    ("mp3", "audio/mpeg"),
+    ("ogg", "audio/ogg"),
+    ("oga", "audio/ogg"),
+    ("flac", "audio/flac"),
+    ("wav", "audio/wav"),
+    ("m4a", "audio/mp4"),
+    ("mp4", "video/mp4"),
+    ("m4v", "video/mp4"),
+    ("webm", "video/webm"),
+    ("ogv", "video/ogg"),
+    ("pdf", "application/pdf"),
+    ("json", "application/json"),
+    ("txt", "text/plain"),
+];
+
+/// Detects the media type of a resource from the extension of its path, falling back to its
+/// leading bytes when the extension is unknown.
+///
+/// # Parameters
+/// - `path`: The file path of the resource.
+/// - `bytes`: The binary data of the resource.
+///
+/// # Returns
+/// The detected media type, or `application/octet-stream` if it is unknown.
+pub fn detect(path: &str, bytes: &[u8]) -> String {
+    from_extension(path)
+        .or_else(|| from_bytes(bytes))
+        .unwrap_or(OCTET_STREAM)
+        .into()
+}
+
+/// Detects the media type of a resource from the signature at its start.
+///
+/// # Parameters
+/// - `bytes`: The binary data of the resource.
+///
+/// # Returns
+/// An optional media type, `None` if no known signature matches.
+pub fn from_bytes(bytes: &[u8]) -> Option<&'static str> {
+    // Container formats carry their type after a generic header.
+    if bytes.len() >= 12 && &bytes[0..4] == b"RIFF" {
+        match &bytes[8..12] {
+            b"WEBP" => return Some("image/webp"),
+            b"WAVE" => return Some("audio/wav"),
+            _ => {}
+        }
+    }
+    if bytes.len() >= 12 && &bytes[4..8] == b"ftyp" {
+        return match &bytes[8..12] {
+            b"avif" | b"avis" => Some("image/avif"),
+            b"M4A " => Some("audio/mp4"),
+            _ => Some("video/mp4"),
+        };
+    }
+
+    if let Some((_, media_type)) = SIGNATURES.iter().find(|(v, _)| bytes.starts_with(v)) {
+        return Some(media_type);
+    }
+
+    is_svg(bytes).then_some("image/svg+xml")
+}
+
+/// Guesses the media type of a resource from the extension of its path.
+///
+/// # Parameters
+/// - `path`: The file path of the resource.
+///
+/// # Returns
+/// An optional media type, `None` if the extension is unknown.
+pub fn from_extension(path: &str) -> Option<&'static str> {
+    let (_, extension) = path.rsplit_once('.')?;
+    let extension = extension.to_ascii_lowercase();
+
+    EXTENSIONS
+        .iter()
+        .find(|(v, _)| *v == extension)
+        .map(|(_, media_type)| *media_type)
+}
+
+/// Checks whether a text document has an `<svg>` root element, possibly preceded by an XML
+/// declaration, comments, or a doctype.
+fn is_svg(bytes: &[u8]) -> bool {
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(1024)]);
+    let mut rest = head.trim_start_matches('\u{FEFF}').trim_start();
+
+    while let Some(prolog) = rest.strip_prefix("<?").or_else(|| rest.strip_prefix("<!")) {
+        let end = if prolog.starts_with("--") { "-->" } else { ">" };
+        let Some(offset) = prolog.find(end) else {
+            return false;
+        };
+        rest = prolog[offset + end.len()..].trim_start();
+    }
+
+    rest.strip_prefix("<svg")
+        .is_some_and(|v| v.starts_with(|c: char| c.is_ascii_whitespace() || c == '>' || c == '/'))
+}
+
+/// Determines which resources are accepted by a document.
+///
+/// # Variants
+/// - `AcceptAll`: Every resource is kept, whatever its media type.
+/// - `AllowList`: Only resources whose media type is listed are kept.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum ResourcePolicy {
+    #[default]
+    AcceptAll,
+    AllowList(Vec<String>),
+}
+
+impl ResourcePolicy {
+    /// Creates the policy accepting only JPEG and PNG images.
+    ///
+    /// # Returns
+    /// A `ResourcePolicy::AllowList` containing `image/jpeg` and `image/png`.
+    pub fn images_only() -> Self {
+        ResourcePolicy::AllowList(vec!["image/jpeg".into(), "image/png".into()])
+    }
+
+    /// Checks whether a media type is accepted by the policy.
+    ///
+    /// # Parameters
+    /// - `media_type`: The media type to check.
+    ///
+    /// # Returns
+    /// `true` if resources of this media type are accepted, otherwise `false`.
+    pub fn allows(&self, media_type: &str) -> bool {
+        match self {
+            ResourcePolicy::AcceptAll => true,
+            ResourcePolicy::AllowList(list) => list.iter().any(|v| v == media_type),
+        }
+    }
+}
@@ -0,0 +1,254 @@
+use std::io::{Read, Seek};
+
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::Value;
+use zip::ZipArchive;
+
+use crate::{
+    archive,
+    error::{FobzError, Result},
+    manifest::Manifest,
+    media,
+    toc::TableOfContents,
+    tor::TableOfResources,
+    tos::TableOfStyles,
+};
+
+/// The format version written by this library.
+pub const CURRENT_VERSION: &str = "1.3";
+
+/// The manifest and tables of an archive, deserialized into the current layout.
+pub(crate) struct Layout {
+    pub(crate) manifest: Manifest,
+    pub(crate) toc: TableOfContents,
+    pub(crate) tor: TableOfResources,
+    pub(crate) tos: TableOfStyles,
+}
+
+/// The manifest and tables of an archive as raw JSON, in the layout of the version they were written with.
+struct RawLayout {
+    manifest: Value,
+    toc: Value,
+    tor: Value,
+    tos: Value,
+}
+
+/// Upgrades the raw layout of one format version to the next one.
+///
+/// # Fields
+/// - `from`: The version the migration applies to.
+/// - `to`: The version the layout conforms to once migrated.
+/// - `apply`: The function rewriting the raw layout.
+struct Migration {
+    from: &'static str,
+    to: &'static str,
+    apply: fn(&mut RawLayout),
+}
+
+// Every known migration, chained from the oldest version up to `CURRENT_VERSION`.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        from: "1.0",
+        to: "1.1",
+        apply: add_media_types,
+    },
+    Migration {
+        from: "1.1",
+        to: "1.2",
+        apply: split_anchors,
+    },
+    Migration {
+        from: "1.2",
+        to: "1.3",
+        apply: add_bibliographic_metadata,
+    },
+];
+
+/// Only the version of a manifest, read before the rest of the layout.
+#[derive(Deserialize)]
+struct VersionOnly {
+    version: String,
+}
+
+/// Reads the manifest and tables of an archive, migrating them from older format versions.
+///
+/// # Parameters
+/// - `archive`: The archive to read from.
+///
+/// # Returns
+/// A result containing the `Layout`, `FobzError::UnsupportedVersion` if the archive was written
+/// with an unknown major version, or another error if an entry is missing or malformed.
+pub(crate) fn read_layout<R: Read + Seek>(archive: &mut ZipArchive<R>) -> Result<Layout> {
+    read_layout_with(|entry| read_entry(archive, entry))
+}
+
+/// Reads the manifest and tables from any source, migrating them from older format versions.
+///
+/// # Parameters
+/// - `read`: The function returning the raw bytes of an entry, given its name.
+///
+/// # Returns
+/// A result containing the `Layout`, `FobzError::UnsupportedVersion` if the layout was written
+/// with an unknown major version, or another error if an entry is missing or malformed.
+pub(crate) fn read_layout_with<F>(mut read: F) -> Result<Layout>
+where
+    F: FnMut(&str) -> Result<Vec<u8>>,
+{
+    let manifest = read("manifest.json")?;
+    let toc = read("toc.json")?;
+    let tor = read("tor.json")?;
+    let tos = read("tos.json")?;
+
+    let version: VersionOnly = from_slice("manifest.json", &manifest)?;
+
+    // Archives in the current layout are deserialized directly to keep error positions accurate.
+    if version.version == CURRENT_VERSION {
+        return Ok(Layout {
+            manifest: from_slice("manifest.json", &manifest)?,
+            toc: from_slice("toc.json", &toc)?,
+            tor: from_slice("tor.json", &tor)?,
+            tos: from_slice("tos.json", &tos)?,
+        });
+    }
+
+    let mut raw = RawLayout {
+        manifest: from_slice("manifest.json", &manifest)?,
+        toc: from_slice("toc.json", &toc)?,
+        tor: from_slice("tor.json", &tor)?,
+        tos: from_slice("tos.json", &tos)?,
+    };
+    migrate(&mut raw, version.version)?;
+
+    Ok(Layout {
+        manifest: from_value("manifest.json", raw.manifest)?,
+        toc: from_value("toc.json", raw.toc)?,
+        tor: from_value("tor.json", raw.tor)?,
+        tos: from_value("tos.json", raw.tos)?,
+    })
+}
+
+/// Checks whether a document can be written with its format version.
+///
+/// Documents read from a newer minor version keep that version, but the fields it added were
+/// dropped while deserializing, so writing them back would lose data under a version that claims
+/// to hold it.
+///
+/// # Parameters
+/// - `version`: The version of the document.
+///
+/// # Returns
+/// A result indicating success, or `FobzError::UnsupportedVersion` if the version is newer than
+/// `CURRENT_VERSION`.
+pub(crate) fn check_writable(version: &str) -> Result<()> {
+    if is_newer(version) {
+        return Err(FobzError::UnsupportedVersion(version.into()));
+    }
+    Ok(())
+}
+
+/// Runs the chain of migrations until the layout reaches `CURRENT_VERSION`.
+///
+/// Newer minor versions of the current major version only add fields that older readers can
+/// ignore, so they are read as they are and keep their version; see [`check_writable`].
+fn migrate(raw: &mut RawLayout, mut version: String) -> Result<()> {
+    while version != CURRENT_VERSION {
+        match MIGRATIONS.iter().find(|v| v.from == version) {
+            Some(migration) => {
+                (migration.apply)(raw);
+                version = migration.to.into();
+                raw.manifest["version"] = Value::String(version.clone());
+            }
+            None if is_newer(&version) => return Ok(()),
+            None => return Err(FobzError::UnsupportedVersion(version)),
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks whether a version is a newer minor version of the current major version.
+fn is_newer(version: &str) -> bool {
+    match (parse_version(version), parse_version(CURRENT_VERSION)) {
+        (Some((major, minor)), Some((current_major, current_minor))) => {
+            major == current_major && minor > current_minor
+        }
+        _ => false,
+    }
+}
+
+/// Splits a `major.minor` version string into its numeric parts.
+fn parse_version(version: &str) -> Option<(u32, u32)> {
+    let (major, minor) = version.split_once('.')?;
+    Some((major.parse().ok()?, minor.parse().ok()?))
+}
+
+/// Reads the raw bytes of an entry of the archive.
+fn read_entry<R: Read + Seek>(archive: &mut ZipArchive<R>, entry: &str) -> Result<Vec<u8>> {
+    let mut file = archive
+        .by_name(entry)
+        .map_err(|err| FobzError::entry(entry, err))?;
+    archive::read_file(&mut file)
+}
+
+/// Deserializes the bytes of a JSON entry.
+fn from_slice<T: DeserializeOwned>(entry: &str, bytes: &[u8]) -> Result<T> {
+    serde_json::from_slice(bytes).map_err(|err| FobzError::json(entry, err))
+}
+
+/// Deserializes a migrated JSON entry.
+fn from_value<T: DeserializeOwned>(entry: &str, value: Value) -> Result<T> {
+    serde_json::from_value(value).map_err(|err| FobzError::json(entry, err))
+}
+
+/// 1.0 → 1.1: resources gain a `media_type`, guessed here from their extension.
+///
+/// Resources with an unknown extension are left without one, so that it is detected from their
+/// content once loaded.
+fn add_media_types(raw: &mut RawLayout) {
+    let Some(resources) = raw.tor.get_mut("resources").and_then(Value::as_array_mut) else {
+        return;
+    };
+
+    for resource in resources.iter_mut().filter_map(Value::as_object_mut) {
+        if resource.contains_key("media_type") {
+            continue;
+        }
+
+        let media_type = resource
+            .get("path")
+            .and_then(Value::as_str)
+            .and_then(media::from_extension);
+
+        if let Some(media_type) = media_type {
+            resource.insert("media_type".into(), media_type.into());
+        }
+    }
+}
+
+/// 1.1 → 1.2: the flat table of contents becomes a tree, and fragments move out of `path`.
+///
+/// Flat sections are already valid top-level sections; only a `path#fragment` written by older
+/// versions needs to be split into `path` and `anchor`.
+fn split_anchors(raw: &mut RawLayout) {
+    let Some(sections) = raw.toc.get_mut("sections").and_then(Value::as_array_mut) else {
+        return;
+    };
+
+    for section in sections.iter_mut().filter_map(Value::as_object_mut) {
+        let split = section
+            .get("path")
+            .and_then(Value::as_str)
+            .and_then(|v| v.split_once('#'))
+            .map(|(path, anchor)| (path.to_string(), anchor.to_string()));
+
+        if let Some((path, anchor)) = split {
+            section.insert("path".into(), path.into());
+            section.insert("anchor".into(), anchor.into());
+        }
+    }
+}
+
+/// 1.2 → 1.3: the manifest gains optional bibliographic metadata.
+///
+/// Every new field defaults to empty when missing, so there is nothing to rewrite.
+fn add_bibliographic_metadata(_: &mut RawLayout) {}
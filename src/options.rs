@@ -0,0 +1,116 @@
+use zip::DateTime;
+
+use crate::{compression::CompressionPolicy, media::ResourcePolicy, sanitize::SanitizePolicy};
+
+/// Determines when the entries of a `.fobz` archive are decompressed.
+///
+/// # Variants
+/// - `Eager`: Every content, resource, and style is read into memory when the archive is opened.
+/// - `Lazy`: The archive is kept open and each entry is read the first time it is requested.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LoadMode {
+    #[default]
+    Eager,
+    Lazy,
+}
+
+/// Represents the options used when opening a `.fobz` archive.
+///
+/// # Fields
+/// - `mode`: Whether the entries are read when opening or on first access.
+/// - `resource_policy`: Which resources are kept; the others are skipped while reading.
+/// - `sanitize_policy`: The policy applied to the HTML of contents. Unless it is
+///   `SanitizePolicy::Off`, contents are read when opening even in `LoadMode::Lazy`, and the
+///   policy stays in effect for contents added later.
+/// - `limits`: The bounds an archive must stay within to be read at all.
+#[derive(Debug, Default, Clone)]
+pub struct OpenOptions {
+    pub mode: LoadMode,
+    pub resource_policy: ResourcePolicy,
+    pub sanitize_policy: SanitizePolicy,
+    pub limits: ReadLimits,
+}
+
+/// Identifies one of the bounds of [`ReadLimits`].
+///
+/// # Variants
+/// - `TotalSize`: The uncompressed size of every entry together.
+/// - `EntrySize`: The uncompressed size of a single entry.
+/// - `EntryCount`: The number of entries of the archive.
+/// - `CompressionRatio`: The uncompressed size of an entry divided by its compressed size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    TotalSize,
+    EntrySize,
+    EntryCount,
+    CompressionRatio,
+}
+
+/// Represents the bounds protecting against archives crafted to exhaust memory (e.g., zip bombs).
+///
+/// The sizes declared by the archive are checked before anything is decompressed, and no entry is
+/// ever decompressed past its declared size.
+///
+/// # Fields
+/// - `max_total_size`: The largest uncompressed size of every entry together, in bytes. Defaults
+///   to 1 GiB.
+/// - `max_entry_size`: The largest uncompressed size of a single entry, in bytes. Defaults to
+///   256 MiB.
+/// - `max_entries`: The largest number of entries, directories included. Defaults to 100 000.
+/// - `max_compression_ratio`: The largest ratio between the uncompressed and compressed sizes of
+///   an entry. Entries of 1 MiB or less are not checked, since short repetitive texts compress
+///   well. Defaults to 100.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadLimits {
+    pub max_total_size: u64,
+    pub max_entry_size: u64,
+    pub max_entries: usize,
+    pub max_compression_ratio: u64,
+}
+
+impl Default for ReadLimits {
+    fn default() -> Self {
+        ReadLimits {
+            max_total_size: 1 << 30,
+            max_entry_size: 256 << 20,
+            max_entries: 100_000,
+            max_compression_ratio: 100,
+        }
+    }
+}
+
+/// Represents the options used when saving a `.fobz` archive.
+///
+/// # Fields
+/// - `last_modified`: Modification time recorded for every entry. Defaults to
+///   1980-01-01 00:00:00 so that saving equal documents produces identical bytes.
+/// - `compression`: How each entry is compressed.
+#[derive(Debug, Default, Clone)]
+pub struct SaveOptions {
+    pub last_modified: DateTime,
+    pub compression: CompressionPolicy,
+}
+
+/// Determines the syntax of the text exported from the contents of a document.
+///
+/// # Variants
+/// - `Plain`: Plain text, with headings and paragraphs separated by blank lines and the targets
+///   of external links in parentheses.
+/// - `Markdown`: CommonMark, keeping headings, emphasis, links, images, and tables.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TextFormat {
+    #[default]
+    Plain,
+    Markdown,
+}
+
+/// Represents the options used when packing a document from a directory.
+///
+/// # Fields
+/// - `auto_register`: Whether files missing from the tables are added to them instead of being
+///   rejected. Contents are titled after their `<title>`, or else their file name, and resources
+///   are named after their file name.
+#[derive(Debug, Default, Clone)]
+pub struct PackOptions {
+    pub auto_register: bool,
+}
@@ -0,0 +1,411 @@
+// HTML attributes holding a single reference to another file.
+const REFERENCE_ATTRIBUTES: &[&str] = &["href", "src", "xlink:href", "poster", "data"];
+
+/// A reference to another file found in a content or a stylesheet.
+///
+/// # Fields
+/// - `start`: The byte offset where the reference starts in the text.
+/// - `end`: The byte offset where the reference ends in the text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Reference {
+    pub(crate) start: usize,
+    pub(crate) end: usize,
+}
+
+impl Reference {
+    /// Retrieves the reference as written in the text.
+    pub(crate) fn value<'a>(&self, text: &'a str) -> &'a str {
+        &text[self.start..self.end]
+    }
+}
+
+/// Finds the references of an HTML document: link attributes (`href`, `src`, ...) and the `url()`s
+/// of inline styles and `<style>` elements. Comments and scripts are skipped.
+pub(crate) fn html_references(html: &str) -> Vec<Reference> {
+    let mut references = vec![];
+    let mut i = 0;
+
+    while let Some(offset) = html[i..].find('<') {
+        let start = i + offset;
+        let rest = &html[start..];
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            i = start + 4 + comment_end(comment).1;
+            continue;
+        }
+        if rest[1..].starts_with(['!', '?']) {
+            i = start + rest.find('>').map_or(rest.len(), |v| v + 1);
+            continue;
+        }
+
+        let Some(end) = tag_end(rest) else {
+            break;
+        };
+        let tag = &rest[1..end];
+        let name_end = tag
+            .find(|c: char| c.is_ascii_whitespace() || c == '/')
+            .unwrap_or(tag.len());
+        let name = tag[..name_end].to_ascii_lowercase();
+
+        for (attribute, value) in attributes(tag, start + 1) {
+            let Some((value_start, value_end)) = value else {
+                continue;
+            };
+            if REFERENCE_ATTRIBUTES.contains(&attribute.as_str()) {
+                references.push(Reference {
+                    start: value_start,
+                    end: value_end,
+                });
+            } else if attribute == "style" {
+                references.extend(shifted(
+                    css_references(&html[value_start..value_end]),
+                    value_start,
+                ));
+            }
+        }
+        i = start + end + 1;
+
+        // The text of these elements is not HTML, so look for their end tag directly.
+        if (name == "style" || name == "script") && !tag.ends_with('/') {
+            let close = format!("</{}", name);
+            let body_end = html[i..]
+                .to_ascii_lowercase()
+                .find(&close)
+                .map_or(html.len(), |v| i + v);
+            if name == "style" {
+                references.extend(shifted(css_references(&html[i..body_end]), i));
+            }
+            i = body_end;
+        }
+    }
+
+    references
+}
+
+/// Finds the references of a stylesheet: `url()` values and `@import` strings. Comments are skipped.
+pub(crate) fn css_references(css: &str) -> Vec<Reference> {
+    let mut references = vec![];
+    let bytes = css.as_bytes();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let rest = &css[i..];
+
+        if rest.starts_with("/*") {
+            i += rest.find("*/").map_or(rest.len(), |v| v + 2);
+        } else if bytes[i..]
+            .get(..4)
+            .is_some_and(|v| v.eq_ignore_ascii_case(b"url("))
+        {
+            let mut start = i + 4;
+            while bytes.get(start).is_some_and(u8::is_ascii_whitespace) {
+                start += 1;
+            }
+            let (start, end) = match bytes.get(start) {
+                Some(&quote @ (b'"' | b'\'')) => {
+                    let end = css[start + 1..]
+                        .find(quote as char)
+                        .map_or(css.len(), |v| start + 1 + v);
+                    (start + 1, end)
+                }
+                _ => {
+                    let end = css[start..].find(')').map_or(css.len(), |v| start + v);
+                    (start, start + css[start..end].trim_end().len())
+                }
+            };
+            references.push(Reference { start, end });
+            i = end;
+        } else if rest.starts_with("@import") {
+            let mut start = i + 7;
+            while bytes.get(start).is_some_and(u8::is_ascii_whitespace) {
+                start += 1;
+            }
+            if let Some(&quote @ (b'"' | b'\'')) = bytes.get(start) {
+                let end = css[start + 1..]
+                    .find(quote as char)
+                    .map_or(css.len(), |v| start + 1 + v);
+                references.push(Reference {
+                    start: start + 1,
+                    end,
+                });
+                i = end;
+            } else {
+                i = start;
+            }
+        } else {
+            i += rest.chars().next().map_or(1, char::len_utf8);
+        }
+    }
+
+    references
+}
+
+/// Replaces references in a text, keeping those for which `rewrite` returns `None`.
+///
+/// `references` must be sorted by position and must not overlap, as returned by
+/// [`html_references`] and [`css_references`].
+pub(crate) fn rewrite<F>(text: &str, references: &[Reference], mut rewrite: F) -> String
+where
+    F: FnMut(&str) -> Option<String>,
+{
+    let mut output = String::with_capacity(text.len());
+    let mut last = 0;
+
+    for reference in references {
+        if let Some(value) = rewrite(reference.value(text)) {
+            output.push_str(&text[last..reference.start]);
+            output.push_str(&value);
+            last = reference.end;
+        }
+    }
+
+    output.push_str(&text[last..]);
+    output
+}
+
+/// Checks whether a reference points outside the document: it has a scheme (e.g., `https:`,
+/// `mailto:`, `data:`) or is protocol-relative.
+pub(crate) fn is_external(href: &str) -> bool {
+    if href.starts_with("//") {
+        return true;
+    }
+
+    match href.find(':') {
+        Some(colon) => {
+            let scheme = &href[..colon];
+            !scheme.is_empty()
+                && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        }
+        None => false,
+    }
+}
+
+/// Splits a local reference into its decoded path and its fragment, dropping any query.
+pub(crate) fn split_reference(href: &str) -> (String, Option<&str>) {
+    let (path, fragment) = match href.split_once('#') {
+        Some((path, fragment)) => (path, Some(fragment)),
+        None => (href, None),
+    };
+    let path = path.split_once('?').map_or(path, |(v, _)| v);
+
+    (percent_decode(path), fragment)
+}
+
+/// Resolves a path relative to the file `base`, both inside the same archive.
+///
+/// # Returns
+/// The normalized path, or `None` if it climbs above the root of the archive.
+pub(crate) fn resolve(base: &str, path: &str) -> Option<String> {
+    let mut segments: Vec<&str> = if path.starts_with('/') {
+        vec![]
+    } else {
+        base.split('/').collect()
+    };
+    segments.pop();
+
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop()?;
+            }
+            segment => segments.push(segment),
+        }
+    }
+
+    Some(segments.join("/"))
+}
+
+/// Builds the path leading from the file `from` to the file `to`, both inside the same archive.
+pub(crate) fn relative(from: &str, to: &str) -> String {
+    let from: Vec<&str> = from.split('/').collect();
+    let to: Vec<&str> = to.split('/').collect();
+    let from_dirs = &from[..from.len() - 1];
+
+    let common = from_dirs
+        .iter()
+        .zip(&to[..to.len() - 1])
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut path = "../".repeat(from_dirs.len() - common);
+    path.push_str(&to[common..].join("/"));
+    path
+}
+
+/// Offsets references found in a slice of a larger text.
+fn shifted(references: Vec<Reference>, offset: usize) -> impl Iterator<Item = Reference> {
+    references.into_iter().map(move |v| Reference {
+        start: v.start + offset,
+        end: v.end + offset,
+    })
+}
+
+/// Finds the `>` closing the tag starting `text`, skipping quoted attribute values.
+///
+/// As in HTML, a quote only starts a value right after an `=`; anywhere else it is part of a name
+/// or of an unquoted value, and does not hide a `>`.
+pub(crate) fn tag_end(text: &str) -> Option<usize> {
+    // Where the scan stands inside the tag, following the states of the HTML tokenizer.
+    enum State {
+        TagName,
+        BeforeName,
+        Name,
+        AfterName,
+        BeforeValue,
+        Value,
+    }
+
+    let bytes = text.as_bytes();
+    let mut state = State::TagName;
+    let mut i = 1;
+
+    while i < bytes.len() {
+        let c = bytes[i];
+        let space = c.is_ascii_whitespace();
+        state = match (state, c) {
+            (_, b'>') => return Some(i),
+            (State::BeforeValue, quote @ (b'"' | b'\'')) => {
+                i += 1 + bytes[i + 1..].iter().position(|v| *v == quote)?;
+                State::BeforeName
+            }
+            (State::BeforeValue, _) if space => State::BeforeValue,
+            (State::BeforeValue | State::Value, _) if !space => State::Value,
+            (State::Name | State::AfterName, b'=') => State::BeforeValue,
+            (State::Name | State::AfterName, _) if space => State::AfterName,
+            (_, b'/') => State::BeforeName,
+            (_, _) if space => State::BeforeName,
+            (State::TagName, _) => State::TagName,
+            _ => State::Name,
+        };
+        i += 1;
+    }
+
+    None
+}
+
+/// Finds where the comment following a `<!--` ends, as HTML does: at the first `-->` or `--!>`,
+/// or right away for the empty comments `<!-->` and `<!--->`.
+///
+/// # Returns
+/// The byte offsets, in `text`, of the end of the comment's text and of the whole comment. An
+/// unterminated comment runs to the end of `text`.
+pub(crate) fn comment_end(text: &str) -> (usize, usize) {
+    if text.starts_with('>') {
+        return (0, 1);
+    }
+    if text.starts_with("->") {
+        return (0, 2);
+    }
+
+    let close = text.find("-->").map(|v| (v, v + 3));
+    let bang = text.find("--!>").map(|v| (v, v + 4));
+    match (close, bang) {
+        (Some(a), Some(b)) => a.min(b),
+        (a, b) => a.or(b).unwrap_or((text.len(), text.len())),
+    }
+}
+
+/// Lists the attributes of a tag (without its `<` and `>`) as lowercase names with the byte range
+/// of their value, shifted by `offset`, or `None` for attributes without a value.
+pub(crate) fn attributes(tag: &str, offset: usize) -> Vec<(String, Option<(usize, usize)>)> {
+    let bytes = tag.as_bytes();
+    let mut attributes = vec![];
+
+    // Skip the element name, which a `/` ends as well (e.g., `<img/src=x>`).
+    let mut i = tag
+        .find(|c: char| c.is_ascii_whitespace() || c == '/')
+        .unwrap_or(tag.len());
+
+    while i < bytes.len() {
+        while i < bytes.len() && (bytes[i].is_ascii_whitespace() || bytes[i] == b'/') {
+            i += 1;
+        }
+        let name_start = i;
+        // As in HTML, a `=` starting a name is part of it.
+        if bytes.get(i) == Some(&b'=') {
+            i += 1;
+        }
+        while i < bytes.len() && !bytes[i].is_ascii_whitespace() && !b"=/>".contains(&bytes[i]) {
+            i += 1;
+        }
+        let name = tag[name_start..i].to_ascii_lowercase();
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        if bytes.get(i) != Some(&b'=') {
+            if i == name_start {
+                i += 1;
+            } else {
+                attributes.push((name, None));
+            }
+            continue;
+        }
+        i += 1;
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+
+        let (start, end) = match bytes.get(i) {
+            Some(&quote @ (b'"' | b'\'')) => {
+                let end = tag[i + 1..]
+                    .find(quote as char)
+                    .map_or(tag.len(), |v| i + 1 + v);
+                (i + 1, end)
+            }
+            _ => {
+                let end = tag[i..]
+                    .find(|c: char| c.is_ascii_whitespace())
+                    .map_or(tag.len(), |v| i + v);
+                (i, end)
+            }
+        };
+        attributes.push((name, Some((offset + start, offset + end))));
+        i = end + 1;
+    }
+
+    attributes
+}
+
+/// Escapes the characters of a path that cannot appear as they are in a URL, or that would end it
+/// early in an unquoted CSS `url()`. Other non-ASCII characters are kept.
+pub(crate) fn percent_encode(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for c in path.chars() {
+        if c.is_ascii_control() || " \"#%'()<>?\\^`{|}".contains(c) {
+            encoded.push_str(&format!("%{:02X}", c as u32));
+        } else {
+            encoded.push(c);
+        }
+    }
+    encoded
+}
+
+/// Decodes the `%XX` escapes of a URL path, keeping invalid ones as they are.
+pub(crate) fn percent_decode(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|v| std::str::from_utf8(v).ok())
+            .and_then(|v| u8::from_str_radix(v, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8(decoded).unwrap_or_else(|_| path.into())
+}
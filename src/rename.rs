@@ -0,0 +1,187 @@
+use std::sync::OnceLock;
+
+use crate::{
+    check_path, decode_binary, decode_text,
+    error::{FobzError, Result},
+    escape, html, refs,
+    toc::split_href,
+    FobZ, CONTENT_EXTENSIONS, STYLE_EXTENSIONS,
+};
+
+impl FobZ {
+    /// Renames or moves a content, resource, or stylesheet, keeping every reference to it valid.
+    ///
+    /// The entry of its table, its payload, the manifest index and cover, and the references of
+    /// every content and stylesheet (`href`, `src`, `url()`, `@import`, ...) follow the entry to
+    /// its new path. When a content or stylesheet moves to another directory, its own relative
+    /// references are rewritten to still point at the same files. References are only rewritten
+    /// when their target changes, and keep their query and fragment. The stored search index is
+    /// dropped.
+    ///
+    /// # Parameters
+    /// - `path`: The current path of the entry.
+    /// - `new_path`: The new path of the entry, in the same directory tree (`contents/`,
+    ///   `resources/`, or `styles/`) and with an extension accepted there.
+    ///
+    /// # Returns
+    /// A result indicating success, `FobzError::UnknownPath` if no content, resource, or
+    /// stylesheet exists at `path`, `FobzError::InvalidPath` if `new_path` is not acceptable,
+    /// `FobzError::DuplicatePath` if an entry already exists at `new_path`, or an error if a
+    /// payload could not be read.
+    pub fn rename(&mut self, path: &str, new_path: String) -> Result<()> {
+        let (directory, extensions) = if self.contents.contains_key(path) {
+            ("contents/", CONTENT_EXTENSIONS)
+        } else if self.resources.contains_key(path) {
+            ("resources/", &[][..])
+        } else if self.styles.contains_key(path) {
+            ("styles/", STYLE_EXTENSIONS)
+        } else {
+            return Err(FobzError::UnknownPath(path.into()));
+        };
+        // Defaults are found by their path, so they cannot move.
+        check_path(path, directory, extensions)?;
+        check_path(&new_path, directory, extensions)?;
+
+        if new_path == path {
+            return Ok(());
+        }
+        if self.contents.contains_key(&new_path)
+            || self.resources.contains_key(&new_path)
+            || self.styles.contains_key(&new_path)
+        {
+            return Err(FobzError::DuplicatePath(new_path));
+        }
+
+        // Read everything first, so that a failure leaves the document untouched, and so that
+        // the moved payload no longer depends on the archive it was opened from.
+        let mut contents = vec![];
+        for file in self.contents.keys() {
+            if let Some(content) = self.load(&self.contents, file, decode_text)? {
+                contents.push((file.clone(), rewrite_html(content, file, path, &new_path)));
+            }
+        }
+        let mut styles = vec![];
+        for file in self.styles.keys() {
+            if let Some(style) = self.load(&self.styles, file, decode_text)? {
+                styles.push((file.clone(), rewrite_css(style, file, path, &new_path)));
+            }
+        }
+        self.load(&self.resources, path, decode_binary)?;
+
+        if let Some(cell) = self.contents.remove(path) {
+            self.contents.insert(new_path.clone(), cell);
+        }
+        if let Some(cell) = self.resources.remove(path) {
+            self.resources.insert(new_path.clone(), cell);
+        }
+        if let Some(cell) = self.styles.remove(path) {
+            self.styles.insert(new_path.clone(), cell);
+        }
+
+        let location = |file: String| if file == path { new_path.clone() } else { file };
+        for (file, content) in contents {
+            if let Some(content) = content {
+                self.contents
+                    .insert(location(file), OnceLock::from(content));
+            }
+        }
+        for (file, style) in styles {
+            if let Some(style) = style {
+                self.styles.insert(location(file), OnceLock::from(style));
+            }
+        }
+
+        self.toc.rename(path, &new_path);
+        if let Some(info) = self.tor.get_mut(path) {
+            info.path = new_path.clone();
+        }
+        if let Some(info) = self.tos.get_mut(path) {
+            info.path = new_path.clone();
+        }
+
+        if let Some(index) = renamed_href(self.manifest.get_index(), path, &new_path) {
+            self.manifest.set_index(index);
+        }
+        if let Some(cover) = renamed_href(self.manifest.get_cover(), path, &new_path) {
+            self.manifest.set_cover(cover);
+        }
+
+        self.search = None;
+        Ok(())
+    }
+}
+
+/// Rewrites the references of the HTML of `file` after `path` moved to `new_path`.
+///
+/// # Returns
+/// The new HTML, or `None` if no reference changed.
+fn rewrite_html(html: &str, file: &str, path: &str, new_path: &str) -> Option<String> {
+    let location = if file == path { new_path } else { file };
+    let references = refs::html_references(html);
+
+    let mut changed = false;
+    let text = refs::rewrite(html, &references, |href| {
+        let href = relocate(&html::unescape(href), file, location, path, new_path)?;
+        changed = true;
+        Some(escape(&href))
+    });
+
+    changed.then_some(text)
+}
+
+/// Rewrites the references of the stylesheet `file` after `path` moved to `new_path`.
+///
+/// # Returns
+/// The new stylesheet, or `None` if no reference changed.
+fn rewrite_css(css: &str, file: &str, path: &str, new_path: &str) -> Option<String> {
+    let location = if file == path { new_path } else { file };
+    let references = refs::css_references(css);
+
+    let mut changed = false;
+    let text = refs::rewrite(css, &references, |href| {
+        let href = relocate(href, file, location, path, new_path)?;
+        changed = true;
+        Some(href)
+    });
+
+    changed.then_some(text)
+}
+
+/// Rewrites a reference of the file `source`, now at `location`, after `path` moved to
+/// `new_path`.
+///
+/// # Returns
+/// The new reference, or `None` if it still points at the same file.
+fn relocate(
+    href: &str,
+    source: &str,
+    location: &str,
+    path: &str,
+    new_path: &str,
+) -> Option<String> {
+    if href.is_empty() || href.starts_with(['#', '?']) || refs::is_external(href) {
+        return None;
+    }
+
+    let file = refs::split_reference(href).0;
+    let target = refs::resolve(source, &file)?;
+    let target = if target == path { new_path } else { &target };
+    if refs::resolve(location, &file).as_deref() == Some(target) {
+        return None;
+    }
+
+    let suffix = href.find(['?', '#']).map_or("", |v| &href[v..]);
+    Some(refs::percent_encode(&refs::relative(location, target)) + suffix)
+}
+
+/// Rewrites a manifest reference (`path` or `path#anchor`) pointing at `path`.
+///
+/// # Returns
+/// The new reference, or `None` if it points elsewhere.
+fn renamed_href(href: &str, path: &str, new_path: &str) -> Option<String> {
+    match split_href(href) {
+        (file, None) if file == path => Some(new_path.into()),
+        (file, Some(anchor)) if file == path => Some(format!("{}#{}", new_path, anchor)),
+        _ => None,
+    }
+}
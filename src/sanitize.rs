@@ -0,0 +1,650 @@
+//! HTML sanitization of document contents.
+//!
+//! A [`SanitizePolicy`] decides which elements, attributes, and URL schemes a content may use.
+//! Whatever the policy (except [`SanitizePolicy::Off`]), `<script>` elements, event handler
+//! attributes (`onclick`, ...), and `javascript:` or `vbscript:` URLs are removed, while the
+//! document structure (`html`, `head`, `body`, `title`, and `meta`) is always kept.
+//!
+//! Elements that are not allowed are removed but their content is kept, except for elements whose
+//! content is not text meant for the reader (e.g., `<style>`, `<iframe>`, `<svg>`), which are
+//! removed entirely. Attributes that are not allowed are removed, and so are URL attributes
+//! (`href`, `src`, ...) using a scheme that is not allowed. Relative URLs are always allowed.
+//! Comments, processing instructions, and other declarations are removed as well, except a doctype
+//! at the start of the content, which is written back as `<!DOCTYPE html>`.
+//!
+//! The policy can be applied when opening a document (see
+//! [`OpenOptions`](crate::options::OpenOptions)), when adding contents (see
+//! [`FobZ::set_sanitize_policy`]), or on demand with [`FobZ::sanitize`].
+
+use std::{fmt, mem};
+
+use crate::{
+    decode_text,
+    error::Result,
+    html::{self, Token},
+    FobZ,
+};
+
+// Elements kept by every policy, so that contents remain HTML documents.
+const DOCUMENT_ELEMENTS: &[&str] = &["html", "head", "body", "title", "meta"];
+
+// Elements allowed by the strict policy: text, lists, tables, links, and images.
+const STRICT_ELEMENTS: &[&str] = &[
+    "a",
+    "abbr",
+    "address",
+    "article",
+    "aside",
+    "b",
+    "bdi",
+    "bdo",
+    "blockquote",
+    "br",
+    "caption",
+    "cite",
+    "code",
+    "col",
+    "colgroup",
+    "dd",
+    "del",
+    "details",
+    "dfn",
+    "div",
+    "dl",
+    "dt",
+    "em",
+    "figcaption",
+    "figure",
+    "footer",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "i",
+    "img",
+    "ins",
+    "kbd",
+    "li",
+    "main",
+    "mark",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "q",
+    "rp",
+    "rt",
+    "ruby",
+    "s",
+    "samp",
+    "section",
+    "small",
+    "span",
+    "strong",
+    "sub",
+    "summary",
+    "sup",
+    "table",
+    "tbody",
+    "td",
+    "tfoot",
+    "th",
+    "thead",
+    "time",
+    "tr",
+    "u",
+    "ul",
+    "var",
+    "wbr",
+];
+
+// Elements allowed by the standard policy beside the strict ones: styles and media.
+const STANDARD_ELEMENTS: &[&str] = &[
+    "area", "audio", "link", "map", "picture", "source", "style", "track", "video",
+];
+
+// Elements refused by the permissive policy, which can run code or load other documents, or
+// whose content browsers parse differently than `html::tokenize` (raw text, or SVG and MathML).
+const BLOCKED_ELEMENTS: &[&str] = &[
+    "animate",
+    "applet",
+    "base",
+    "embed",
+    "frame",
+    "frameset",
+    "iframe",
+    "math",
+    "noembed",
+    "noframes",
+    "noscript",
+    "object",
+    "plaintext",
+    "script",
+    "set",
+    "svg",
+    "textarea",
+    "xmp",
+];
+
+// Attributes allowed by the strict policy. A trailing `*` allows every attribute with the prefix.
+const STRICT_ATTRIBUTES: &[&str] = &[
+    "alt",
+    "charset",
+    "cite",
+    "colspan",
+    "content",
+    "datetime",
+    "dir",
+    "epub:type",
+    "headers",
+    "height",
+    "href",
+    "id",
+    "lang",
+    "name",
+    "open",
+    "reversed",
+    "rowspan",
+    "scope",
+    "span",
+    "src",
+    "start",
+    "title",
+    "type",
+    "value",
+    "width",
+    "xml:lang",
+    "xmlns",
+    "xmlns:*",
+];
+
+// Attributes allowed by the standard policy beside the strict ones.
+const STANDARD_ATTRIBUTES: &[&str] = &[
+    "aria-*", "class", "controls", "coords", "data-*", "default", "hreflang", "kind", "label",
+    "loop", "media", "muted", "poster", "preload", "rel", "role", "shape", "sizes", "srclang",
+    "srcset", "style", "usemap",
+];
+
+// Attributes refused by the permissive policy beside event handlers.
+const BLOCKED_ATTRIBUTES: &[&str] = &["http-equiv", "srcdoc"];
+
+// Attributes holding a URL, whose scheme is checked.
+const URL_ATTRIBUTES: &[&str] = &[
+    "action",
+    "background",
+    "cite",
+    "data",
+    "formaction",
+    "href",
+    "poster",
+    "src",
+    "xlink:href",
+];
+
+// Schemes of URLs refused by every policy, as they run code.
+const SCRIPT_SCHEMES: &[&str] = &["javascript", "vbscript"];
+
+// Elements removed along with their content when they are not allowed. Those read as raw text
+// (e.g., `<textarea>`) are among them, as their text would be read as markup once they are gone.
+const OPAQUE_ELEMENTS: &[&str] = &[
+    "applet",
+    "frameset",
+    "iframe",
+    "math",
+    "noembed",
+    "noframes",
+    "noscript",
+    "object",
+    "plaintext",
+    "script",
+    "style",
+    "svg",
+    "template",
+    "textarea",
+    "xmp",
+];
+
+// Elements in which a self-closing start tag has no end tag, as in SVG and MathML.
+const FOREIGN_ELEMENTS: &[&str] = &["math", "svg"];
+
+// Elements that never have an end tag.
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+/// Lists what a custom sanitization policy allows.
+///
+/// # Fields
+/// - `elements`: The lowercase names of the allowed elements.
+/// - `attributes`: The lowercase names of the allowed attributes, on any allowed element. A name
+///   ending with `*` (e.g., `data-*`) allows every attribute starting with what precedes it.
+/// - `url_schemes`: The lowercase schemes allowed in URL attributes (e.g., `https`).
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct AllowList {
+    pub elements: Vec<String>,
+    pub attributes: Vec<String>,
+    pub url_schemes: Vec<String>,
+}
+
+/// Decides what the HTML of contents may contain.
+///
+/// # Variants
+/// - `Off`: Contents are kept as they are.
+/// - `Strict`: Only text, lists, tables, links, and images, without styles; URLs may use `http`,
+///   `https`, and `mailto`.
+/// - `Standard`: The strict elements along with stylesheets, classes, inline styles, and audio
+///   and video; URLs may also use `tel`.
+/// - `Permissive`: Every element and attribute except those that can run code or load other
+///   documents (`<iframe>`, `<object>`, `<embed>`, ...), and those whose content browsers parse
+///   differently (`<noscript>`, `<textarea>`, `<svg>`, ...); URLs may also use `ftp` and `data`.
+/// - `Custom`: Only what an allow-list contains.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum SanitizePolicy {
+    #[default]
+    Off,
+    Strict,
+    Standard,
+    Permissive,
+    Custom(AllowList),
+}
+
+/// Identifies what a sanitization removed.
+///
+/// # Variants
+/// - `Element`: An element was removed, keeping its content.
+/// - `Content`: An element was removed along with its content.
+/// - `Attribute`: An attribute was removed.
+/// - `Url`: A URL attribute was removed because of its scheme.
+/// - `Comment`: A comment, a processing instruction, or another declaration was removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemovalKind {
+    Element,
+    Content,
+    Attribute,
+    Url,
+    Comment,
+}
+
+/// Represents a single part of a content removed by a sanitization.
+///
+/// # Fields
+/// - `kind`: What was removed.
+/// - `path`: The path of the content.
+/// - `element`: The name of the removed element, or of the element holding the removed attribute;
+///   `#comment` for comments and declarations, or `!doctype` for a misplaced doctype.
+/// - `attribute`: The name of the removed attribute, `None` for elements.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Removal {
+    pub kind: RemovalKind,
+    pub path: String,
+    pub element: String,
+    pub attribute: Option<String>,
+}
+
+impl fmt::Display for Removal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let attribute = self.attribute.as_deref().unwrap_or_default();
+        match self.kind {
+            RemovalKind::Element => write!(f, "{}: removed <{}>", self.path, self.element),
+            RemovalKind::Content => write!(
+                f,
+                "{}: removed <{}> and its content",
+                self.path, self.element
+            ),
+            RemovalKind::Attribute => write!(
+                f,
+                "{}: removed attribute '{}' of <{}>",
+                self.path, attribute, self.element
+            ),
+            RemovalKind::Url => write!(
+                f,
+                "{}: removed URL '{}' of <{}>",
+                self.path, attribute, self.element
+            ),
+            RemovalKind::Comment => write!(f, "{}: removed a comment or declaration", self.path),
+        }
+    }
+}
+
+/// Collects everything removed by sanitizations.
+///
+/// # Fields
+/// - `removals`: The removals, in the order they were made.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SanitizeReport {
+    removals: Vec<Removal>,
+}
+
+impl SanitizeReport {
+    /// Returns every removal made.
+    pub fn removals(&self) -> &[Removal] {
+        &self.removals
+    }
+
+    /// Checks whether nothing was removed.
+    ///
+    /// # Returns
+    /// `true` if no removal was made, otherwise `false`.
+    pub fn is_clean(&self) -> bool {
+        self.removals.is_empty()
+    }
+
+    /// Moves the removals of another report at the end of this one.
+    pub(crate) fn append(&mut self, other: &mut SanitizeReport) {
+        self.removals.append(&mut other.removals);
+    }
+
+    /// Records a new removal.
+    fn push(&mut self, kind: RemovalKind, path: &str, element: &str, attribute: Option<&str>) {
+        self.removals.push(Removal {
+            kind,
+            path: path.into(),
+            element: element.into(),
+            attribute: attribute.map(Into::into),
+        });
+    }
+}
+
+impl SanitizePolicy {
+    /// Removes what the policy does not allow from the HTML of a content.
+    ///
+    /// Kept tags are always rewritten from what was parsed, with lowercase names and
+    /// double-quoted values, so that browsers cannot read them differently. Text is copied as
+    /// written. Comments and declarations are removed, except a leading doctype, which is
+    /// rewritten as `<!DOCTYPE html>`.
+    ///
+    /// # Parameters
+    /// - `path`: The path of the content, recorded in the report.
+    /// - `html`: The HTML to sanitize.
+    ///
+    /// # Returns
+    /// The sanitized HTML, and a report of what was removed.
+    pub fn sanitize(&self, path: &str, html: &str) -> (String, SanitizeReport) {
+        let mut report = SanitizeReport::default();
+        if *self == SanitizePolicy::Off {
+            return (html.into(), report);
+        }
+
+        let mut output = String::with_capacity(html.len());
+        // The element being removed with its content, and how many of them are open inside it.
+        let mut skipped: Option<(String, usize)> = None;
+        // Whether only whitespace and declarations were read so far, so that a doctype is in place.
+        let mut leading = true;
+
+        for (span, token) in html::tokenize_spans(html) {
+            if let Some((element, depth)) = &mut skipped {
+                match &token {
+                    Token::Start {
+                        name, self_closing, ..
+                    } if name == element && !is_closed(name, *self_closing) => *depth += 1,
+                    Token::End(name) if name == element => {
+                        *depth -= 1;
+                        if *depth == 0 {
+                            skipped = None;
+                        }
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+
+            match &token {
+                Token::Text(text) if text.trim().is_empty() => {}
+                Token::Comment(_) | Token::Declaration(_) => {}
+                _ => leading = false,
+            }
+
+            match &token {
+                // Markup hidden from the reader may be parsed differently by browsers, so it is
+                // not kept as written.
+                Token::Declaration(declaration) if leading && is_doctype(declaration) => {
+                    output.push_str("<!DOCTYPE html>");
+                    leading = false;
+                }
+                Token::Declaration(declaration) if is_doctype(declaration) => {
+                    report.push(RemovalKind::Comment, path, "!doctype", None);
+                }
+                Token::Comment(_) | Token::Declaration(_) => {
+                    report.push(RemovalKind::Comment, path, "#comment", None);
+                }
+                Token::Start {
+                    name,
+                    attributes,
+                    self_closing,
+                } => {
+                    if !self.allows_element(name) {
+                        let opaque = OPAQUE_ELEMENTS.contains(&name.as_str());
+                        // HTML ignores `/>` on other elements than void and foreign ones.
+                        if opaque && !is_closed(name, *self_closing) {
+                            skipped = Some((name.clone(), 1));
+                        }
+                        let kind = if opaque {
+                            RemovalKind::Content
+                        } else {
+                            RemovalKind::Element
+                        };
+                        report.push(kind, path, name, None);
+                        continue;
+                    }
+
+                    let mut kept = vec![];
+                    for attribute in attributes {
+                        match self.check_attribute(&attribute.name, attribute.value) {
+                            None => kept.push(attribute),
+                            Some(kind) => report.push(kind, path, name, Some(&attribute.name)),
+                        }
+                    }
+                    output.push('<');
+                    output.push_str(name);
+                    for attribute in kept {
+                        output.push(' ');
+                        output.push_str(&attribute.name);
+                        if let Some(value) = attribute.value {
+                            output.push_str("=\"");
+                            output.push_str(&value.replace('"', "&quot;"));
+                            output.push('"');
+                        }
+                    }
+                    output.push_str(if *self_closing { "/>" } else { ">" });
+                }
+                // The start tag was reported already.
+                Token::End(name) if !self.allows_element(name) => {}
+                Token::End(name) => {
+                    output.push_str("</");
+                    output.push_str(name);
+                    output.push('>');
+                }
+                _ => output.push_str(&html[span]),
+            }
+        }
+
+        (output, report)
+    }
+
+    /// Checks whether the policy allows an element.
+    fn allows_element(&self, name: &str) -> bool {
+        if name == "script" {
+            return false;
+        }
+        if DOCUMENT_ELEMENTS.contains(&name) {
+            return true;
+        }
+
+        match self {
+            SanitizePolicy::Off => true,
+            SanitizePolicy::Strict => STRICT_ELEMENTS.contains(&name),
+            SanitizePolicy::Standard => {
+                STRICT_ELEMENTS.contains(&name) || STANDARD_ELEMENTS.contains(&name)
+            }
+            SanitizePolicy::Permissive => !BLOCKED_ELEMENTS.contains(&name),
+            SanitizePolicy::Custom(list) => list.elements.iter().any(|v| v == name),
+        }
+    }
+
+    /// Checks an attribute against the policy.
+    ///
+    /// # Returns
+    /// `None` if the attribute is kept, or the reason for removing it.
+    fn check_attribute(&self, name: &str, value: Option<&str>) -> Option<RemovalKind> {
+        // Names that cannot be written back as they were parsed (e.g., `="`) are dropped.
+        let allowed = !name.starts_with("on")
+            && !name.contains(['"', '\'', '<', '='])
+            && match self {
+                SanitizePolicy::Off => true,
+                SanitizePolicy::Strict => matches_any(STRICT_ATTRIBUTES, name),
+                SanitizePolicy::Standard => {
+                    matches_any(STRICT_ATTRIBUTES, name) || matches_any(STANDARD_ATTRIBUTES, name)
+                }
+                SanitizePolicy::Permissive => !BLOCKED_ATTRIBUTES.contains(&name),
+                SanitizePolicy::Custom(list) => list
+                    .attributes
+                    .iter()
+                    .any(|v| matches_any(&[v.as_str()], name)),
+            };
+        if !allowed {
+            return Some(RemovalKind::Attribute);
+        }
+
+        let value = html::unescape(value.unwrap_or_default());
+        let safe = if URL_ATTRIBUTES.contains(&name) {
+            scheme(&value).is_none_or(|v| self.allows_scheme(&v))
+        } else if name == "style" {
+            // Old browsers run scripts from styles as well.
+            let value: String = value.chars().filter(|v| !v.is_whitespace()).collect();
+            let value = value.to_ascii_lowercase();
+            !value.contains("expression(") && !SCRIPT_SCHEMES.iter().any(|v| value.contains(v))
+        } else {
+            true
+        };
+        (!safe).then_some(RemovalKind::Url)
+    }
+
+    /// Checks whether the policy allows a lowercase URL scheme.
+    fn allows_scheme(&self, scheme: &str) -> bool {
+        if SCRIPT_SCHEMES.contains(&scheme) {
+            return false;
+        }
+
+        let schemes: &[&str] = match self {
+            SanitizePolicy::Off => return true,
+            SanitizePolicy::Strict => &["http", "https", "mailto"],
+            SanitizePolicy::Standard => &["http", "https", "mailto", "tel"],
+            SanitizePolicy::Permissive => &["http", "https", "mailto", "tel", "ftp", "data"],
+            SanitizePolicy::Custom(list) => return list.url_schemes.iter().any(|v| v == scheme),
+        };
+        schemes.contains(&scheme)
+    }
+}
+
+impl FobZ {
+    /// Retrieves the policy applied to contents added to the document.
+    pub fn get_sanitize_policy(&self) -> &SanitizePolicy {
+        &self.sanitize_policy
+    }
+
+    /// Sets the policy applied to contents added to the document.
+    ///
+    /// Contents added from then on with [`FobZ::add_content`], [`FobZ::upsert_content`],
+    /// [`FobZ::insert_content_at`], or [`FobZ::add_child_content`] are sanitized, and what is
+    /// removed from them is recorded until [`FobZ::take_sanitize_report`] is called. Contents
+    /// already in the document are left as they are; see [`FobZ::sanitize`].
+    ///
+    /// # Parameters
+    /// - `policy`: The new sanitization policy.
+    pub fn set_sanitize_policy(&mut self, policy: SanitizePolicy) {
+        self.sanitize_policy = policy;
+    }
+
+    /// Takes what was removed from contents while opening the document and since, leaving the
+    /// recorded report empty.
+    ///
+    /// # Returns
+    /// The removals made by the sanitization policy of the document.
+    pub fn take_sanitize_report(&mut self) -> SanitizeReport {
+        mem::take(&mut self.sanitize_report)
+    }
+
+    /// Sanitizes every content of the document now.
+    ///
+    /// This does not change the policy applied to contents added later.
+    ///
+    /// # Parameters
+    /// - `policy`: The policy to apply.
+    ///
+    /// # Returns
+    /// A result containing what was removed, or an error if a content could not be read.
+    pub fn sanitize(&mut self, policy: &SanitizePolicy) -> Result<SanitizeReport> {
+        let mut paths: Vec<String> = self.contents.keys().cloned().collect();
+        paths.sort_unstable();
+
+        let mut report = SanitizeReport::default();
+        for path in paths {
+            let Some(content) = self.load(&self.contents, &path, decode_text)? else {
+                continue;
+            };
+            let (sanitized, mut removals) = policy.sanitize(&path, content);
+            report.append(&mut removals);
+            if sanitized != *content {
+                self.contents.insert(path, sanitized.into());
+                self.search = None;
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Applies the sanitization policy of the document to a content being added, recording what
+    /// is removed.
+    pub(crate) fn sanitize_content(&mut self, path: &str, content: String) -> String {
+        if self.sanitize_policy == SanitizePolicy::Off {
+            return content;
+        }
+
+        let (content, mut report) = self.sanitize_policy.sanitize(path, &content);
+        self.sanitize_report.append(&mut report);
+        content
+    }
+}
+
+/// Checks whether an attribute name matches one of a list of names and `prefix*` patterns.
+fn matches_any(list: &[&str], name: &str) -> bool {
+    list.iter().any(|v| match v.strip_suffix('*') {
+        Some(prefix) => name.starts_with(prefix),
+        None => *v == name,
+    })
+}
+
+/// Extracts the lowercase scheme of a URL, ignoring the whitespace and control characters that
+/// browsers skip (e.g., `java\tscript:`).
+fn scheme(url: &str) -> Option<String> {
+    let url: String = url
+        .chars()
+        .filter(|v| !v.is_ascii_whitespace() && !v.is_ascii_control())
+        .collect();
+    let colon = url.find(':')?;
+    let scheme = &url[..colon];
+
+    let valid = scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    valid.then(|| scheme.to_ascii_lowercase())
+}
+
+/// Checks whether a start tag has no end tag to look for.
+fn is_closed(name: &str, self_closing: bool) -> bool {
+    VOID_ELEMENTS.contains(&name) || (self_closing && FOREIGN_ELEMENTS.contains(&name))
+}
+
+/// Checks whether a declaration is a doctype (`<!DOCTYPE ...>`, in any case).
+fn is_doctype(declaration: &str) -> bool {
+    declaration
+        .as_bytes()
+        .get(..9)
+        .is_some_and(|v| v.eq_ignore_ascii_case(b"<!doctype"))
+}
@@ -0,0 +1,595 @@
+//! Full-text search over the sections of a document.
+//!
+//! A [`SearchIndex`] holds the plain text of every section (as produced by
+//! [`FobZ::to_text_sections`] with [`TextFormat::Plain`]) split into lowercase words, and answers
+//! queries with ranked [`SearchHit`]s. Queries are made of clauses separated by whitespace, all of
+//! which must match a section:
+//!
+//! - `word`: the word itself, case-insensitively.
+//! - `wor*`: any word starting with `wor`.
+//! - `"some words"`: the words next to each other, in this order; the last one may end with `*`.
+//!
+//! A clause spanning several words once split (e.g., `don't`) is matched as a phrase.
+//!
+//! The index can be stored inside the archive as `search/index.bin` so that it does not have to be
+//! rebuilt every time the document is opened; see [`FobZ::store_search_index`].
+
+use std::{collections::HashMap, fmt};
+
+use crate::{
+    error::{FobzError, Result},
+    options::TextFormat,
+    FobZ,
+};
+
+/// The entry of the archive holding a stored index.
+pub(crate) const INDEX_ENTRY: &str = "search/index.bin";
+
+// The first bytes of a stored index, followed by the version of its encoding.
+const MAGIC: &[u8] = b"FOBZSRCH";
+const ENCODING_VERSION: u32 = 1;
+
+// The number of characters of context kept on each side of the first match of a snippet.
+const SNIPPET_CONTEXT: usize = 40;
+
+/// Represents a section matching a query.
+///
+/// # Fields
+/// - `path`: The path of the content in the archive.
+/// - `title`: The title of the section in the table of contents.
+/// - `score`: The relevance of the section; hits are sorted by decreasing score.
+/// - `matches`: The start and end character offsets of every match in the text of the section,
+///   in order. The text is available from [`SearchIndex::get_text`].
+/// - `snippet`: An excerpt of the text around the first match, on a single line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchHit {
+    pub path: String,
+    pub title: String,
+    pub score: f32,
+    pub matches: Vec<(usize, usize)>,
+    pub snippet: String,
+}
+
+/// A word of a section.
+///
+/// # Fields
+/// - `term`: The position of the lowercase word in `SearchIndex.terms`.
+/// - `start`: The character offset where the word starts in the text of the section.
+/// - `end`: The character offset where the word ends in the text of the section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Word {
+    term: u32,
+    start: u32,
+    end: u32,
+}
+
+/// A section of the index.
+///
+/// # Fields
+/// - `path`: The path of the content in the archive.
+/// - `title`: The title of the section in the table of contents.
+/// - `text`: The plain text of the section.
+/// - `words`: The words of the text, in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Section {
+    path: String,
+    title: String,
+    text: String,
+    words: Vec<Word>,
+}
+
+/// Represents an inverted index of the words of every section of a document.
+///
+/// # Fields
+/// - `sections`: The indexed sections, in table of contents order.
+/// - `terms`: Every distinct lowercase word, sorted.
+/// - `postings`: For each term, the section and word positions where it occurs.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SearchIndex {
+    sections: Vec<Section>,
+    terms: Vec<String>,
+    postings: Vec<Vec<(u32, u32)>>,
+}
+
+impl fmt::Debug for SearchIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SearchIndex")
+            .field("sections", &self.sections.len())
+            .field("terms", &self.terms.len())
+            .finish()
+    }
+}
+
+/// A clause of a query: words to find next to each other, the last one possibly a prefix.
+struct Clause {
+    words: Vec<String>,
+    prefix: bool,
+}
+
+impl SearchIndex {
+    /// Builds the index of a list of sections.
+    ///
+    /// # Parameters
+    /// - `sections`: The path, title, and plain text of each section.
+    fn new(sections: Vec<(String, String, String)>) -> Self {
+        let mut ids: HashMap<String, u32> = HashMap::new();
+        let mut terms: Vec<String> = vec![];
+
+        let mut indexed = vec![];
+        for (path, title, text) in sections {
+            let words = tokenize(&text)
+                .into_iter()
+                .map(|(word, start, end)| {
+                    let term = *ids.entry(word).or_insert_with_key(|word| {
+                        terms.push(word.clone());
+                        (terms.len() - 1) as u32
+                    });
+                    Word {
+                        term,
+                        start: start as u32,
+                        end: end as u32,
+                    }
+                })
+                .collect();
+            indexed.push(Section {
+                path,
+                title,
+                text,
+                words,
+            });
+        }
+
+        // Sort the terms so that prefixes are contiguous ranges, renumbering the words.
+        let mut order: Vec<u32> = (0..terms.len() as u32).collect();
+        order.sort_unstable_by(|a, b| terms[*a as usize].cmp(&terms[*b as usize]));
+        let mut renumbered = vec![0; terms.len()];
+        for (new, old) in order.iter().enumerate() {
+            renumbered[*old as usize] = new as u32;
+        }
+        for word in indexed.iter_mut().flat_map(|v| v.words.iter_mut()) {
+            word.term = renumbered[word.term as usize];
+        }
+        let terms = order.iter().map(|v| terms[*v as usize].clone()).collect();
+
+        Self::with_postings(indexed, terms)
+    }
+
+    /// Completes an index from its sections and sorted terms by listing where each term occurs.
+    fn with_postings(sections: Vec<Section>, terms: Vec<String>) -> Self {
+        let mut postings = vec![vec![]; terms.len()];
+        for (i, section) in sections.iter().enumerate() {
+            for (position, word) in section.words.iter().enumerate() {
+                postings[word.term as usize].push((i as u32, position as u32));
+            }
+        }
+
+        SearchIndex {
+            sections,
+            terms,
+            postings,
+        }
+    }
+
+    /// Finds the sections matching a query, most relevant first.
+    ///
+    /// Sections are scored by how often each clause matches them, weighted by how rare the
+    /// clause is across the document. Sections with the same score keep their table of contents
+    /// order.
+    ///
+    /// # Parameters
+    /// - `query`: The query, as described in the [module documentation](self).
+    ///
+    /// # Returns
+    /// The matching sections, or an empty list if the query has no words.
+    pub fn search(&self, query: &str) -> Vec<SearchHit> {
+        let clauses = parse_query(query);
+        if clauses.is_empty() {
+            return vec![];
+        }
+
+        // The matches of every clause, by section.
+        let mut found: Vec<HashMap<u32, Vec<(usize, usize)>>> = vec![];
+        for clause in &clauses {
+            let matches = self.find(clause);
+            if matches.is_empty() {
+                return vec![];
+            }
+            found.push(matches);
+        }
+
+        let total = self.sections.len() as f32;
+        let mut hits: Vec<(u32, SearchHit)> = vec![];
+        for section in found[0].keys() {
+            if !found.iter().all(|v| v.contains_key(section)) {
+                continue;
+            }
+
+            let mut score = 0.0;
+            let mut matches = vec![];
+            for clause in &found {
+                let ranges = &clause[section];
+                let idf = (1.0 + total / clause.len() as f32).ln();
+                score += (1.0 + (ranges.len() as f32).ln()) * idf;
+                matches.extend_from_slice(ranges);
+            }
+            matches.sort_unstable();
+            matches.dedup();
+
+            let indexed = &self.sections[*section as usize];
+            hits.push((
+                *section,
+                SearchHit {
+                    path: indexed.path.clone(),
+                    title: indexed.title.clone(),
+                    score,
+                    snippet: snippet(&indexed.text, matches[0]),
+                    matches,
+                },
+            ));
+        }
+
+        hits.sort_by(|(a, hit_a), (b, hit_b)| {
+            hit_b.score.total_cmp(&hit_a.score).then_with(|| a.cmp(b))
+        });
+        hits.into_iter().map(|(_, hit)| hit).collect()
+    }
+
+    /// Retrieves the plain text of an indexed section, which match offsets refer to.
+    ///
+    /// # Parameters
+    /// - `path`: The path of the content.
+    ///
+    /// # Returns
+    /// The text, or `None` if no section has this path.
+    pub fn get_text(&self, path: &str) -> Option<&str> {
+        self.sections
+            .iter()
+            .find(|v| v.path == path)
+            .map(|v| v.text.as_str())
+    }
+
+    /// Returns the number of indexed sections.
+    pub fn len(&self) -> usize {
+        self.sections.len()
+    }
+
+    /// Checks whether no section is indexed.
+    pub fn is_empty(&self) -> bool {
+        self.sections.is_empty()
+    }
+
+    /// Encodes the index in the binary form stored in archives.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        put_u32(&mut bytes, ENCODING_VERSION);
+
+        put_u32(&mut bytes, self.terms.len() as u32);
+        for term in &self.terms {
+            put_str(&mut bytes, term);
+        }
+
+        put_u32(&mut bytes, self.sections.len() as u32);
+        for section in &self.sections {
+            put_str(&mut bytes, &section.path);
+            put_str(&mut bytes, &section.title);
+            put_str(&mut bytes, &section.text);
+            put_u32(&mut bytes, section.words.len() as u32);
+            for word in &section.words {
+                put_u32(&mut bytes, word.term);
+                put_u32(&mut bytes, word.start);
+                put_u32(&mut bytes, word.end);
+            }
+        }
+
+        bytes
+    }
+
+    /// Decodes an index encoded by [`SearchIndex::to_bytes`].
+    ///
+    /// # Parameters
+    /// - `bytes`: The encoded index.
+    ///
+    /// # Returns
+    /// A result containing the index, or `FobzError::InvalidSearchIndex` if the bytes are
+    /// truncated, inconsistent, or use another version of the encoding.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut reader = Reader { bytes, position: 0 };
+
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(FobzError::InvalidSearchIndex("not a search index".into()));
+        }
+        let version = reader.u32()?;
+        if version != ENCODING_VERSION {
+            return Err(FobzError::InvalidSearchIndex(format!(
+                "unsupported encoding version {}",
+                version
+            )));
+        }
+
+        let mut terms: Vec<String> = vec![];
+        for _ in 0..reader.u32()? {
+            let term = reader.string()?;
+            if terms.last().is_some_and(|v| *v >= term) {
+                return Err(FobzError::InvalidSearchIndex("terms are not sorted".into()));
+            }
+            terms.push(term);
+        }
+
+        let mut sections = vec![];
+        for _ in 0..reader.u32()? {
+            let path = reader.string()?;
+            let title = reader.string()?;
+            let text = reader.string()?;
+            let length = text.chars().count() as u32;
+
+            let mut words = vec![];
+            for _ in 0..reader.u32()? {
+                let word = Word {
+                    term: reader.u32()?,
+                    start: reader.u32()?,
+                    end: reader.u32()?,
+                };
+                if word.term as usize >= terms.len() || word.start > word.end || word.end > length {
+                    return Err(FobzError::InvalidSearchIndex(format!(
+                        "word out of range in '{}'",
+                        path
+                    )));
+                }
+                words.push(word);
+            }
+            sections.push(Section {
+                path,
+                title,
+                text,
+                words,
+            });
+        }
+
+        if reader.position != bytes.len() {
+            return Err(FobzError::InvalidSearchIndex("trailing bytes".into()));
+        }
+
+        Ok(Self::with_postings(sections, terms))
+    }
+
+    /// Lists the matches of a clause, by section, as character ranges.
+    fn find(&self, clause: &Clause) -> HashMap<u32, Vec<(usize, usize)>> {
+        let mut matches: HashMap<u32, Vec<(usize, usize)>> = HashMap::new();
+        let last = clause.words.len() - 1;
+
+        // Terms the word at `i` of the clause may be.
+        let candidates = |i: usize| -> std::ops::Range<usize> {
+            let word = &clause.words[i];
+            let start = self.terms.partition_point(|v| v < word);
+            let end = if clause.prefix && i == last {
+                start + self.terms[start..].partition_point(|v| v.starts_with(word.as_str()))
+            } else {
+                start + usize::from(self.terms.get(start) == Some(word))
+            };
+            start..end
+        };
+
+        let first = candidates(0);
+        let rest: Vec<std::ops::Range<usize>> = (1..clause.words.len()).map(candidates).collect();
+        if rest.iter().any(|v| v.is_empty()) {
+            return matches;
+        }
+
+        for term in first {
+            for (section, position) in &self.postings[term] {
+                let words = &self.sections[*section as usize].words;
+                let position = *position as usize;
+                let Some(following) = words.get(position + 1..position + clause.words.len()) else {
+                    continue;
+                };
+                if following
+                    .iter()
+                    .zip(&rest)
+                    .all(|(word, terms)| terms.contains(&(word.term as usize)))
+                {
+                    let end = following.last().unwrap_or(&words[position]).end;
+                    matches
+                        .entry(*section)
+                        .or_default()
+                        .push((words[position].start as usize, end as usize));
+                }
+            }
+        }
+
+        for ranges in matches.values_mut() {
+            ranges.sort_unstable();
+        }
+        matches
+    }
+}
+
+impl FobZ {
+    /// Builds the search index of the document.
+    ///
+    /// Every content listed in the table of contents is indexed once, with the title of its
+    /// first section.
+    ///
+    /// # Returns
+    /// A result containing the index, or an error if a content could not be read.
+    pub fn build_search_index(&self) -> Result<SearchIndex> {
+        let sections = self
+            .convert_sections(TextFormat::Plain, false)?
+            .into_iter()
+            .map(|(path, text)| {
+                let title = self
+                    .toc
+                    .get(path)
+                    .map_or_else(String::new, |v| v.title.clone());
+                (path.to_string(), title, text)
+            })
+            .collect();
+
+        Ok(SearchIndex::new(sections))
+    }
+
+    /// Builds the search index of the document and keeps it to be saved with the archive, as
+    /// `search/index.bin`.
+    ///
+    /// The stored index is dropped when a content is added, replaced, or removed, when sections
+    /// are moved, swapped, reordered, or added for an anchor, or when an entry is renamed, so that
+    /// a saved index always matches the contents and the table of contents; call this again once
+    /// editing is done.
+    ///
+    /// # Returns
+    /// A result indicating success or an error if a content could not be read.
+    pub fn store_search_index(&mut self) -> Result<()> {
+        self.search = Some(self.build_search_index()?);
+        Ok(())
+    }
+
+    /// Retrieves the stored search index, read from the archive or kept by
+    /// [`FobZ::store_search_index`].
+    ///
+    /// An index that cannot be decoded is ignored when the archive is opened.
+    pub fn get_search_index(&self) -> Option<&SearchIndex> {
+        self.search.as_ref()
+    }
+
+    /// Drops the stored search index, so that it is not saved with the archive.
+    pub fn remove_search_index(&mut self) {
+        self.search = None;
+    }
+
+    /// Finds the sections matching a query, most relevant first.
+    ///
+    /// The stored index is used when there is one; otherwise an index is built for this query
+    /// only, so prefer [`FobZ::build_search_index`] to run several queries.
+    ///
+    /// # Parameters
+    /// - `query`: The query, as described in the [`search`](crate::search) module.
+    ///
+    /// # Returns
+    /// A result containing the hits, or an error if a content could not be read.
+    pub fn search(&self, query: &str) -> Result<Vec<SearchHit>> {
+        match &self.search {
+            Some(index) => Ok(index.search(query)),
+            None => Ok(self.build_search_index()?.search(query)),
+        }
+    }
+}
+
+/// Splits a text into lowercase words of letters and digits, with their start and end character
+/// offsets.
+fn tokenize(text: &str) -> Vec<(String, usize, usize)> {
+    let mut words = vec![];
+    let mut current: Option<(String, usize)> = None;
+
+    for (i, c) in text.chars().enumerate() {
+        match (&mut current, c.is_alphanumeric()) {
+            (Some((word, _)), true) => word.extend(c.to_lowercase()),
+            (None, true) => current = Some((c.to_lowercase().collect(), i)),
+            (Some(_), false) => {
+                if let Some((word, start)) = current.take() {
+                    words.push((word, start, i));
+                }
+            }
+            (None, false) => {}
+        }
+    }
+    if let Some((word, start)) = current {
+        words.push((word, start, text.chars().count()));
+    }
+
+    words
+}
+
+/// Splits a query into clauses.
+fn parse_query(query: &str) -> Vec<Clause> {
+    let mut clauses = vec![];
+
+    // Quotes alternate between phrases and plain clauses.
+    for (i, part) in query.split('"').enumerate() {
+        let chunks = if i % 2 == 1 {
+            vec![part]
+        } else {
+            part.split_whitespace().collect()
+        };
+
+        for chunk in chunks {
+            let chunk = chunk.trim();
+            let prefix = chunk.ends_with('*');
+            let words: Vec<String> = tokenize(chunk).into_iter().map(|(v, ..)| v).collect();
+            if !words.is_empty() {
+                clauses.push(Clause { words, prefix });
+            }
+        }
+    }
+
+    clauses
+}
+
+/// Builds the excerpt of a text around a match, cut at word boundaries.
+fn snippet(text: &str, (start, end): (usize, usize)) -> String {
+    let chars: Vec<char> = text.chars().collect();
+
+    let mut from = start.saturating_sub(SNIPPET_CONTEXT);
+    while from > 0 && !chars[from - 1].is_whitespace() {
+        from += 1;
+        if from >= start {
+            from = start;
+            break;
+        }
+    }
+    let mut to = (end + SNIPPET_CONTEXT).min(chars.len());
+    while to < chars.len() && !chars[to].is_whitespace() {
+        to -= 1;
+        if to <= end {
+            to = end;
+            break;
+        }
+    }
+
+    let excerpt: String = chars[from..to].iter().collect();
+    let mut snippet = excerpt.split_whitespace().collect::<Vec<_>>().join(" ");
+    if from > 0 {
+        snippet.insert_str(0, "… ");
+    }
+    if to < chars.len() {
+        snippet.push_str(" …");
+    }
+    snippet
+}
+
+fn put_u32(bytes: &mut Vec<u8>, value: u32) {
+    bytes.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_str(bytes: &mut Vec<u8>, value: &str) {
+    put_u32(bytes, value.len() as u32);
+    bytes.extend_from_slice(value.as_bytes());
+}
+
+/// Reads the values of an encoded index in order.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, length: usize) -> Result<&'a [u8]> {
+        let end = self
+            .position
+            .checked_add(length)
+            .filter(|v| *v <= self.bytes.len())
+            .ok_or_else(|| FobzError::InvalidSearchIndex("truncated index".into()))?;
+        let bytes = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let length = self.u32()? as usize;
+        String::from_utf8(self.take(length)?.to_vec())
+            .map_err(|_| FobzError::InvalidSearchIndex("invalid UTF-8".into()))
+    }
+}
@@ -0,0 +1,246 @@
+use std::{fmt::Write as _, path::Path};
+
+use crate::{
+    decode_binary, decode_text, error::Result, escape, refs, toc::ContentInfo, write_file, FobZ,
+    DEFAULT_COVER, NO_COVER,
+};
+
+// The generated page listing the sections, at the root of the site.
+const INDEX_PAGE: &str = "index.html";
+
+impl FobZ {
+    /// Exports the document as a static HTML website.
+    ///
+    /// Contents, resources, and styles are written under `dir` with their path in the archive,
+    /// so that relative links between them keep working. Every content becomes a page linking
+    /// every stylesheet, with links to the previous and next pages in table of contents order and
+    /// to `index.html`, a generated page showing the cover, the metadata, and the table of
+    /// contents. Existing files at the same paths are overwritten; other files are left alone.
+    ///
+    /// # Parameters
+    /// - `dir`: The directory to write the site to, created if missing.
+    ///
+    /// # Returns
+    /// A result indicating success or an error if a payload could not be read or a file could not
+    /// be written.
+    pub fn export_site(&self, dir: &str) -> Result<()> {
+        let dir = Path::new(dir);
+
+        let mut styles: Vec<&str> = self.styles.keys().map(String::as_str).collect();
+        styles.sort_unstable();
+        for path in &styles {
+            if let Some(style) = self.load(&self.styles, path, decode_text)? {
+                write_file(dir, path, style.as_bytes())?;
+            }
+        }
+
+        for path in self.resources.keys() {
+            if let Some(resource) = self.load(&self.resources, path, decode_binary)? {
+                write_file(dir, path, resource)?;
+            }
+        }
+
+        // Pages follow the table of contents, each file once; unlisted contents have no siblings.
+        let mut pages: Vec<&str> = vec![];
+        for info in self.toc.iter() {
+            if self.contents.contains_key(&info.path) && !pages.contains(&info.path.as_str()) {
+                pages.push(&info.path);
+            }
+        }
+
+        for path in self.contents.keys().filter(|v| !v.starts_with("default/")) {
+            let Some(content) = self.load(&self.contents, path, decode_text)? else {
+                continue;
+            };
+            let position = pages.iter().position(|v| v == path);
+            let previous = position.and_then(|i| i.checked_sub(1)).map(|i| pages[i]);
+            let next = position.and_then(|i| pages.get(i + 1)).copied();
+
+            let page = self.page(path, content, &styles, previous, next);
+            write_file(dir, path, page.as_bytes())?;
+        }
+
+        write_file(dir, INDEX_PAGE, self.index_page(&styles, &pages).as_bytes())?;
+        if self.manifest.get_cover() == DEFAULT_COVER {
+            write_file(dir, DEFAULT_COVER, NO_COVER)?;
+        }
+
+        Ok(())
+    }
+
+    /// Builds the page of a content, adding the stylesheets and the navigation links.
+    ///
+    /// Complete documents keep their own head and body, with the additions inserted into them;
+    /// fragments are wrapped in a new document.
+    fn page(
+        &self,
+        path: &str,
+        content: &str,
+        styles: &[&str],
+        previous: Option<&str>,
+        next: Option<&str>,
+    ) -> String {
+        let title = self.toc.get(path).map_or(path, |v| v.title.as_str());
+
+        let mut head = String::new();
+        for style in styles {
+            let _ = writeln!(
+                head,
+                "<link rel=\"stylesheet\" href=\"{}\">",
+                escape(&refs::percent_encode(&refs::relative(path, style)))
+            );
+        }
+
+        let link = |class: &str, target: &str, label: &str| {
+            format!(
+                "<a class=\"{}\" href=\"{}\">{}</a>",
+                class,
+                escape(&refs::percent_encode(&refs::relative(path, target))),
+                escape(label)
+            )
+        };
+        let mut nav = String::from("<nav class=\"fobz-nav\">\n");
+        if let Some(previous) = previous {
+            let label = self.toc.get(previous).map_or(previous, |v| &v.title);
+            let _ = writeln!(nav, "{}", link("fobz-previous", previous, label));
+        }
+        let _ = writeln!(nav, "{}", link("fobz-index", INDEX_PAGE, "Contents"));
+        if let Some(next) = next {
+            let label = self.toc.get(next).map_or(next, |v| &v.title);
+            let _ = writeln!(nav, "{}", link("fobz-next", next, label));
+        }
+        nav.push_str("</nav>\n");
+
+        let lower = content.to_ascii_lowercase();
+        let head_end = lower.find("</head");
+        let body_start = lower
+            .find("<body")
+            .and_then(|v| refs::tag_end(&content[v..]).map(|end| v + end + 1));
+        let body_end = lower.rfind("</body");
+
+        match (head_end, body_start, body_end) {
+            (Some(head_end), Some(body_start), Some(body_end))
+                if head_end < body_start && body_start <= body_end =>
+            {
+                format!(
+                    "{}{}{}\n{}{}{}{}",
+                    &content[..head_end],
+                    head,
+                    &content[head_end..body_start],
+                    nav,
+                    &content[body_start..body_end],
+                    nav,
+                    &content[body_end..]
+                )
+            }
+            _ => format!(
+                "<!DOCTYPE html>\n<html lang=\"{}\">\n<head>\n<meta charset=\"utf-8\">\n\
+                 <title>{}</title>\n{}</head>\n<body>\n{}<main>\n{}\n</main>\n{}</body>\n</html>\n",
+                self.language(),
+                escape(title),
+                head,
+                nav,
+                content,
+                nav
+            ),
+        }
+    }
+
+    /// Builds the index page, with the cover, the metadata, and the table of contents.
+    fn index_page(&self, styles: &[&str], pages: &[&str]) -> String {
+        let manifest = &self.manifest;
+        let title = escape(manifest.get_title());
+
+        let mut page = String::new();
+        let _ = write!(
+            page,
+            "<!DOCTYPE html>\n<html lang=\"{}\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>{}</title>\n",
+            self.language(),
+            title
+        );
+        for style in styles {
+            let _ = writeln!(
+                page,
+                "<link rel=\"stylesheet\" href=\"{}\">",
+                escape(&refs::percent_encode(style))
+            );
+        }
+        let _ = write!(page, "</head>\n<body>\n<header class=\"fobz-header\">\n");
+
+        let cover = manifest.get_cover();
+        if cover == DEFAULT_COVER || self.resources.contains_key(cover) {
+            let _ = writeln!(
+                page,
+                "<img class=\"fobz-cover\" src=\"{}\" alt=\"{}\">",
+                escape(&refs::percent_encode(cover)),
+                title
+            );
+        }
+        let _ = writeln!(page, "<h1>{}</h1>", title);
+        if !manifest.get_author().is_empty() {
+            let _ = writeln!(
+                page,
+                "<p class=\"fobz-author\">{}</p>",
+                escape(manifest.get_author())
+            );
+        }
+        if !manifest.get_description().is_empty() {
+            let _ = writeln!(
+                page,
+                "<p class=\"fobz-description\">{}</p>",
+                escape(manifest.get_description())
+            );
+        }
+
+        // Start from the declared index when it is one of the pages, otherwise from the first one.
+        let start = pages
+            .iter()
+            .find(|v| *v == manifest.get_index())
+            .or(pages.first());
+        if let Some(start) = start {
+            let _ = writeln!(
+                page,
+                "<p><a class=\"fobz-start\" href=\"{}\">Start reading</a></p>",
+                escape(&refs::percent_encode(start))
+            );
+        }
+        page.push_str("</header>\n<nav class=\"fobz-toc\">\n");
+
+        if !self.toc.is_empty() {
+            self.toc_list(&mut page, self.toc.sections());
+        }
+        page.push_str("</nav>\n</body>\n</html>\n");
+
+        page
+    }
+
+    /// Writes the nested list of sections of the index page, one level per call.
+    fn toc_list(&self, page: &mut String, sections: &[ContentInfo]) {
+        page.push_str("<ol>\n");
+        for info in sections {
+            let title = escape(&info.title);
+            if self.contents.contains_key(&info.path) {
+                let mut href = refs::percent_encode(&info.path);
+                if let Some(anchor) = &info.anchor {
+                    href.push('#');
+                    href.push_str(anchor);
+                }
+                let _ = write!(page, "<li><a href=\"{}\">{}</a>", escape(&href), title);
+            } else {
+                let _ = write!(page, "<li><span>{}</span>", title);
+            }
+            if !info.children.is_empty() {
+                page.push('\n');
+                self.toc_list(page, &info.children);
+            }
+            page.push_str("</li>\n");
+        }
+        page.push_str("</ol>\n");
+    }
+
+    /// Returns the escaped language of the document, `und` (undetermined) if it has none.
+    fn language(&self) -> String {
+        escape(self.manifest.get_language().map_or("und", String::as_str))
+    }
+}
@@ -0,0 +1,753 @@
+use std::{fs, path::Path};
+
+use crate::{
+    decode_text,
+    error::Result,
+    html::{self, Token},
+    options::TextFormat,
+    refs, write_file, FobZ,
+};
+
+// Elements starting a new block of text.
+const BLOCK_ELEMENTS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "body",
+    "caption",
+    "dd",
+    "details",
+    "div",
+    "dl",
+    "dt",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "header",
+    "main",
+    "nav",
+    "p",
+    "section",
+    "summary",
+];
+
+// Elements whose text is not part of the document.
+const SKIPPED_ELEMENTS: &[&str] = &["head", "noscript", "script", "style", "template", "title"];
+
+impl FobZ {
+    /// Converts the contents of the document to a single text, without HTML.
+    ///
+    /// Contents are taken in table of contents order, each file once, and separated by a blank
+    /// line; contents missing from the table of contents are left out. Links between contents
+    /// are dropped, keeping their text, and other local links point to the path of their target
+    /// in the archive.
+    ///
+    /// # Parameters
+    /// - `format`: Whether to produce plain text or Markdown.
+    ///
+    /// # Returns
+    /// A result containing the text, or an error if a content could not be read.
+    pub fn to_text(&self, format: TextFormat) -> Result<String> {
+        let mut text = String::new();
+
+        for (path, content) in self.text_sources()? {
+            let section = Converter::new(self, path, format, false).convert(content);
+            if section.is_empty() {
+                continue;
+            }
+            if !text.is_empty() {
+                text.push('\n');
+            }
+            text.push_str(&section);
+        }
+
+        Ok(text)
+    }
+
+    /// Converts each content of the document to a separate text, without HTML.
+    ///
+    /// Contents are taken in table of contents order, each file once. Each text is given the
+    /// path of its content with the extension of the format (`.txt` or `.md`), and links between
+    /// contents are rewritten to point to these paths.
+    ///
+    /// # Parameters
+    /// - `format`: Whether to produce plain text or Markdown.
+    ///
+    /// # Returns
+    /// A result containing the path and text of each section, or an error if a content could not
+    /// be read.
+    pub fn to_text_sections(&self, format: TextFormat) -> Result<Vec<(String, String)>> {
+        Ok(self
+            .convert_sections(format, true)?
+            .into_iter()
+            .map(|(path, text)| (text_path(path, format), text))
+            .collect())
+    }
+
+    /// Exports the contents of the document as a single text file.
+    ///
+    /// # Parameters
+    /// - `path`: The file to write, as returned by [`FobZ::to_text`].
+    /// - `format`: Whether to produce plain text or Markdown.
+    ///
+    /// # Returns
+    /// A result indicating success or an error if a content could not be read or the file could
+    /// not be written.
+    pub fn export_text(&self, path: &str, format: TextFormat) -> Result<()> {
+        fs::write(path, self.to_text(format)?)?;
+        Ok(())
+    }
+
+    /// Exports the contents of the document as one text file per section.
+    ///
+    /// # Parameters
+    /// - `dir`: The directory to write the files to, at the paths returned by
+    ///   [`FobZ::to_text_sections`]. It is created if missing.
+    /// - `format`: Whether to produce plain text or Markdown.
+    ///
+    /// # Returns
+    /// A result indicating success or an error if a content could not be read or a file could
+    /// not be written.
+    pub fn export_text_sections(&self, dir: &str, format: TextFormat) -> Result<()> {
+        for (path, text) in self.to_text_sections(format)? {
+            write_file(Path::new(dir), &path, text.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Converts each content to a separate text, keyed by the path of the content.
+    ///
+    /// # Parameters
+    /// - `format`: Whether to produce plain text or Markdown.
+    /// - `split`: Whether links between contents point to their text files, as in
+    ///   [`FobZ::to_text_sections`], rather than being dropped, as in [`FobZ::to_text`].
+    pub(crate) fn convert_sections(
+        &self,
+        format: TextFormat,
+        split: bool,
+    ) -> Result<Vec<(&str, String)>> {
+        Ok(self
+            .text_sources()?
+            .into_iter()
+            .map(|(path, content)| {
+                (
+                    path,
+                    Converter::new(self, path, format, split).convert(content),
+                )
+            })
+            .collect())
+    }
+
+    /// Lists the path and HTML of the contents in table of contents order, each file once.
+    fn text_sources(&self) -> Result<Vec<(&str, &str)>> {
+        let mut sources: Vec<(&str, &str)> = vec![];
+
+        for info in self.toc.iter() {
+            if sources.iter().any(|(v, _)| *v == info.path) {
+                continue;
+            }
+            if let Some(content) = self.load(&self.contents, &info.path, decode_text)? {
+                sources.push((&info.path, content));
+            }
+        }
+
+        Ok(sources)
+    }
+}
+
+/// A block of text containing other blocks, whose lines start with a prefix.
+///
+/// # Fields
+/// - `element`: The element opening the block.
+/// - `prefix`: The text starting each line of the block (e.g., `> ` for quotes).
+/// - `marker`: The text replacing the prefix on the first line, for list items.
+/// - `written`: Whether a line was written inside the block.
+struct Block {
+    element: String,
+    prefix: String,
+    marker: Option<String>,
+    written: bool,
+}
+
+/// An inline element still open.
+///
+/// # Fields
+/// - `element`: The element, `a` for links.
+/// - `start`: The position in the current line just after the opening markup.
+/// - `markup`: The Markdown markup opening the element, empty if it has none.
+/// - `target`: The target of a link, `None` if it is dropped.
+struct Inline {
+    element: String,
+    start: usize,
+    markup: &'static str,
+    target: Option<String>,
+}
+
+/// Converts the HTML of a content to plain text or Markdown.
+struct Converter<'a> {
+    fobz: &'a FobZ,
+    path: &'a str,
+    format: TextFormat,
+    // Whether each content becomes its own file, rather than part of a single one.
+    split: bool,
+    output: String,
+    // The text of the current block, lines separated by `\n`.
+    line: String,
+    // Whether whitespace was collapsed before the next text.
+    space: bool,
+    // Whether the next block follows the previous one without a blank line (list items, rows).
+    tight: bool,
+    blocks: Vec<Block>,
+    inlines: Vec<Inline>,
+    // The number of items already in each open list, and the number of the first item of
+    // ordered lists.
+    lists: Vec<(usize, Option<usize>)>,
+    // The number of rows already in each open table, and of cells in its current row.
+    tables: Vec<(usize, usize)>,
+    heading: Option<usize>,
+    preformatted: bool,
+    skip: usize,
+}
+
+impl<'a> Converter<'a> {
+    fn new(fobz: &'a FobZ, path: &'a str, format: TextFormat, split: bool) -> Self {
+        Self {
+            fobz,
+            path,
+            format,
+            split,
+            output: String::new(),
+            line: String::new(),
+            space: false,
+            tight: false,
+            blocks: vec![],
+            inlines: vec![],
+            lists: vec![],
+            tables: vec![],
+            heading: None,
+            preformatted: false,
+            skip: 0,
+        }
+    }
+
+    fn markdown(&self) -> bool {
+        self.format == TextFormat::Markdown
+    }
+
+    /// Converts an HTML document, ending the text with a newline unless it is empty.
+    fn convert(mut self, content: &str) -> String {
+        for token in html::tokenize(content) {
+            match token {
+                Token::Text(text) => self.text(text),
+                Token::Start { ref name, .. } => self.start(name, &token),
+                Token::End(name) => self.end(&name),
+                Token::Comment(_) | Token::Declaration(_) => {}
+            }
+        }
+        self.flush();
+
+        if !self.output.is_empty() {
+            self.output.push('\n');
+        }
+        self.output
+    }
+
+    fn start(&mut self, name: &str, token: &Token) {
+        // The head may be left open in HTML; the body ends it.
+        if name == "body" {
+            self.skip = 0;
+        }
+        if SKIPPED_ELEMENTS.contains(&name) {
+            if !matches!(
+                token,
+                Token::Start {
+                    self_closing: true,
+                    ..
+                }
+            ) {
+                self.skip += 1;
+            }
+            return;
+        }
+        if self.skip > 0 {
+            return;
+        }
+
+        match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.flush();
+                self.heading = name[1..].parse().ok();
+            }
+            "ul" | "ol" => {
+                self.flush();
+                let start = token.attribute("start").and_then(|v| v.trim().parse().ok());
+                self.lists
+                    .push((0, (name == "ol").then_some(start.unwrap_or(1))));
+                self.open_block(name, String::new(), None);
+            }
+            "li" => {
+                self.flush();
+                if self.blocks.last().is_some_and(|v| v.element == "li") {
+                    self.close_block("li");
+                }
+                let nested = self.lists.len() > 1;
+                let Some((count, first)) = self.lists.last_mut() else {
+                    return;
+                };
+                let marker = match first {
+                    Some(first) => format!("{}. ", *first + *count),
+                    None => "- ".into(),
+                };
+                self.tight = *count > 0 || nested;
+                *count += 1;
+                self.open_block("li", " ".repeat(marker.len()), Some(marker));
+            }
+            "blockquote" => {
+                self.flush();
+                let prefix = if self.markdown() { "> " } else { "    " };
+                self.open_block(name, prefix.into(), None);
+            }
+            "pre" => {
+                self.flush();
+                self.preformatted = true;
+            }
+            "hr" => {
+                self.flush();
+                self.write("* * *");
+            }
+            "table" => {
+                self.flush();
+                self.tables.push((0, 0));
+            }
+            "tr" => {
+                self.flush();
+                if let Some((_, cells)) = self.tables.last_mut() {
+                    *cells = 0;
+                }
+            }
+            "td" | "th" => {
+                if let Some((_, cells)) = self.tables.last_mut() {
+                    if *cells > 0 {
+                        self.line.push_str(" | ");
+                    }
+                    *cells += 1;
+                    self.space = false;
+                }
+            }
+            "br" => {
+                if !self.line.is_empty() {
+                    let markup = if self.markdown() && !self.preformatted {
+                        "\\\n"
+                    } else {
+                        "\n"
+                    };
+                    self.line.push_str(markup);
+                    self.space = false;
+                }
+            }
+            "img" => self.image(token),
+            "a" => {
+                let target = token
+                    .attribute("href")
+                    .filter(|_| !self.preformatted)
+                    .and_then(|v| self.link_target(v));
+                let markup = if self.markdown() && target.is_some() {
+                    "["
+                } else {
+                    ""
+                };
+                self.open_inline(name, markup, target);
+            }
+            _ => {
+                if let Some(markup) = self.markup(name) {
+                    self.open_inline(name, markup, None);
+                } else if BLOCK_ELEMENTS.contains(&name) && !self.in_cell() {
+                    self.flush();
+                }
+            }
+        }
+    }
+
+    fn end(&mut self, name: &str) {
+        if SKIPPED_ELEMENTS.contains(&name) {
+            self.skip = self.skip.saturating_sub(1);
+            return;
+        }
+        if self.skip > 0 {
+            return;
+        }
+
+        match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "pre" => self.flush(),
+            "ul" | "ol" | "li" | "blockquote" => {
+                self.flush();
+                if self.close_block(name) && matches!(name, "ul" | "ol") {
+                    self.lists.pop();
+                }
+            }
+            "tr" => {
+                let Some((rows, cells)) = self.tables.last().copied() else {
+                    return;
+                };
+                let row = std::mem::take(&mut self.line);
+                let row = row.trim();
+                if row.is_empty() {
+                    return;
+                }
+                if self.markdown() {
+                    self.line = format!("| {} |", row);
+                } else {
+                    self.line = row.into();
+                }
+                self.tight = rows > 0;
+                self.flush();
+
+                if self.markdown() && rows == 0 {
+                    self.tight = true;
+                    self.write(&format!("|{}", " --- |".repeat(cells.max(1))));
+                }
+                if let Some((rows, _)) = self.tables.last_mut() {
+                    *rows += 1;
+                }
+            }
+            "table" => {
+                self.flush();
+                self.tables.pop();
+            }
+            _ if BLOCK_ELEMENTS.contains(&name) => {
+                if !self.in_cell() {
+                    self.flush();
+                }
+            }
+            _ => self.close_inline(name),
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        if self.skip > 0 {
+            return;
+        }
+        let text = html::unescape(text).replace('\u{a0}', " ");
+
+        if self.preformatted {
+            self.line.push_str(&text);
+            return;
+        }
+
+        if text.starts_with(|c: char| c.is_ascii_whitespace()) {
+            self.space = true;
+        }
+        let code = self.inlines.iter().any(|v| v.markup == "`");
+        for (i, word) in text.split_ascii_whitespace().enumerate() {
+            if i > 0 {
+                self.space = true;
+            }
+            if self.markdown() && !code {
+                self.push(&escape_markdown(word));
+            } else {
+                self.push(word);
+            }
+        }
+        if text.ends_with(|c: char| c.is_ascii_whitespace()) {
+            self.space = true;
+        }
+    }
+
+    /// Adds inline text to the current line, preceded by any collapsed whitespace.
+    ///
+    /// Whitespace just after opening markup goes before it, as Markdown requires.
+    fn push(&mut self, text: &str) {
+        if self.space && !self.line.is_empty() && !self.line.ends_with('\n') {
+            match self.inlines.last_mut() {
+                Some(inline) if !inline.markup.is_empty() && inline.start == self.line.len() => {
+                    let at = inline.start - inline.markup.len();
+                    if at > 0 && !self.line[..at].ends_with([' ', '\n']) {
+                        self.line.insert(at, ' ');
+                        inline.start += 1;
+                    }
+                }
+                _ => self.line.push(' '),
+            }
+        }
+        self.space = false;
+        self.line.push_str(text);
+    }
+
+    /// Opens an inline element, adding its Markdown markup.
+    fn open_inline(&mut self, element: &str, markup: &'static str, target: Option<String>) {
+        self.push(markup);
+        self.inlines.push(Inline {
+            element: element.into(),
+            start: self.line.len(),
+            markup,
+            target,
+        });
+    }
+
+    /// Retrieves the Markdown markup of an inline element, if it has any.
+    fn markup(&self, element: &str) -> Option<&'static str> {
+        if !self.markdown() || self.preformatted {
+            return None;
+        }
+
+        match element {
+            "em" | "i" | "cite" | "dfn" | "var" => Some("*"),
+            "strong" | "b" => Some("**"),
+            "code" | "kbd" | "samp" => Some("`"),
+            _ => None,
+        }
+    }
+
+    /// Closes an inline element and the ones opened inside it.
+    fn close_inline(&mut self, element: &str) {
+        let Some(position) = self.inlines.iter().rposition(|v| v.element == element) else {
+            return;
+        };
+
+        for inline in self.inlines.split_off(position).into_iter().rev() {
+            let text = self.line[inline.start..].trim();
+            if !inline.markup.is_empty() && text.is_empty() {
+                self.line.truncate(inline.start - inline.markup.len());
+                continue;
+            }
+
+            match inline.target {
+                Some(target) if self.markdown() => {
+                    self.line.push_str(&format!("]({})", destination(&target)));
+                }
+                // Plain text shows the target of external links after their text.
+                Some(target) => {
+                    if text.is_empty() {
+                        self.push(&target);
+                    } else if text != target && Some(text) != target.strip_prefix("mailto:") {
+                        self.line.push_str(&format!(" ({})", target));
+                    }
+                }
+                None => self.line.push_str(inline.markup),
+            }
+        }
+    }
+
+    /// Adds an image, described by the name of its resource or else by its alternative text.
+    fn image(&mut self, token: &Token) {
+        let src = token
+            .attribute("src")
+            .map(html::unescape)
+            .unwrap_or_default();
+        let resource = refs::resolve(self.path, &refs::split_reference(&src).0)
+            .and_then(|v| self.fobz.get_resource_info(&v));
+
+        let alt = match resource {
+            Some(info) if !info.name.is_empty() => info.name.clone(),
+            _ => token
+                .attribute("alt")
+                .map(html::unescape)
+                .unwrap_or_default(),
+        };
+        let alt = alt.split_whitespace().collect::<Vec<_>>().join(" ");
+
+        if self.markdown() {
+            let target = token
+                .attribute("src")
+                .and_then(|v| self.link_target(v))
+                .unwrap_or(src);
+            self.push(&format!(
+                "![{}]({})",
+                escape_markdown(&alt),
+                destination(&target)
+            ));
+        } else if !alt.is_empty() {
+            self.push(&format!("[{}]", alt));
+        }
+    }
+
+    /// Finds where a link should point in the text, `None` to drop it and keep only its text.
+    ///
+    /// External links are kept as they are. Plain text drops local links, and Markdown keeps
+    /// them unless they point into the contents of a single text.
+    fn link_target(&self, href: &str) -> Option<String> {
+        let href = html::unescape(href);
+        if refs::is_external(&href) {
+            return Some(href);
+        }
+        if !self.markdown() || href.is_empty() {
+            return None;
+        }
+        if href.starts_with('#') {
+            return self.split.then_some(href);
+        }
+
+        let (path, fragment) = refs::split_reference(&href);
+        let target = refs::resolve(self.path, &path)?;
+        let mut link = match (self.fobz.contents.contains_key(&target), self.split) {
+            (true, true) => refs::relative(
+                &text_path(self.path, self.format),
+                &text_path(&target, self.format),
+            ),
+            (true, false) => return None,
+            (false, true) => return Some(href),
+            (false, false) => target,
+        };
+        if let Some(fragment) = fragment {
+            link.push('#');
+            link.push_str(fragment);
+        }
+
+        Some(link)
+    }
+
+    fn in_cell(&self) -> bool {
+        self.tables.last().is_some_and(|(_, cells)| *cells > 0)
+    }
+
+    fn open_block(&mut self, element: &str, prefix: String, marker: Option<String>) {
+        self.blocks.push(Block {
+            element: element.into(),
+            prefix,
+            marker,
+            written: false,
+        });
+    }
+
+    /// Closes a block and the ones opened inside it, returning whether it was open.
+    fn close_block(&mut self, element: &str) -> bool {
+        match self.blocks.iter().rposition(|v| v.element == element) {
+            Some(position) => {
+                self.blocks.truncate(position);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Ends the current block, writing its text to the output.
+    fn flush(&mut self) {
+        let line = std::mem::take(&mut self.line);
+        let heading = self.heading.take();
+        let preformatted = std::mem::replace(&mut self.preformatted, false);
+        self.inlines.clear();
+        self.space = false;
+
+        if preformatted {
+            let code = line.strip_prefix('\n').unwrap_or(&line).trim_end();
+            if code.is_empty() {
+                return;
+            }
+            if self.markdown() {
+                let fence = "`".repeat(longest_run(code, '`').max(2) + 1);
+                self.write(&format!("{}\n{}\n{}", fence, code, fence));
+            } else {
+                self.write(code);
+            }
+            return;
+        }
+
+        let text = line.trim();
+        if text.is_empty() {
+            return;
+        }
+        match heading {
+            Some(level) if self.markdown() => {
+                let text = text.replace("\\\n", " ").replace('\n', " ");
+                self.write(&format!("{} {}", "#".repeat(level), text));
+            }
+            Some(_) => self.write(&text.replace('\n', " ")),
+            None if self.markdown() => self.write(&escape_line_start(text)),
+            None => self.write(text),
+        }
+    }
+
+    /// Writes the lines of a block to the output, each starting with the prefixes of the open
+    /// blocks.
+    fn write(&mut self, text: &str) {
+        if !self.output.is_empty() {
+            self.output.push('\n');
+            // The blank line only belongs to the blocks that already contain text.
+            if !self.tight {
+                let prefix: String = self
+                    .blocks
+                    .iter()
+                    .filter(|v| v.written)
+                    .map(|v| v.prefix.as_str())
+                    .collect();
+                self.output.push_str(prefix.trim_end());
+                self.output.push('\n');
+            }
+        }
+        self.tight = false;
+
+        for (i, line) in text.split('\n').enumerate() {
+            if i > 0 {
+                self.output.push('\n');
+            }
+            let mut prefix = String::new();
+            for block in &mut self.blocks {
+                block.written = true;
+                match block.marker.take() {
+                    Some(marker) => prefix.push_str(&marker),
+                    None => prefix.push_str(&block.prefix),
+                }
+            }
+            if line.is_empty() {
+                self.output.push_str(prefix.trim_end());
+            } else {
+                self.output.push_str(&prefix);
+                self.output.push_str(line);
+            }
+        }
+    }
+}
+
+/// Builds the path of the text converted from a content, with the extension of the format.
+fn text_path(path: &str, format: TextFormat) -> String {
+    let extension = match format {
+        TextFormat::Plain => "txt",
+        TextFormat::Markdown => "md",
+    };
+    let name_start = path.rfind('/').map_or(0, |v| v + 1);
+    let stem = match path[name_start..].rfind('.') {
+        Some(dot) if dot > 0 => &path[..name_start + dot],
+        _ => path,
+    };
+
+    format!("{}.{}", stem, extension)
+}
+
+/// Escapes the characters of a text that Markdown would read as inline markup.
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '*' | '_' | '`' | '[' | ']' | '<') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Escapes the start of a paragraph that Markdown would read as a heading, a list, or a break.
+fn escape_line_start(text: &str) -> String {
+    let digits = text.len() - text.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    if text.starts_with(['#', '>', '-', '+']) {
+        format!("\\{}", text)
+    } else if digits > 0 && text[digits..].starts_with(['.', ')']) {
+        format!("{}\\{}", &text[..digits], &text[digits..])
+    } else {
+        text.into()
+    }
+}
+
+/// Writes a link destination, enclosed in `<>` when it contains spaces or parentheses.
+fn destination(target: &str) -> String {
+    if target.contains([' ', '(', ')', '<', '>']) {
+        format!("<{}>", target.replace('<', "%3C").replace('>', "%3E"))
+    } else {
+        target.into()
+    }
+}
+
+/// Counts the longest run of a character in a text.
+fn longest_run(text: &str, c: char) -> usize {
+    text.split(|v| v != c).map(str::len).max().unwrap_or(0)
+}
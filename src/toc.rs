@@ -1,25 +1,124 @@
+use std::{collections::HashSet, slice};
+
 use serde::{Deserialize, Serialize};
 
+use crate::error::{FobzError, Result};
+
 /// Represents a single section within the `.fobz` document.
 ///
+/// Sections form a tree: a part can contain chapters, which can contain subsections. A section
+/// may point at a fragment inside its file instead of the whole file.
+///
 /// # Fields
 /// - `path`: Path to the section file within the `.fobz` archive.
 /// - `title`: Title of the section, for display purposes.
-#[derive(Debug, Serialize, Deserialize)]
+/// - `anchor`: Optional `id` of the element the section starts at, inside its file.
+/// - `children`: The nested sections, in order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ContentInfo {
     pub path: String,
     pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anchor: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<ContentInfo>,
+}
+
+impl ContentInfo {
+    /// Creates a new `ContentInfo` pointing at a whole file, without children.
+    ///
+    /// # Parameters
+    /// - `path`: Path to the section file within the `.fobz` archive.
+    /// - `title`: Title of the section.
+    pub fn new(path: String, title: String) -> Self {
+        ContentInfo {
+            path,
+            title,
+            anchor: None,
+            children: vec![],
+        }
+    }
+
+    /// Creates a new `ContentInfo` pointing at a fragment of a file, without children.
+    ///
+    /// # Parameters
+    /// - `path`: Path to the section file within the `.fobz` archive.
+    /// - `anchor`: The `id` of the element the section starts at.
+    /// - `title`: Title of the section.
+    pub fn with_anchor(path: String, anchor: String, title: String) -> Self {
+        ContentInfo {
+            anchor: Some(anchor),
+            ..ContentInfo::new(path, title)
+        }
+    }
+
+    /// Builds the reference of the section, `path#anchor` or just `path` without an anchor.
+    ///
+    /// # Returns
+    /// The reference identifying the section in the table of contents.
+    pub fn href(&self) -> String {
+        match &self.anchor {
+            Some(anchor) => format!("{}#{}", self.path, anchor),
+            None => self.path.clone(),
+        }
+    }
+
+    /// Checks whether the section is identified by a reference.
+    fn is(&self, path: &str, anchor: Option<&str>) -> bool {
+        self.path == path && self.anchor.as_deref() == anchor
+    }
+}
+
+/// Splits a reference into its file path and optional fragment.
+///
+/// # Parameters
+/// - `href`: A reference such as `contents/chapter1.html#part2`.
+///
+/// # Returns
+/// A tuple containing the path and the fragment, if any.
+pub fn split_href(href: &str) -> (&str, Option<&str>) {
+    match href.split_once('#') {
+        Some((path, anchor)) => (path, Some(anchor)),
+        None => (href, None),
+    }
 }
 
 /// Represents the table of contents for a `.fobz` document, organizing multiple sections.
 ///
 /// # Fields
-/// - `sections`: A vector of `ContentInfo` items, each representing a distinct part of the document.
-#[derive(Debug, Serialize, Deserialize)]
+/// - `sections`: A vector of the top-level `ContentInfo` items, each possibly containing nested sections.
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct TableOfContents {
     sections: Vec<ContentInfo>,
 }
 
+/// A depth-first iterator over the sections of a `TableOfContents`.
+///
+/// Yields each section together with its depth, top-level sections having depth `0`.
+pub struct DepthFirst<'a> {
+    stack: Vec<slice::Iter<'a, ContentInfo>>,
+}
+
+impl<'a> Iterator for DepthFirst<'a> {
+    type Item = (usize, &'a ContentInfo);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(level) = self.stack.last_mut() {
+            match level.next() {
+                Some(info) => {
+                    let depth = self.stack.len() - 1;
+                    self.stack.push(info.children.iter());
+                    return Some((depth, info));
+                }
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+        None
+    }
+}
+
 impl TableOfContents {
     /// Creates a new `TableOfContents` instance.
     ///
@@ -28,18 +127,61 @@ impl TableOfContents {
         TableOfContents { sections: vec![] }
     }
 
-    /// Retrieves a reference to the `ContentInfo` associated with the given path.
+    /// Retrieves a reference to the `ContentInfo` associated with the given reference.
+    ///
+    /// A reference with a fragment (`path#anchor`) only matches the section with that anchor. A
+    /// plain path matches the section pointing at the whole file, or else the first section
+    /// pointing inside it.
     ///
     /// # Parameters
-    /// - `path`: The path of the section to search for.
+    /// - `path`: The reference of the section to search for.
     ///
     /// # Returns
     /// An `Option` containing a reference to `ContentInfo` if found, or `None` if not found.
-    pub fn get(&self, path: &String) -> Option<&ContentInfo> {
-        self.sections.iter().find(|v| &v.path == path)
+    pub fn get(&self, path: &str) -> Option<&ContentInfo> {
+        let address = self.locate(path)?;
+        Some(self.node(&address))
+    }
+
+    /// Retrieves a mutable reference to the `ContentInfo` associated with the given reference.
+    ///
+    /// # Parameters
+    /// - `path`: The reference of the section to search for, as described in [`TableOfContents::get`].
+    ///
+    /// # Returns
+    /// An `Option` containing a mutable reference to `ContentInfo` if found, or `None` if not found.
+    pub fn get_mut(&mut self, path: &str) -> Option<&mut ContentInfo> {
+        let address = self.locate(path)?;
+        Some(self.node_mut(&address))
     }
 
-    /// Adds a new section to the table of contents.
+    /// Returns the top-level sections, in order.
+    ///
+    /// # Returns
+    /// A slice of the top-level `ContentInfo` items.
+    pub fn sections(&self) -> &[ContentInfo] {
+        &self.sections
+    }
+
+    /// Returns a depth-first iterator over every section, in reading order.
+    ///
+    /// # Returns
+    /// An iterator yielding a reference to each `ContentInfo`.
+    pub fn iter(&self) -> impl Iterator<Item = &ContentInfo> {
+        self.depth_first().map(|(_, info)| info)
+    }
+
+    /// Returns a depth-first iterator over every section together with its depth.
+    ///
+    /// # Returns
+    /// A `DepthFirst` iterator yielding `(depth, &ContentInfo)` pairs.
+    pub fn depth_first(&self) -> DepthFirst<'_> {
+        DepthFirst {
+            stack: vec![self.sections.iter()],
+        }
+    }
+
+    /// Adds a new top-level section at the end of the table of contents.
     ///
     /// # Parameters
     /// - `info`: The `ContentInfo` object representing the section to add.
@@ -47,11 +189,274 @@ impl TableOfContents {
         self.sections.append(&mut vec![info]);
     }
 
-    /// Removes a section from the table of contents by its path.
+    /// Adds a new section at the end of the children of another section.
     ///
     /// # Parameters
-    /// - `path`: The path of the section to remove.
-    pub fn remove(&mut self, path: &String) {
-        self.sections.retain(|v| &v.path != path);
+    /// - `parent`: The reference of the parent section.
+    /// - `info`: The `ContentInfo` object representing the section to add.
+    ///
+    /// # Returns
+    /// A result indicating success, `FobzError::UnknownPath` if the parent does not exist, or
+    /// `FobzError::DuplicatePath` if a section with the same reference already exists.
+    pub fn add_child(&mut self, parent: &str, info: ContentInfo) -> Result<()> {
+        let address = self.find(parent)?;
+        self.check_unique(&info)?;
+
+        self.node_mut(&address).children.push(info);
+        Ok(())
+    }
+
+    /// Removes every section pointing at the given file, or at the given fragment.
+    ///
+    /// The children of a removed section take its place.
+    ///
+    /// # Parameters
+    /// - `path`: The path of the file, or the reference of a single section (`path#anchor`).
+    pub fn remove(&mut self, path: &str) {
+        let (path, anchor) = split_href(path);
+        remove_from(&mut self.sections, &|v: &ContentInfo| {
+            v.path == path && (anchor.is_none() || v.anchor.as_deref() == anchor)
+        });
+    }
+
+    /// Points every section of a file at another file, keeping their anchors.
+    ///
+    /// # Parameters
+    /// - `path`: The current path of the file.
+    /// - `new_path`: The path the sections point at afterwards.
+    pub fn rename(&mut self, path: &str, new_path: &str) {
+        rename_in(&mut self.sections, path, new_path);
+    }
+
+    /// Returns the number of top-level sections in the table of contents.
+    pub fn len(&self) -> usize {
+        self.sections.len()
+    }
+
+    /// Checks whether the table of contents has no sections.
+    pub fn is_empty(&self) -> bool {
+        self.sections.is_empty()
+    }
+
+    /// Finds the position of a section among its siblings.
+    ///
+    /// # Parameters
+    /// - `path`: The reference of the section to search for.
+    ///
+    /// # Returns
+    /// An `Option` containing the index of the section in its parent (or in the top level), or
+    /// `None` if not found.
+    pub fn position(&self, path: &str) -> Option<usize> {
+        self.locate(path)?.last().copied()
+    }
+
+    /// Inserts a new top-level section at the given position, shifting the following sections down.
+    ///
+    /// # Parameters
+    /// - `index`: The position of the new section (at most the number of top-level sections).
+    /// - `info`: The `ContentInfo` object representing the section to insert.
+    ///
+    /// # Returns
+    /// A result indicating success, `FobzError::IndexOutOfBounds` if `index` is past the end, or
+    /// `FobzError::DuplicatePath` if a section with the same reference already exists.
+    pub fn insert_at(&mut self, index: usize, info: ContentInfo) -> Result<()> {
+        check_index(index, self.sections.len(), self.sections.len())?;
+        self.check_unique(&info)?;
+
+        self.sections.insert(index, info);
+        Ok(())
+    }
+
+    /// Moves an existing section, with its children, to the given position among its siblings.
+    ///
+    /// # Parameters
+    /// - `path`: The reference of the section to move.
+    /// - `index`: The position of the section once moved (less than the number of siblings).
+    ///
+    /// # Returns
+    /// A result indicating success, `FobzError::UnknownPath` if the section does not exist, or
+    /// `FobzError::IndexOutOfBounds` if `index` is past the last sibling.
+    pub fn move_to(&mut self, path: &str, index: usize) -> Result<()> {
+        let address = self.find(path)?;
+        let (from, parent) = address.split_last().expect("addresses are never empty");
+        let siblings = self.siblings_mut(parent);
+        check_index(index, siblings.len() - 1, siblings.len())?;
+
+        let info = siblings.remove(*from);
+        siblings.insert(index, info);
+        Ok(())
+    }
+
+    /// Swaps the positions of two sections, each moving with its children.
+    ///
+    /// # Parameters
+    /// - `a`: The reference of the first section.
+    /// - `b`: The reference of the second section.
+    ///
+    /// # Returns
+    /// A result indicating success, `FobzError::UnknownPath` if either section does not exist, or
+    /// `FobzError::InvalidPath` if one section is nested inside the other.
+    pub fn swap(&mut self, a: &str, b: &str) -> Result<()> {
+        let first = self.find(a)?;
+        let second = self.find(b)?;
+
+        if first == second {
+            return Ok(());
+        }
+        if first.starts_with(&second) || second.starts_with(&first) {
+            return Err(FobzError::InvalidPath(b.to_string()));
+        }
+
+        let placeholder = ContentInfo::new(String::new(), String::new());
+        let first_info = std::mem::replace(self.node_mut(&first), placeholder);
+        let second_info = std::mem::replace(self.node_mut(&second), first_info);
+        *self.node_mut(&first) = second_info;
+        Ok(())
+    }
+
+    /// Reorders a list of sibling sections to follow the given list of references.
+    ///
+    /// The list must contain every section of one level (the top level, or the children of a
+    /// single section) exactly once; otherwise the table is left unchanged.
+    ///
+    /// # Parameters
+    /// - `paths`: The references of all the sibling sections, in their new order.
+    ///
+    /// # Returns
+    /// A result indicating success, `FobzError::UnknownPath` if a reference is not a sibling of
+    /// the first one, `FobzError::DuplicatePath` if a reference is listed twice, or
+    /// `FobzError::MissingEntry` if a sibling is not listed.
+    pub fn reorder(&mut self, paths: &[String]) -> Result<()> {
+        let parent = match paths.first() {
+            Some(first) => {
+                let mut address = self.find(first)?;
+                address.pop();
+                address
+            }
+            None => vec![],
+        };
+
+        let mut order = Vec::with_capacity(paths.len());
+        let mut seen = HashSet::new();
+        for path in paths {
+            let address = self.find(path)?;
+            let (index, address_parent) = address.split_last().expect("addresses are never empty");
+            if address_parent != parent.as_slice() {
+                return Err(FobzError::UnknownPath(path.clone()));
+            }
+            if !seen.insert(*index) {
+                return Err(FobzError::DuplicatePath(path.clone()));
+            }
+            order.push(*index);
+        }
+
+        let siblings = self.siblings_mut(&parent);
+        if let Some(missing) = (0..siblings.len()).find(|v| !seen.contains(v)) {
+            return Err(FobzError::MissingEntry(siblings[missing].href()));
+        }
+
+        let mut taken: Vec<Option<ContentInfo>> = siblings.drain(..).map(Some).collect();
+        siblings.extend(order.into_iter().filter_map(|v| taken[v].take()));
+        Ok(())
+    }
+
+    /// Finds the address of a section: its index at each level, from the top down.
+    fn locate(&self, href: &str) -> Option<Vec<usize>> {
+        let (path, anchor) = split_href(href);
+
+        let exact = locate_in(&self.sections, &|v: &ContentInfo| v.is(path, anchor));
+        match (exact, anchor) {
+            (None, None) => locate_in(&self.sections, &|v: &ContentInfo| v.path == path),
+            (exact, _) => exact,
+        }
+    }
+
+    /// Finds the address of a section, failing if it does not exist.
+    fn find(&self, path: &str) -> Result<Vec<usize>> {
+        self.locate(path)
+            .ok_or_else(|| FobzError::UnknownPath(path.to_string()))
+    }
+
+    /// Fails if a section with the same reference as `info` already exists.
+    fn check_unique(&self, info: &ContentInfo) -> Result<()> {
+        let anchor = info.anchor.as_deref();
+        if locate_in(&self.sections, &|v: &ContentInfo| v.is(&info.path, anchor)).is_some() {
+            return Err(FobzError::DuplicatePath(info.href()));
+        }
+        Ok(())
+    }
+
+    /// Returns the section at an address.
+    fn node(&self, address: &[usize]) -> &ContentInfo {
+        let (first, rest) = address.split_first().expect("addresses are never empty");
+        rest.iter()
+            .fold(&self.sections[*first], |node, v| &node.children[*v])
+    }
+
+    /// Returns the section at an address, mutably.
+    fn node_mut(&mut self, address: &[usize]) -> &mut ContentInfo {
+        let (first, rest) = address.split_first().expect("addresses are never empty");
+        rest.iter()
+            .fold(&mut self.sections[*first], |node, v| &mut node.children[*v])
+    }
+
+    /// Returns the list of children of the section at `parent`, or the top level if empty.
+    fn siblings_mut(&mut self, parent: &[usize]) -> &mut Vec<ContentInfo> {
+        if parent.is_empty() {
+            &mut self.sections
+        } else {
+            &mut self.node_mut(parent).children
+        }
+    }
+}
+
+/// Finds the address of the first section, depth-first, satisfying `predicate`.
+fn locate_in(
+    sections: &[ContentInfo],
+    predicate: &dyn Fn(&ContentInfo) -> bool,
+) -> Option<Vec<usize>> {
+    for (i, info) in sections.iter().enumerate() {
+        if predicate(info) {
+            return Some(vec![i]);
+        }
+        if let Some(mut address) = locate_in(&info.children, predicate) {
+            address.insert(0, i);
+            return Some(address);
+        }
+    }
+    None
+}
+
+/// Removes the sections satisfying `predicate`, replacing each with its own (filtered) children.
+fn remove_from(sections: &mut Vec<ContentInfo>, predicate: &dyn Fn(&ContentInfo) -> bool) {
+    let mut kept = Vec::with_capacity(sections.len());
+
+    for mut info in sections.drain(..) {
+        remove_from(&mut info.children, predicate);
+        if predicate(&info) {
+            kept.append(&mut info.children);
+        } else {
+            kept.push(info);
+        }
+    }
+
+    *sections = kept;
+}
+
+/// Points the sections of `path` at `new_path`, recursively.
+fn rename_in(sections: &mut [ContentInfo], path: &str, new_path: &str) {
+    for info in sections {
+        if info.path == path {
+            info.path = new_path.into();
+        }
+        rename_in(&mut info.children, path, new_path);
+    }
+}
+
+/// Checks that `index` is at most `max`, reporting `len` entries otherwise.
+fn check_index(index: usize, max: usize, len: usize) -> Result<()> {
+    if index > max {
+        return Err(FobzError::IndexOutOfBounds { index, len });
     }
+    Ok(())
 }
@@ -5,17 +5,20 @@ use serde::{Deserialize, Serialize};
 /// # Fields
 /// - `path`: Path to the resource file within the `.fobz` archive.
 /// - `name`: Descriptive name of the resource used if unable to load the file.
+/// - `media_type`: MIME type of the resource (e.g., `image/png`), empty in archives predating it.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ResourceInfo {
     pub path: String,
     pub name: String,
+    #[serde(default)]
+    pub media_type: String,
 }
 
 /// Represents the table of resources, a collection of resources used in the `.fobz` document.
 ///
 /// # Fields
 /// - `resources`: A vector of `ResourceInfo` items.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct TableOfResources {
     resources: Vec<ResourceInfo>,
 }
@@ -35,8 +38,27 @@ impl TableOfResources {
     ///
     /// # Returns
     /// An `Option` containing a reference to `ResourceInfo` if found, or `None` if not found.
-    pub fn get(&self, path: &String) -> Option<&ResourceInfo> {
-        self.resources.iter().find(|v| &v.path == path)
+    pub fn get(&self, path: &str) -> Option<&ResourceInfo> {
+        self.resources.iter().find(|v| v.path == path)
+    }
+
+    /// Retrieves a mutable reference to the `ResourceInfo` associated with the given path.
+    ///
+    /// # Parameters
+    /// - `path`: The path of the resource to search for.
+    ///
+    /// # Returns
+    /// An `Option` containing a mutable reference to `ResourceInfo` if found, or `None` if not found.
+    pub fn get_mut(&mut self, path: &str) -> Option<&mut ResourceInfo> {
+        self.resources.iter_mut().find(|v| v.path == path)
+    }
+
+    /// Returns an iterator over the resources, in order.
+    ///
+    /// # Returns
+    /// An iterator yielding a reference to each `ResourceInfo`.
+    pub fn iter(&self) -> impl Iterator<Item = &ResourceInfo> {
+        self.resources.iter()
     }
 
     /// Adds a new resource to the table of resources.
@@ -51,7 +73,7 @@ impl TableOfResources {
     ///
     /// # Parameters
     /// - `path`: The path of the resource to remove.
-    pub fn remove(&mut self, path: &String) {
-        self.resources.retain(|v| &v.path != path);
+    pub fn remove(&mut self, path: &str) {
+        self.resources.retain(|v| v.path != path);
     }
 }
@@ -13,7 +13,7 @@ pub struct StyleInfo {
 ///
 /// # Fields
 /// - `styles`: A vector of `StyleInfo` items, each pointing to a distinct stylesheet.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct TableOfStyles {
     styles: Vec<StyleInfo>,
 }
@@ -33,8 +33,27 @@ impl TableOfStyles {
     ///
     /// # Returns
     /// An `Option` containing a reference to `StyleInfo` if found, or `None` if not found.
-    pub fn get(&self, path: &String) -> Option<&StyleInfo> {
-        self.styles.iter().find(|v| &v.path == path)
+    pub fn get(&self, path: &str) -> Option<&StyleInfo> {
+        self.styles.iter().find(|v| v.path == path)
+    }
+
+    /// Retrieves a mutable reference to the `StyleInfo` associated with the given path.
+    ///
+    /// # Parameters
+    /// - `path`: The path of the stylesheet to search for.
+    ///
+    /// # Returns
+    /// An `Option` containing a mutable reference to `StyleInfo` if found, or `None` if not found.
+    pub fn get_mut(&mut self, path: &str) -> Option<&mut StyleInfo> {
+        self.styles.iter_mut().find(|v| v.path == path)
+    }
+
+    /// Returns an iterator over the stylesheets, in order.
+    ///
+    /// # Returns
+    /// An iterator yielding a reference to each `StyleInfo`.
+    pub fn iter(&self) -> impl Iterator<Item = &StyleInfo> {
+        self.styles.iter()
     }
 
     /// Adds a new stylesheet to the table of stylesheets.
@@ -49,7 +68,7 @@ impl TableOfStyles {
     ///
     /// # Parameters
     /// - `path`: The path of the stylesheet to remove.
-    pub fn remove(&mut self, path: &String) {
-        self.styles.retain(|v| &v.path != path);
+    pub fn remove(&mut self, path: &str) {
+        self.styles.retain(|v| v.path != path);
     }
 }
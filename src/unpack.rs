@@ -0,0 +1,380 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs, io,
+    path::Path,
+    sync::OnceLock,
+};
+
+use serde::Serialize;
+
+use crate::{
+    check_path, decode_binary, decode_text,
+    error::{FobzError, Result},
+    html, media,
+    migration::{self, Layout},
+    options::PackOptions,
+    search,
+    toc::{split_href, ContentInfo},
+    tor::ResourceInfo,
+    tos::StyleInfo,
+    write_file, FobZ, CONTENT_EXTENSIONS, DEFAULT_COVER, DEFAULT_SECTION, STYLE_EXTENSIONS,
+};
+
+// The directories of an unpacked document holding its files, managed by `unpack` and `pack`.
+const TREES: &[&str] = &["contents", "resources", "styles", "default"];
+
+impl FobZ {
+    /// Writes the document to a directory as editable files, in the same layout as the archive.
+    ///
+    /// The manifest and the tables are written as pretty-printed JSON next to the `contents/`,
+    /// `resources/`, `styles/`, and `default/` directories. Files listed by the manifest and tables
+    /// an earlier unpack left in `dir` that no longer belong to the document are removed, while
+    /// any other file (e.g., `.gitkeep` or notes added by hand) is left alone. The stored search
+    /// index is written
+    /// to `search/index.bin`, which is removed when there is none. [`FobZ::pack`] reads the
+    /// directory back into an identical document.
+    ///
+    /// # Parameters
+    /// - `dir`: The directory to write the document to, created if missing.
+    ///
+    /// # Returns
+    /// A result indicating success, `FobzError::UnsupportedVersion` if the document was read from
+    /// a newer format version, `FobzError::InvalidPath` if an entry of the document has a path
+    /// that would leave `dir`, or another error if a payload could not be read, if the tables
+    /// already in `dir` could not be read, or if a file could not be written.
+    pub fn unpack(&self, dir: &str) -> Result<()> {
+        migration::check_writable(self.manifest.get_version())?;
+
+        let root = Path::new(dir);
+        let unpacked = unpacked_files(root)?;
+
+        let mut entries: Vec<(&str, &[u8])> = vec![];
+        for path in self.contents.keys() {
+            if let Some(content) = self.load(&self.contents, path, decode_text)? {
+                entries.push((path, content.as_bytes()));
+            }
+        }
+        for path in self.resources.keys() {
+            if let Some(resource) = self.load(&self.resources, path, decode_binary)? {
+                entries.push((path, resource));
+            }
+        }
+        for path in self.styles.keys() {
+            if let Some(style) = self.load(&self.styles, path, decode_text)? {
+                entries.push((path, style.as_bytes()));
+            }
+        }
+        entries.sort_unstable_by_key(|(path, _)| *path);
+
+        write_json_file(root, "manifest.json", &self.manifest)?;
+        write_json_file(root, "toc.json", &self.toc)?;
+        write_json_file(root, "tor.json", &self.tor)?;
+        write_json_file(root, "tos.json", &self.tos)?;
+
+        let kept: HashSet<&str> = entries.iter().map(|(path, _)| *path).collect();
+        for path in unpacked.iter().filter(|v| !kept.contains(v.as_str())) {
+            remove_unpacked(root, path)?;
+        }
+        for (path, bytes) in entries {
+            write_file(root, path, bytes)?;
+        }
+
+        // The search index lives outside the trees, so a stale one is removed here.
+        match &self.search {
+            Some(index) => write_file(root, search::INDEX_ENTRY, &index.to_bytes())?,
+            None => {
+                match fs::remove_file(root.join(search::INDEX_ENTRY)) {
+                    Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err.into()),
+                    _ => {}
+                }
+                remove_empty_dirs(&root.join("search"))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Rebuilds a document from a directory written by [`FobZ::unpack`].
+    ///
+    /// Every file of `contents/`, `resources/`, and `styles/` must be listed in its table; hidden
+    /// files are ignored. When `search/index.bin` is present, the search index is rebuilt from the
+    /// contents, which may have been edited. Layouts written with an older format version are
+    /// migrated.
+    ///
+    /// # Parameters
+    /// - `dir`: The directory to read the document from.
+    ///
+    /// # Returns
+    /// A result containing the `FobZ` instance, `FobzError::UnlistedEntry` if a file is not
+    /// listed in its table, or another error as described in [`FobZ::pack_with`].
+    pub fn pack(dir: &str) -> Result<Self> {
+        Self::pack_with(dir, &PackOptions::default())
+    }
+
+    /// Rebuilds a document from a directory written by [`FobZ::unpack`] using the given options.
+    ///
+    /// # Parameters
+    /// - `dir`: The directory to read the document from.
+    /// - `options`: The options controlling how files missing from the tables are handled.
+    ///
+    /// # Returns
+    /// A result containing the `FobZ` instance, `FobzError::UnlistedEntry` if a file is not
+    /// listed in its table and `options.auto_register` is off, `FobzError::InvalidPath` if a file
+    /// has a name or extension not accepted in its directory, `FobzError::MissingEntry` if the
+    /// manifest or a table is missing, or if a table entry or the manifest's index or cover has
+    /// no file, or another error if a file could not be read or parsed.
+    pub fn pack_with(dir: &str, options: &PackOptions) -> Result<Self> {
+        let root = Path::new(dir);
+        let Layout {
+            manifest,
+            toc,
+            tor,
+            tos,
+        } = migration::read_layout_with(|entry| read_entry(root, entry))?;
+
+        let mut fobz = FobZ {
+            manifest,
+            toc,
+            tor,
+            tos,
+            contents: HashMap::new(),
+            resources: HashMap::new(),
+            styles: HashMap::new(),
+            policy: Default::default(),
+            source: None,
+            search: None,
+            sanitize_policy: Default::default(),
+            sanitize_report: Default::default(),
+        };
+
+        // The stored index may no longer match the contents, so it is only used as a marker.
+        let indexed = root.join(search::INDEX_ENTRY).try_exists()?;
+
+        let mut files = vec![];
+        for tree in TREES {
+            collect_files(root, tree, &mut files)?;
+        }
+        files.sort_unstable();
+
+        for path in files {
+            let bytes = fs::read(root.join(&path))?;
+            let unlisted = || -> Result<()> {
+                if options.auto_register {
+                    Ok(())
+                } else {
+                    Err(FobzError::UnlistedEntry(path.clone()))
+                }
+            };
+
+            if path.starts_with("default/") {
+                // Defaults are not listed in any table, and are told apart as `FobZ::new` does.
+                if path.ends_with(".html") {
+                    let content = decode_text(&path, bytes)?;
+                    fobz.contents.insert(path, OnceLock::from(content));
+                } else {
+                    fobz.resources.insert(path, OnceLock::from(bytes));
+                }
+            } else if path.starts_with("contents/") {
+                check_path(&path, "contents/", CONTENT_EXTENSIONS)?;
+                let content = decode_text(&path, bytes)?;
+                if fobz.toc.get(&path).is_none() {
+                    unlisted()?;
+                    let title = html::document_title(&content).unwrap_or_else(|| file_stem(&path));
+                    fobz.toc.add(ContentInfo::new(path.clone(), title));
+                }
+                fobz.contents.insert(path, OnceLock::from(content));
+            } else if path.starts_with("resources/") {
+                check_path(&path, "resources/", &[])?;
+                match fobz.tor.get_mut(&path) {
+                    Some(info) if info.media_type.is_empty() => {
+                        info.media_type = media::detect(&path, &bytes);
+                    }
+                    Some(_) => {}
+                    None => {
+                        unlisted()?;
+                        fobz.tor.add(ResourceInfo {
+                            path: path.clone(),
+                            name: file_stem(&path),
+                            media_type: media::detect(&path, &bytes),
+                        });
+                    }
+                }
+                fobz.resources.insert(path, OnceLock::from(bytes));
+            } else {
+                check_path(&path, "styles/", STYLE_EXTENSIONS)?;
+                let style = decode_text(&path, bytes)?;
+                if fobz.tos.get(&path).is_none() {
+                    unlisted()?;
+                    fobz.tos.add(StyleInfo { path: path.clone() });
+                }
+                fobz.styles.insert(path, OnceLock::from(style));
+            }
+        }
+
+        // Every table entry, and the manifest's index and cover unless they are the built-in
+        // defaults, must have a file. The index may point at an anchor of its file.
+        let index = fobz.manifest.get_index();
+        let cover = fobz.manifest.get_cover();
+        let missing = fobz
+            .toc
+            .iter()
+            .map(|v| v.path.as_str())
+            .chain((index != DEFAULT_SECTION).then(|| split_href(index).0))
+            .find(|v| !fobz.contents.contains_key(*v))
+            .or_else(|| {
+                fobz.tor
+                    .iter()
+                    .map(|v| v.path.as_str())
+                    .chain((cover != DEFAULT_COVER).then_some(cover.as_str()))
+                    .find(|v| !fobz.resources.contains_key(*v))
+            })
+            .or_else(|| {
+                fobz.tos
+                    .iter()
+                    .map(|v| v.path.as_str())
+                    .find(|v| !fobz.styles.contains_key(*v))
+            });
+        if let Some(path) = missing {
+            return Err(FobzError::MissingEntry(path.into()));
+        }
+
+        if indexed {
+            fobz.store_search_index()?;
+        }
+
+        Ok(fobz)
+    }
+}
+
+/// Reads the manifest or a table of an unpacked document.
+fn read_entry(root: &Path, entry: &str) -> Result<Vec<u8>> {
+    fs::read(root.join(entry)).map_err(|err| match err.kind() {
+        io::ErrorKind::NotFound => FobzError::MissingEntry(entry.into()),
+        _ => err.into(),
+    })
+}
+
+/// Writes a value as a pretty-printed JSON file ending with a newline.
+fn write_json_file<T: Serialize>(root: &Path, entry: &str, value: &T) -> Result<()> {
+    let mut json =
+        serde_json::to_string_pretty(value).map_err(|err| FobzError::json(entry, err))?;
+    json.push('\n');
+    write_file(root, entry, json.as_bytes())
+}
+
+/// Lists the files of a directory of an unpacked document and its subdirectories, skipping
+/// hidden ones, as paths relative to `root`.
+fn collect_files(root: &Path, dir: &str, files: &mut Vec<String>) -> Result<()> {
+    let entries = match fs::read_dir(root.join(dir)) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err.into()),
+    };
+
+    for entry in entries {
+        let entry = entry?;
+        let name = entry.file_name();
+        let Some(name) = name.to_str() else {
+            return Err(FobzError::InvalidPath(format!(
+                "{}/{}",
+                dir,
+                name.to_string_lossy()
+            )));
+        };
+        if name.starts_with('.') {
+            continue;
+        }
+
+        let path = format!("{}/{}", dir, name);
+        if entry.file_type()?.is_dir() {
+            collect_files(root, &path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+/// Lists the files written by an earlier unpack to `root`, from the tables it left there.
+///
+/// # Returns
+/// A result containing the paths listed in the tables along with the built-in defaults, none if
+/// `root` holds no manifest, or an error if the manifest or a table could not be read.
+fn unpacked_files(root: &Path) -> Result<Vec<String>> {
+    if !root.join("manifest.json").try_exists()? {
+        return Ok(vec![]);
+    }
+    let Layout { toc, tor, tos, .. } =
+        migration::read_layout_with(|entry| read_entry(root, entry))?;
+
+    let files = toc
+        .iter()
+        .map(|v| v.path.clone())
+        .chain(tor.iter().map(|v| v.path.clone()))
+        .chain(tos.iter().map(|v| v.path.clone()))
+        .chain([DEFAULT_SECTION.into(), DEFAULT_COVER.into()])
+        .collect();
+    Ok(files)
+}
+
+/// Removes a file written by an earlier unpack, along with the directories of its tree this
+/// leaves empty. Paths outside the trees, or that could leave `root`, are left alone.
+fn remove_unpacked(root: &Path, path: &str) -> Result<()> {
+    let in_tree = TREES
+        .iter()
+        .any(|v| path.strip_prefix(v).is_some_and(|v| v.starts_with('/')));
+    let escapes = path.contains('\\')
+        || path
+            .split('/')
+            .any(|v| v.is_empty() || v == "." || v == "..");
+    if !in_tree || escapes {
+        return Ok(());
+    }
+
+    match fs::remove_file(root.join(path)) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err.into()),
+        _ => {}
+    }
+    let mut path = path;
+    while let Some((parent, _)) = path.rsplit_once('/') {
+        let empty = fs::read_dir(root.join(parent)).is_ok_and(|mut v| v.next().is_none());
+        if !empty {
+            break;
+        }
+        fs::remove_dir(root.join(parent))?;
+        path = parent;
+    }
+
+    Ok(())
+}
+
+/// Removes a directory and its subdirectories if they hold no files, returning whether it was
+/// removed.
+fn remove_empty_dirs(dir: &Path) -> Result<bool> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(false),
+        Err(err) => return Err(err.into()),
+    };
+
+    let mut empty = true;
+    for entry in entries {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() || !remove_empty_dirs(&entry.path())? {
+            empty = false;
+        }
+    }
+    if empty {
+        fs::remove_dir(dir)?;
+    }
+
+    Ok(empty)
+}
+
+/// Extracts the file name of a path, without its extension.
+fn file_stem(path: &str) -> String {
+    Path::new(path)
+        .file_stem()
+        .map_or_else(|| path.into(), |v| v.to_string_lossy().into())
+}
@@ -0,0 +1,295 @@
+use std::{
+    collections::HashSet,
+    fmt,
+    fs::File,
+    io::{Read, Seek},
+};
+
+use crate::{
+    archive,
+    error::Result,
+    manifest::Manifest,
+    migration::{read_layout, Layout},
+    search,
+    toc::{split_href, TableOfContents},
+    tor::TableOfResources,
+    tos::TableOfStyles,
+    FobZ, DEFAULT_COVER, DEFAULT_SECTION,
+};
+
+/// Describes how serious a validation issue is.
+///
+/// # Variants
+/// - `Error`: The document is broken (e.g., a table entry points to a missing file).
+/// - `Warning`: The document is usable but contains something unexpected (e.g., an unlisted file).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// Identifies the kind of problem found by a validation pass.
+///
+/// # Variants
+/// - `DanglingEntry`: A table entry has no matching file in the archive.
+/// - `OrphanFile`: A file is present in the archive but not listed in any table.
+/// - `DuplicatePath`: The same path is listed more than once in a table.
+/// - `UnresolvedIndex`: `Manifest.index` does not point to a listed content.
+/// - `UnresolvedCover`: `Manifest.cover` does not point to a listed resource.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IssueKind {
+    DanglingEntry,
+    OrphanFile,
+    DuplicatePath,
+    UnresolvedIndex,
+    UnresolvedCover,
+}
+
+/// Represents a single problem found while validating a document.
+///
+/// # Fields
+/// - `severity`: Whether the issue is an error or a warning.
+/// - `kind`: The kind of problem.
+/// - `path`: The entry path the issue refers to.
+/// - `message`: A human-readable description of the problem.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationIssue {
+    pub severity: Severity,
+    pub kind: IssueKind,
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, "{}: {}: {}", severity, self.path, self.message)
+    }
+}
+
+/// Collects every issue found while validating a document.
+///
+/// # Fields
+/// - `issues`: The issues, in the order they were found.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    /// Returns every issue found, errors and warnings alike.
+    pub fn issues(&self) -> &[ValidationIssue] {
+        &self.issues
+    }
+
+    /// Returns an iterator over the issues with `Severity::Error`.
+    pub fn errors(&self) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues.iter().filter(|v| v.severity == Severity::Error)
+    }
+
+    /// Returns an iterator over the issues with `Severity::Warning`.
+    pub fn warnings(&self) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues
+            .iter()
+            .filter(|v| v.severity == Severity::Warning)
+    }
+
+    /// Checks whether the document has no errors. Warnings are allowed.
+    ///
+    /// # Returns
+    /// `true` if no issue has `Severity::Error`, otherwise `false`.
+    pub fn is_valid(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    /// Records a new issue.
+    fn push(&mut self, severity: Severity, kind: IssueKind, path: &str, message: String) {
+        self.issues.push(ValidationIssue {
+            severity,
+            kind,
+            path: path.into(),
+            message,
+        });
+    }
+}
+
+impl FobZ {
+    /// Checks the structural consistency of the document.
+    ///
+    /// The tables are compared against the stored contents, resources, and styles, and the
+    /// manifest's index and cover are resolved.
+    ///
+    /// # Returns
+    /// A `ValidationReport` listing every error and warning found.
+    pub fn validate(&self) -> ValidationReport {
+        let files = self
+            .contents
+            .keys()
+            .chain(self.resources.keys())
+            .chain(self.styles.keys())
+            .map(String::as_str)
+            .collect();
+
+        check(&self.manifest, &self.toc, &self.tor, &self.tos, &files)
+    }
+
+    /// Checks the structural consistency of a `.fobz` file without loading its payloads.
+    ///
+    /// # Parameters
+    /// - `path`: The file path to the `.fobz` archive.
+    ///
+    /// # Returns
+    /// A result containing the `ValidationReport`, or an error if the manifest or tables cannot be read.
+    pub fn validate_file(path: &str) -> Result<ValidationReport> {
+        let file = File::open(path)?;
+        Self::validate_reader(file)
+    }
+
+    /// Checks the structural consistency of a `.fobz` archive read from any seekable reader.
+    ///
+    /// Unlike [`FobZ::validate`], every file of the archive is considered, including those that
+    /// [`FobZ::open`] would skip.
+    ///
+    /// # Parameters
+    /// - `reader`: The source of the `.fobz` archive.
+    ///
+    /// # Returns
+    /// A result containing the `ValidationReport`, or an error if the manifest or tables cannot be
+    /// read, or if the archive is unsafe to read (see [`ReadLimits`](crate::options::ReadLimits)).
+    pub fn validate_reader<R: Read + Seek>(reader: R) -> Result<ValidationReport> {
+        let mut archive = archive::open(reader, &Default::default())?;
+
+        let Layout {
+            manifest,
+            toc,
+            tor,
+            tos,
+        } = read_layout(&mut archive)?;
+
+        let files = archive
+            .file_names()
+            .filter(|v| !v.ends_with('/'))
+            .filter(|v| !matches!(*v, "manifest.json" | "toc.json" | "tor.json" | "tos.json"))
+            .filter(|v| *v != search::INDEX_ENTRY)
+            .collect();
+
+        Ok(check(&manifest, &toc, &tor, &tos, &files))
+    }
+}
+
+/// Runs every structural check against the manifest, the tables, and the stored file names.
+fn check(
+    manifest: &Manifest,
+    toc: &TableOfContents,
+    tor: &TableOfResources,
+    tos: &TableOfStyles,
+    files: &HashSet<&str>,
+) -> ValidationReport {
+    let mut report = ValidationReport::default();
+
+    let contents = check_table(&mut report, "toc.json", toc.iter().map(|v| v.href()), files);
+    let resources = check_table(
+        &mut report,
+        "tor.json",
+        tor.iter().map(|v| v.path.clone()),
+        files,
+    );
+    let styles = check_table(
+        &mut report,
+        "tos.json",
+        tos.iter().map(|v| v.path.clone()),
+        files,
+    );
+
+    // Files that no table mentions. Built-in defaults are provided by the library itself.
+    let mut orphans: Vec<&str> = files
+        .iter()
+        .copied()
+        .filter(|v| !v.starts_with("default/"))
+        .filter(|v| !contents.contains(*v) && !resources.contains(*v) && !styles.contains(*v))
+        .collect();
+    orphans.sort_unstable();
+
+    for path in orphans {
+        report.push(
+            Severity::Warning,
+            IssueKind::OrphanFile,
+            path,
+            "file is not listed in any table".into(),
+        );
+    }
+
+    // The index may point at an anchor of a content, so only its file is looked up.
+    let index = manifest.get_index();
+    let (index_path, _) = split_href(index);
+    if index != DEFAULT_SECTION && !(contents.contains(index_path) && files.contains(index_path)) {
+        report.push(
+            Severity::Error,
+            IssueKind::UnresolvedIndex,
+            index,
+            "manifest index does not point to a listed content".into(),
+        );
+    }
+
+    let cover = manifest.get_cover();
+    if cover != DEFAULT_COVER
+        && !(resources.contains(cover.as_str()) && files.contains(cover.as_str()))
+    {
+        report.push(
+            Severity::Error,
+            IssueKind::UnresolvedCover,
+            cover,
+            "manifest cover does not point to a listed resource".into(),
+        );
+    }
+
+    report
+}
+
+/// Reports duplicate and dangling entries of a single table.
+///
+/// Entries are compared by their full reference, so that several sections may point at different
+/// fragments of the same file.
+///
+/// # Returns
+/// The set of file paths listed in the table.
+fn check_table(
+    report: &mut ValidationReport,
+    table: &str,
+    hrefs: impl Iterator<Item = String>,
+    files: &HashSet<&str>,
+) -> HashSet<String> {
+    let mut seen = HashSet::new();
+    let mut duplicates = HashSet::new();
+    let mut listed = HashSet::new();
+
+    for href in hrefs {
+        if seen.contains(&href) {
+            if duplicates.insert(href.clone()) {
+                report.push(
+                    Severity::Error,
+                    IssueKind::DuplicatePath,
+                    &href,
+                    format!("path is listed more than once in {}", table),
+                );
+            }
+            continue;
+        }
+
+        let (path, _) = split_href(&href);
+        if listed.insert(path.to_string()) && !files.contains(path) {
+            report.push(
+                Severity::Error,
+                IssueKind::DanglingEntry,
+                path,
+                format!("{} entry has no matching file", table),
+            );
+        }
+        seen.insert(href);
+    }
+
+    listed
+}
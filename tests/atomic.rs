@@ -0,0 +1,66 @@
+use std::{fs, io::Cursor, path::PathBuf, process};
+
+use fobzip::{
+    compression::{Compression, CompressionPolicy},
+    options::{LoadMode, OpenOptions, SaveOptions},
+    FobZ,
+};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("fobzip-{}-{}", name, process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn failed_save_leaves_the_existing_file_untouched() {
+    let dir = temp_dir("atomic");
+    let path = dir.join("book.fobz");
+    let path = path.to_str().unwrap();
+
+    let mut fobz = FobZ::new("".into(), "".into(), "".into(), vec![]);
+    fobz.add_content(
+        "contents/ch1.html".into(),
+        "Chapter 1".into(),
+        "<p>Original chapter.</p>".into(),
+    )
+    .unwrap();
+    fobz.save_to(path).unwrap();
+    let original = fs::read(path).unwrap();
+
+    // A lazily opened document whose content is corrupted fails halfway through saving.
+    let options = SaveOptions {
+        compression: CompressionPolicy::new(Compression::Stored),
+        ..Default::default()
+    };
+    let mut bytes = fobz
+        .write_to_with(Cursor::new(Vec::new()), &options)
+        .unwrap()
+        .into_inner();
+    let needle = b"Original chapter";
+    let offset = bytes
+        .windows(needle.len())
+        .position(|v| v == needle)
+        .unwrap();
+    bytes[offset] = b'o';
+    let broken = FobZ::from_reader_with(
+        Cursor::new(bytes),
+        &OpenOptions {
+            mode: LoadMode::Lazy,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    assert!(broken.save_to(path).is_err());
+    assert_eq!(fs::read(path).unwrap(), original);
+
+    let files: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|v| v.unwrap().file_name())
+        .collect();
+    assert_eq!(files, ["book.fobz"]);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,200 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+use fobzip::FobZ;
+use serde_json::Value;
+
+/// Saves a small document into a fresh directory of the system's temporary directory.
+fn workspace(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("fobzip-cli-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+
+    let mut fobz = FobZ::new(
+        "Sample".into(),
+        "John Doe".into(),
+        "A description.".into(),
+        vec!["sample".into()],
+    );
+    fobz.add_content(
+        "contents/one.html".into(),
+        "One".into(),
+        "<p>First</p>".into(),
+    )
+    .unwrap();
+    fobz.get_manifest_mut()
+        .set_index("contents/one.html".into());
+    fobz.save_to(dir.join("book.fobz").to_str().unwrap())
+        .unwrap();
+    fs::write(dir.join("two.html"), "<h1>Two</h1>").unwrap();
+
+    dir
+}
+
+fn fobz(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_fobz"))
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn json(output: &Output) -> Value {
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+#[test]
+fn document_is_edited() {
+    let dir = workspace("edit");
+
+    let output = fobz(
+        &dir,
+        &[
+            "add-content",
+            "book.fobz",
+            "contents/two.html",
+            "two.html",
+            "--title",
+            "Two",
+        ],
+    );
+    assert!(output.status.success());
+    assert!(fobz(&dir, &["set-meta", "book.fobz", "language", "en"])
+        .status
+        .success());
+    assert!(
+        fobz(&dir, &["set-meta", "book.fobz", "series", "Samples", "2"])
+            .status
+            .success()
+    );
+
+    let output = fobz(&dir, &["--json", "ls", "book.fobz"]);
+    assert!(output.status.success());
+    let entries = json(&output);
+    let paths: Vec<&str> = entries
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| v["path"].as_str().unwrap())
+        .collect();
+    assert!(paths.ends_with(&["contents/one.html", "contents/two.html"]));
+
+    let output = fobz(&dir, &["cat", "book.fobz", "contents/two.html"]);
+    assert_eq!(output.stdout, b"<h1>Two</h1>");
+
+    let info = json(&fobz(&dir, &["info", "book.fobz", "--json"]));
+    assert_eq!(info["manifest"]["language"], "en");
+    assert_eq!(info["manifest"]["series"]["position"], 2);
+
+    assert!(fobz(&dir, &["rm", "book.fobz", "contents/two.html"])
+        .status
+        .success());
+    let fobz = FobZ::open(dir.join("book.fobz").to_str().unwrap()).unwrap();
+    assert!(fobz.get_content("contents/two.html").is_none());
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn documents_are_edited_in_place_whatever_their_extension() {
+    let dir = workspace("in-place");
+    fs::rename(dir.join("book.fobz"), dir.join("book.zip")).unwrap();
+    fs::copy(dir.join("book.zip"), dir.join("book")).unwrap();
+
+    for file in ["book.zip", "book"] {
+        assert!(fobz(&dir, &["set-meta", file, "language", "en"])
+            .status
+            .success());
+        assert!(fobz(
+            &dir,
+            &["add-content", file, "contents/two.html", "two.html"]
+        )
+        .status
+        .success());
+
+        let fobz = FobZ::open(dir.join(file).to_str().unwrap()).unwrap();
+        assert_eq!(fobz.get_manifest().get_language().unwrap(), "en");
+        assert!(fobz.get_content("contents/two.html").is_some());
+    }
+
+    let mut files: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|v| v.unwrap().file_name())
+        .collect();
+    files.sort();
+    assert_eq!(files, ["book", "book.zip", "two.html"]);
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn exit_codes_tell_failures_apart() {
+    let dir = workspace("codes");
+
+    let output = fobz(&dir, &["--json", "validate", "book.fobz"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(json(&output)["valid"], true);
+
+    assert!(fobz(
+        &dir,
+        &["set-meta", "book.fobz", "index", "contents/none.html"]
+    )
+    .status
+    .success());
+    let output = fobz(&dir, &["--json", "validate", "book.fobz"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(json(&output)["issues"][0]["kind"], "unresolved_index");
+
+    assert_eq!(fobz(&dir, &["frobnicate"]).status.code(), Some(2));
+    assert_eq!(fobz(&dir, &["cat", "book.fobz"]).status.code(), Some(2));
+    assert_eq!(
+        fobz(&dir, &["rm", "book.fobz", "contents/none.html"])
+            .status
+            .code(),
+        Some(3)
+    );
+    assert_eq!(
+        fobz(&dir, &["add-content", "book.fobz", "one.html", "two.html"])
+            .status
+            .code(),
+        Some(4)
+    );
+    assert_eq!(fobz(&dir, &["info", "missing.fobz"]).status.code(), Some(5));
+
+    let output = fobz(&dir, &["--json", "cat", "book.fobz", "contents/none.html"]);
+    let error: Value = serde_json::from_slice(&output.stderr).unwrap();
+    assert_eq!(error["code"], 3);
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn documents_are_converted() {
+    let dir = workspace("convert");
+
+    assert!(fobz(&dir, &["convert", "book.fobz", "book.epub"])
+        .status
+        .success());
+    assert!(fobz(&dir, &["convert", "book.epub", "copy.fobz"])
+        .status
+        .success());
+    assert!(fobz(&dir, &["convert", "copy.fobz", "book.md"])
+        .status
+        .success());
+    assert_eq!(fs::read_to_string(dir.join("book.md")).unwrap(), "First\n");
+    assert!(
+        fobz(&dir, &["convert", "book.fobz", "site", "--to", "site"])
+            .status
+            .success()
+    );
+    assert!(dir.join("site/index.html").is_file());
+    assert_eq!(
+        fobz(&dir, &["convert", "book.fobz", "book"]).status.code(),
+        Some(2)
+    );
+
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,108 @@
+use std::io::Cursor;
+
+use fobzip::{
+    compression::{Compression, CompressionPolicy, EntryKind, EntryMatcher},
+    error::FobzError,
+    options::SaveOptions,
+    FobZ,
+};
+use zip::{CompressionMethod, ZipArchive};
+
+const PNG: &[u8] = b"\x89PNG\r\n\x1A\n\0\0\0\0";
+
+#[test]
+fn policy_rules_pick_the_method_of_each_entry() {
+    let mut fobz = FobZ::new("".into(), "".into(), "".into(), vec![]);
+    fobz.add_content(
+        "contents/ch1.html".into(),
+        "Chapter 1".into(),
+        "<p>Chapter 1</p>".repeat(20),
+    )
+    .unwrap();
+    fobz.add_resource("resources/cat.png".into(), "Cat".into(), PNG.to_vec())
+        .unwrap();
+    fobz.add_style("styles/main.css".into(), "p { margin: 0; }\n".repeat(20))
+        .unwrap();
+
+    let mut compression = CompressionPolicy::new(Compression::Deflated(Some(9)));
+    compression.add_rule(
+        EntryMatcher::Kind(EntryKind::Content),
+        Compression::Bzip2(None),
+    );
+    compression.add_rule(
+        EntryMatcher::Pattern("styles/**.css".into()),
+        Compression::Zstd(Some(3)),
+    );
+    compression.add_rule(
+        EntryMatcher::MediaType("image/".into()),
+        Compression::Stored,
+    );
+    let options = SaveOptions {
+        compression,
+        ..Default::default()
+    };
+    let bytes = fobz
+        .write_to_with(Cursor::new(Vec::new()), &options)
+        .unwrap()
+        .into_inner();
+
+    let mut archive = ZipArchive::new(Cursor::new(bytes.as_slice())).unwrap();
+    for (name, method) in [
+        ("manifest.json", CompressionMethod::Deflated),
+        ("contents/ch1.html", CompressionMethod::Bzip2),
+        ("resources/cat.png", CompressionMethod::Stored),
+        ("styles/main.css", CompressionMethod::Zstd),
+    ] {
+        assert_eq!(
+            archive.by_name(name).unwrap().compression(),
+            method,
+            "{}",
+            name
+        );
+    }
+
+    let reopened = FobZ::from_bytes(&bytes).unwrap();
+    assert_eq!(
+        reopened.get_content("contents/ch1.html").unwrap().1,
+        &"<p>Chapter 1</p>".repeat(20)
+    );
+    assert_eq!(reopened.get_resource("resources/cat.png").unwrap().1, PNG);
+    assert_eq!(
+        reopened.get_style("styles/main.css").unwrap().1,
+        &"p { margin: 0; }\n".repeat(20)
+    );
+}
+
+#[test]
+fn levels_out_of_range_are_refused() {
+    let fobz = FobZ::new("".into(), "".into(), "".into(), vec![]);
+
+    for (compression, range) in [
+        (Compression::Deflated(Some(99)), (0, 9)),
+        (Compression::Deflated(Some(-1)), (0, 9)),
+        (Compression::Bzip2(Some(0)), (1, 9)),
+        (Compression::Zstd(Some(99)), (1, 22)),
+    ] {
+        // Even a rule that matches no entry is checked.
+        let mut policy = CompressionPolicy::new(Compression::Deflated(None));
+        policy.add_rule(EntryMatcher::Pattern("none/**".into()), compression);
+        let options = SaveOptions {
+            compression: policy,
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            fobz.write_to_with(Cursor::new(Vec::new()), &options),
+            Err(FobzError::InvalidCompressionLevel { compression: v, min, max })
+                if v == compression && (min, max) == range
+        ));
+    }
+
+    let options = SaveOptions {
+        compression: CompressionPolicy::new(Compression::Zstd(Some(22))),
+        ..Default::default()
+    };
+    assert!(fobz
+        .write_to_with(Cursor::new(Vec::new()), &options)
+        .is_ok());
+}
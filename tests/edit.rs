@@ -0,0 +1,187 @@
+use fobzip::{error::FobzError, media::ResourcePolicy, FobZ};
+
+const PNG: &[u8] = b"\x89PNG\r\n\x1A\n\0\0\0\0";
+
+fn sample() -> FobZ {
+    let mut fobz = FobZ::new("".into(), "".into(), "".into(), vec![]);
+    fobz.add_content("contents/a.html".into(), "A".into(), "<p>A</p>".into())
+        .unwrap();
+    fobz.add_content("contents/b.html".into(), "B".into(), "<p>B</p>".into())
+        .unwrap();
+    fobz.add_resource("resources/a.png".into(), "A".into(), PNG.to_vec())
+        .unwrap();
+    fobz.add_style("styles/a.css".into(), "p {}".into())
+        .unwrap();
+    fobz
+}
+
+fn toc_paths(fobz: &FobZ) -> Vec<&str> {
+    fobz.get_toc().iter().map(|v| v.path.as_str()).collect()
+}
+
+#[test]
+fn invalid_paths_are_refused() {
+    let mut fobz = sample();
+
+    for path in [
+        "resources/c.html",
+        "contents/c.txt",
+        "contents/",
+        "contents/../c.html",
+        "contents/./c.html",
+        "contents//c.html",
+        "contents\\c.html",
+        "contents/c#d.html",
+        "contents/c?d.html",
+    ] {
+        let invalid = |result| matches!(result, Err(FobzError::InvalidPath(v)) if v == path);
+        assert!(invalid(fobz.add_content(path.into(), "".into(), "".into())));
+        assert!(invalid(fobz.upsert_content(
+            path.into(),
+            "".into(),
+            "".into()
+        )));
+        assert!(invalid(fobz.insert_content_at(
+            0,
+            path.into(),
+            "".into(),
+            "".into()
+        )));
+        assert!(invalid(fobz.add_child_content(
+            "contents/a.html",
+            path.into(),
+            "".into(),
+            "".into()
+        )));
+    }
+
+    assert!(matches!(
+        fobz.add_resource("styles/c.png".into(), "".into(), PNG.to_vec()),
+        Err(FobzError::InvalidPath(_))
+    ));
+    assert!(matches!(
+        fobz.add_style("styles/c.txt".into(), "".into()),
+        Err(FobzError::InvalidPath(_))
+    ));
+    assert!(matches!(
+        fobz.upsert_style("contents/c.css".into(), "".into()),
+        Err(FobzError::InvalidPath(_))
+    ));
+    assert!(matches!(
+        fobz.add_resource("resources/c#1.png".into(), "".into(), PNG.to_vec()),
+        Err(FobzError::InvalidPath(_))
+    ));
+    assert!(matches!(
+        fobz.add_style("styles/c?v=1.css".into(), "".into()),
+        Err(FobzError::InvalidPath(_))
+    ));
+
+    assert_eq!(toc_paths(&fobz), ["contents/a.html", "contents/b.html"]);
+}
+
+#[test]
+fn duplicate_paths_are_refused() {
+    let mut fobz = sample();
+
+    let duplicate =
+        |result| matches!(result, Err(FobzError::DuplicatePath(v)) if v == "contents/b.html");
+    assert!(duplicate(fobz.add_content(
+        "contents/b.html".into(),
+        "".into(),
+        "".into()
+    )));
+    assert!(duplicate(fobz.insert_content_at(
+        0,
+        "contents/b.html".into(),
+        "".into(),
+        "".into()
+    )));
+    assert!(duplicate(fobz.add_child_content(
+        "contents/a.html",
+        "contents/b.html".into(),
+        "".into(),
+        "".into()
+    )));
+    assert!(matches!(
+        fobz.add_resource("resources/a.png".into(), "".into(), PNG.to_vec()),
+        Err(FobzError::DuplicatePath(_))
+    ));
+    assert!(matches!(
+        fobz.add_style("styles/a.css".into(), "".into()),
+        Err(FobzError::DuplicatePath(_))
+    ));
+
+    assert_eq!(toc_paths(&fobz), ["contents/a.html", "contents/b.html"]);
+    assert_eq!(fobz.get_content("contents/b.html").unwrap().1, "<p>B</p>");
+}
+
+#[test]
+fn upserts_replace_existing_entries_in_place() {
+    let mut fobz = sample();
+
+    fobz.upsert_content("contents/a.html".into(), "A2".into(), "<p>A2</p>".into())
+        .unwrap();
+    fobz.upsert_resource("resources/a.png".into(), "A2".into(), b"GIF89a".to_vec())
+        .unwrap();
+    fobz.upsert_style("styles/a.css".into(), "p { margin: 0; }".into())
+        .unwrap();
+
+    assert_eq!(toc_paths(&fobz), ["contents/a.html", "contents/b.html"]);
+    let (info, content) = fobz.get_content("contents/a.html").unwrap();
+    assert_eq!((info.title.as_str(), content.as_str()), ("A2", "<p>A2</p>"));
+
+    assert_eq!(fobz.get_tor().iter().count(), 1);
+    let (info, resource) = fobz.get_resource("resources/a.png").unwrap();
+    assert_eq!(info.name, "A2");
+    assert_eq!(info.media_type, "image/png");
+    assert_eq!(resource, b"GIF89a");
+
+    assert_eq!(fobz.get_tos().iter().count(), 1);
+    assert_eq!(
+        fobz.get_style("styles/a.css").unwrap().1,
+        "p { margin: 0; }"
+    );
+}
+
+#[test]
+fn upserts_add_missing_entries() {
+    let mut fobz = sample();
+
+    fobz.upsert_content("contents/c.html".into(), "C".into(), "<p>C</p>".into())
+        .unwrap();
+    fobz.upsert_resource("resources/b.png".into(), "B".into(), PNG.to_vec())
+        .unwrap();
+    fobz.upsert_style("styles/b.css".into(), "p {}".into())
+        .unwrap();
+
+    assert_eq!(
+        toc_paths(&fobz),
+        ["contents/a.html", "contents/b.html", "contents/c.html"]
+    );
+    assert_eq!(fobz.get_content("contents/c.html").unwrap().1, "<p>C</p>");
+    assert_eq!(fobz.get_resource("resources/b.png").unwrap().1, PNG);
+    assert_eq!(fobz.get_style("styles/b.css").unwrap().1, "p {}");
+    assert!(fobz.validate().is_valid());
+}
+
+#[test]
+fn rejected_media_types_are_refused() {
+    let mut fobz = sample();
+    fobz.set_resource_policy(ResourcePolicy::images_only());
+
+    assert!(matches!(
+        fobz.add_resource("resources/a.pdf".into(), "".into(), b"%PDF-1.7".to_vec()),
+        Err(FobzError::RejectedMediaType { path, media_type })
+            if path == "resources/a.pdf" && media_type == "application/pdf"
+    ));
+    assert!(matches!(
+        fobz.upsert_resource_with_type(
+            "resources/a.png".into(),
+            "".into(),
+            "application/pdf".into(),
+            b"%PDF-1.7".to_vec()
+        ),
+        Err(FobzError::RejectedMediaType { .. })
+    ));
+    assert_eq!(fobz.get_resource("resources/a.png").unwrap().1, PNG);
+}
@@ -0,0 +1,740 @@
+use std::{
+    collections::HashMap,
+    io::{Cursor, Read, Write},
+};
+
+use fobzip::{
+    error::FobzError,
+    import::ImportIssueKind,
+    manifest::{Contributor, Identifier, Role, Series},
+    options::{Limit, ReadLimits},
+    FobZ,
+};
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+const OPF: &str = "http://www.idpf.org/2007/opf";
+const XHTML: &str = "http://www.w3.org/1999/xhtml";
+const PNG: &[u8] = b"\x89PNG\r\n\x1A\n\0\0\0\0";
+
+fn sample() -> FobZ {
+    let mut fobz = FobZ::new(
+        "Sample & Co".into(),
+        "John Doe".into(),
+        "A <short> description.".into(),
+        vec!["fiction".into()],
+    );
+
+    let manifest = fobz.get_manifest_mut();
+    manifest.add_contributor(Contributor::new("Jane Roe".into(), Role::Translator));
+    manifest.set_language(Some("en".into()));
+    manifest.set_published(Some("2024-05-01".into()));
+    manifest.add_identifier(Identifier::Isbn("978-3-16-148410-0".into()));
+    manifest.set_series(Some(Series::new("Samples".into(), 2)));
+    manifest.set_extra("edition".into(), "second".into());
+    manifest.set_index("contents/part1.html".into());
+    manifest.set_cover("resources/cover.png".into());
+
+    fobz.add_content(
+        "contents/part1.html".into(),
+        "Part 1".into(),
+        "<h1>Part 1</h1><p>One<br>two</p>".into(),
+    )
+    .unwrap();
+    fobz.add_child_content(
+        "contents/part1.html",
+        "contents/chapters/chapter1.html".into(),
+        "Chapter 1".into(),
+        "<h2>Chapter 1</h2><img src=\"../../resources/cover.png\" alt=\"Cover\"><h3 id=\"end\">End</h3>".into(),
+    )
+    .unwrap();
+    fobz.add_anchor(
+        Some("contents/chapters/chapter1.html"),
+        "contents/chapters/chapter1.html".into(),
+        "end".into(),
+        "The End".into(),
+    )
+    .unwrap();
+    fobz.add_content(
+        "contents/appendix.html".into(),
+        "Appendix".into(),
+        "<p>Appendix</p>".into(),
+    )
+    .unwrap();
+    fobz.add_resource("resources/cover.png".into(), "Cover".into(), PNG.to_vec())
+        .unwrap();
+    fobz.add_style("styles/main.css".into(), "p { margin: 0; }".into())
+        .unwrap();
+
+    fobz
+}
+
+fn read_entries(bytes: &[u8]) -> (Vec<String>, HashMap<String, Vec<u8>>) {
+    let mut archive = ZipArchive::new(Cursor::new(bytes)).unwrap();
+    let mut names = vec![];
+    let mut entries = HashMap::new();
+
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i).unwrap();
+        let mut data = vec![];
+        file.read_to_end(&mut data).unwrap();
+        names.push(file.name().to_string());
+        entries.insert(file.name().to_string(), data);
+    }
+
+    (names, entries)
+}
+
+fn parse(xml: &str) -> Result<roxmltree::Document<'_>, roxmltree::Error> {
+    let options = roxmltree::ParsingOptions {
+        allow_dtd: true,
+        ..Default::default()
+    };
+    roxmltree::Document::parse_with_options(xml, options)
+}
+
+fn text(entries: &HashMap<String, Vec<u8>>, name: &str) -> String {
+    String::from_utf8(entries[name].clone()).unwrap()
+}
+
+#[test]
+fn mimetype_is_first_and_stored() {
+    let bytes = sample()
+        .export_epub(Cursor::new(Vec::new()))
+        .unwrap()
+        .into_inner();
+
+    let mut archive = ZipArchive::new(Cursor::new(&bytes)).unwrap();
+    let mimetype = archive.by_index(0).unwrap();
+    assert_eq!(mimetype.name(), "mimetype");
+    assert_eq!(mimetype.compression(), CompressionMethod::Stored);
+
+    // The content must be readable at a fixed offset, right after the local header.
+    assert_eq!(&bytes[30..38], b"mimetype");
+    assert_eq!(&bytes[38..58], b"application/epub+zip");
+}
+
+#[test]
+fn package_is_structurally_valid() {
+    let bytes = sample()
+        .export_epub(Cursor::new(Vec::new()))
+        .unwrap()
+        .into_inner();
+    let (names, entries) = read_entries(&bytes);
+
+    // The container points to the package document.
+    let container = text(&entries, "META-INF/container.xml");
+    let container = parse(&container).unwrap();
+    let rootfile = container
+        .descendants()
+        .find(|v| v.has_tag_name("rootfile"))
+        .unwrap();
+    let opf_path = rootfile.attribute("full-path").unwrap();
+    let base = &opf_path[..opf_path.rfind('/').map_or(0, |v| v + 1)];
+
+    let opf = text(&entries, opf_path);
+    let opf = parse(&opf).unwrap();
+    let package = opf.root_element();
+    assert!(package.has_tag_name((OPF, "package")));
+    assert_eq!(package.attribute("version"), Some("3.0"));
+
+    // The unique identifier and the required metadata are present.
+    let unique = package.attribute("unique-identifier").unwrap();
+    let identifier = opf
+        .descendants()
+        .find(|v| v.attribute("id") == Some(unique))
+        .unwrap();
+    assert_eq!(identifier.tag_name().name(), "identifier");
+    assert_eq!(identifier.text(), Some("urn:isbn:978-3-16-148410-0"));
+
+    let metadata = |name: &str| {
+        opf.descendants()
+            .filter(|v| v.tag_name().name() == name)
+            .filter_map(|v| v.text())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(metadata("title"), ["Sample & Co"]);
+    assert_eq!(metadata("language"), ["en"]);
+    assert_eq!(metadata("creator"), ["John Doe"]);
+    assert_eq!(metadata("contributor"), ["Jane Roe"]);
+
+    let modified = opf
+        .descendants()
+        .find(|v| v.attribute("property") == Some("dcterms:modified"))
+        .unwrap();
+    assert_eq!(modified.text(), Some("2024-05-01T00:00:00Z"));
+
+    // Every item exists, and exactly one of them is the navigation document.
+    let items: HashMap<&str, roxmltree::Node> = opf
+        .descendants()
+        .filter(|v| v.has_tag_name((OPF, "item")))
+        .map(|v| (v.attribute("id").unwrap(), v))
+        .collect();
+    for item in items.values() {
+        let path = format!("{}{}", base, item.attribute("href").unwrap());
+        assert!(names.contains(&path), "missing item {}", path);
+        assert!(item.attribute("media-type").is_some());
+    }
+    let navs: Vec<_> = items
+        .values()
+        .filter(|v| v.attribute("properties") == Some("nav"))
+        .collect();
+    assert_eq!(navs.len(), 1);
+
+    let cover = items
+        .values()
+        .find(|v| v.attribute("properties") == Some("cover-image"))
+        .unwrap();
+    assert_eq!(cover.attribute("href"), Some("resources/cover.png"));
+    assert_eq!(cover.attribute("media-type"), Some("image/png"));
+
+    // The spine follows the table of contents.
+    let spine: Vec<&str> = opf
+        .descendants()
+        .filter(|v| v.has_tag_name((OPF, "itemref")))
+        .map(|v| {
+            items[v.attribute("idref").unwrap()]
+                .attribute("href")
+                .unwrap()
+        })
+        .collect();
+    assert_eq!(
+        spine,
+        [
+            "contents/part1.html",
+            "contents/chapters/chapter1.html",
+            "contents/appendix.html"
+        ]
+    );
+
+    // Every page is well-formed XHTML.
+    let mut ids = HashMap::new();
+    for item in items.values() {
+        if item.attribute("media-type") != Some("application/xhtml+xml") {
+            continue;
+        }
+        let href = item.attribute("href").unwrap();
+        let page = text(&entries, &format!("{}{}", base, href));
+        let page =
+            parse(&page).unwrap_or_else(|err| panic!("{} is not well-formed: {}", href, err));
+        assert!(page.root_element().has_tag_name((XHTML, "html")));
+        ids.insert(
+            href.to_string(),
+            page.descendants()
+                .filter_map(|v| v.attribute("id"))
+                .map(String::from)
+                .collect::<Vec<_>>(),
+        );
+    }
+
+    // Every link of the navigation document resolves, fragments included.
+    let nav_href = navs[0].attribute("href").unwrap();
+    let nav = text(&entries, &format!("{}{}", base, nav_href));
+    let nav = parse(&nav).unwrap();
+    let links: Vec<&str> = nav
+        .descendants()
+        .filter(|v| v.has_tag_name((XHTML, "a")))
+        .filter_map(|v| v.attribute("href"))
+        .collect();
+    assert!(links.contains(&"contents/chapters/chapter1.html#end"));
+    for link in links {
+        let (path, fragment) = link.split_once('#').unwrap_or((link, ""));
+        assert!(ids.contains_key(path), "dangling link {}", link);
+        assert!(fragment.is_empty() || ids[path].iter().any(|v| v == fragment));
+    }
+}
+
+#[test]
+fn pages_link_styles_relative_to_their_location() {
+    let bytes = sample()
+        .export_epub(Cursor::new(Vec::new()))
+        .unwrap()
+        .into_inner();
+    let (_, entries) = read_entries(&bytes);
+
+    let page = text(&entries, "OEBPS/contents/chapters/chapter1.html");
+    assert!(page.contains("href=\"../../styles/main.css\""));
+    assert!(page.contains("<title>Chapter 1</title>"));
+}
+
+#[test]
+fn export_is_reproducible() {
+    let first = sample()
+        .export_epub(Cursor::new(Vec::new()))
+        .unwrap()
+        .into_inner();
+    let second = sample()
+        .export_epub(Cursor::new(Vec::new()))
+        .unwrap()
+        .into_inner();
+
+    assert_eq!(first, second);
+}
+
+#[test]
+fn exported_publication_imports_back() {
+    let bytes = sample()
+        .export_epub(Cursor::new(Vec::new()))
+        .unwrap()
+        .into_inner();
+    let (fobz, report) = FobZ::import_epub(Cursor::new(bytes)).unwrap();
+
+    assert!(report.is_complete(), "{:?}", report.issues());
+
+    let manifest = fobz.get_manifest();
+    assert_eq!(manifest.get_title(), "Sample & Co");
+    assert_eq!(manifest.get_author(), "John Doe");
+    assert_eq!(
+        manifest.get_contributors(),
+        &[Contributor::new("Jane Roe".into(), Role::Translator)]
+    );
+    assert_eq!(manifest.get_language().unwrap(), "en");
+    assert_eq!(
+        manifest.get_identifiers(),
+        &[Identifier::Isbn("978-3-16-148410-0".into())]
+    );
+    assert_eq!(
+        manifest.get_series(),
+        Some(&Series::new("Samples".into(), 2))
+    );
+    assert_eq!(manifest.get_extra()["edition"], "second");
+    assert_eq!(manifest.get_cover(), "resources/cover.png");
+    assert_eq!(manifest.get_index(), "contents/part1.html");
+
+    let titles: Vec<(&str, &str)> = fobz
+        .get_toc()
+        .iter()
+        .map(|v| (v.path.as_str(), v.title.as_str()))
+        .collect();
+    assert_eq!(
+        titles,
+        [
+            ("contents/part1.html", "Part 1"),
+            ("contents/chapters/chapter1.html", "Chapter 1"),
+            ("contents/appendix.html", "Appendix")
+        ]
+    );
+
+    let (_, chapter) = fobz.get_content("contents/chapters/chapter1.html").unwrap();
+    assert!(chapter.contains("src=\"../../resources/cover.png\""));
+    assert!(fobz.get_style("styles/main.css").is_some());
+    assert!(fobz.validate().is_valid());
+}
+
+fn epub2() -> Vec<u8> {
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+
+    let files: &[(&str, &[u8])] = &[
+        ("mimetype", b"application/epub+zip"),
+        (
+            "META-INF/container.xml",
+            br#"<?xml version="1.0"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles><rootfile full-path="OPS/book.opf" media-type="application/oebps-package+xml"/></rootfiles>
+</container>"#,
+        ),
+        (
+            "OPS/book.opf",
+            br#"<?xml version="1.0"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="2.0" unique-identifier="id">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:opf="http://www.idpf.org/2007/opf">
+    <dc:title>Old Book</dc:title>
+    <dc:creator opf:role="aut">Ann Author</dc:creator>
+    <dc:creator opf:role="edt">Ed Editor</dc:creator>
+    <dc:identifier id="id" opf:scheme="ISBN">9780000000002</dc:identifier>
+    <dc:date opf:event="publication">1999</dc:date>
+    <dc:source>A manuscript</dc:source>
+    <meta name="cover" content="cover"/>
+    <meta name="calibre:series" content="Old Books"/>
+    <meta name="calibre:series_index" content="3.0"/>
+  </metadata>
+  <manifest>
+    <item id="ncx" href="toc.ncx" media-type="application/x-dtbncx+xml"/>
+    <item id="one" href="Text/one.htm" media-type="application/xhtml+xml"/>
+    <item id="two" href="Text/two.xhtml" media-type="application/xhtml+xml"/>
+    <item id="css" href="Styles/style.css" media-type="text/css"/>
+    <item id="cover" href="Images/cover.jpg" media-type="image/jpeg"/>
+    <item id="js" href="Misc/app.js" media-type="application/javascript"/>
+    <item id="gone" href="Text/gone.xhtml" media-type="application/xhtml+xml"/>
+  </manifest>
+  <spine toc="ncx"><itemref idref="two"/><itemref idref="one"/></spine>
+</package>"#,
+        ),
+        (
+            "OPS/toc.ncx",
+            br#"<?xml version="1.0"?>
+<!DOCTYPE ncx PUBLIC "-//NISO//DTD ncx 2005-1//EN" "http://www.daisy.org/z3986/2005/ncx-2005-1.dtd">
+<ncx xmlns="http://www.daisy.org/z3986/2005/ncx/" version="2005-1">
+  <navMap>
+    <navPoint id="p1"><navLabel><text>First</text></navLabel><content src="Text/two.xhtml"/></navPoint>
+    <navPoint id="p2"><navLabel><text>Second</text></navLabel><content src="Text/one.htm#top"/></navPoint>
+  </navMap>
+</ncx>"#,
+        ),
+        (
+            "OPS/Text/one.htm",
+            br#"<html><head><link href="../Styles/style.css" rel="stylesheet"/></head>
+<body><a href="two.xhtml#end">Next</a> <a href="gone.xhtml">Gone</a> <a href="https://example.com">Web</a></body></html>"#,
+        ),
+        (
+            "OPS/Text/two.xhtml",
+            br#"<html><head><title>Two</title></head><body><img src="../Images/cover.jpg"/><p id="end"/></body></html>"#,
+        ),
+        (
+            "OPS/Styles/style.css",
+            b"body { background: url('../Images/cover.jpg'); }",
+        ),
+        ("OPS/Images/cover.jpg", b"\xFF\xD8\xFF\xE0"),
+        ("OPS/Misc/app.js", b"alert(1);"),
+        ("OPS/stray.txt", b"stray"),
+    ];
+
+    let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    for (name, data) in files {
+        zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+        zip.write_all(data).unwrap();
+    }
+    zip.finish().unwrap().into_inner()
+}
+
+#[test]
+fn epub2_is_imported_with_a_report() {
+    let (fobz, report) = FobZ::import_epub(Cursor::new(epub2())).unwrap();
+
+    let manifest = fobz.get_manifest();
+    assert_eq!(manifest.get_title(), "Old Book");
+    assert_eq!(manifest.get_author(), "Ann Author");
+    assert_eq!(
+        manifest.get_contributors(),
+        &[Contributor::new("Ed Editor".into(), Role::Editor)]
+    );
+    assert_eq!(
+        manifest.get_identifiers(),
+        &[Identifier::Isbn("9780000000002".into())]
+    );
+    assert_eq!(manifest.get_published().unwrap(), "1999");
+    assert_eq!(
+        manifest.get_series(),
+        Some(&Series::new("Old Books".into(), 3))
+    );
+    assert_eq!(manifest.get_cover(), "resources/Images/cover.jpg");
+    assert_eq!(manifest.get_index(), "contents/Text/two.xhtml");
+
+    // Spine order, with titles from the NCX.
+    let titles: Vec<(&str, &str)> = fobz
+        .get_toc()
+        .iter()
+        .map(|v| (v.path.as_str(), v.title.as_str()))
+        .collect();
+    assert_eq!(
+        titles,
+        [
+            ("contents/Text/two.xhtml", "First"),
+            ("contents/Text/one.html", "Second")
+        ]
+    );
+
+    let (_, one) = fobz.get_content("contents/Text/one.html").unwrap();
+    assert!(one.contains("href=\"../../styles/Styles/style.css\""));
+    assert!(one.contains("href=\"two.xhtml#end\""));
+    assert!(one.contains("href=\"gone.xhtml\""));
+    assert!(one.contains("href=\"https://example.com\""));
+
+    let (_, style) = fobz.get_style("styles/Styles/style.css").unwrap();
+    assert!(style.contains("url('../../resources/Images/cover.jpg')"));
+
+    let (info, _) = fobz.get_resource("resources/Images/cover.jpg").unwrap();
+    assert_eq!(info.media_type, "image/jpeg");
+
+    let issues: Vec<(ImportIssueKind, &str)> = report
+        .issues()
+        .iter()
+        .map(|v| (v.kind, v.source.as_str()))
+        .collect();
+    assert_eq!(
+        issues,
+        [
+            (ImportIssueKind::MissingItem, "Text/gone.xhtml"),
+            (ImportIssueKind::UnmappedItem, "OPS/Misc/app.js"),
+            (ImportIssueKind::UnmappedItem, "OPS/stray.txt"),
+            (ImportIssueKind::UnmappedMetadata, "metadata"),
+            (ImportIssueKind::UnresolvedLink, "OPS/Text/one.htm"),
+        ]
+    );
+}
+
+#[test]
+fn derived_identifier_is_pinned() {
+    let fobz = FobZ::new(
+        "Title".into(),
+        "Author".into(),
+        "Description".into(),
+        vec![],
+    );
+    let bytes = fobz
+        .export_epub(Cursor::new(Vec::new()))
+        .unwrap()
+        .into_inner();
+    let (_, entries) = read_entries(&bytes);
+
+    let opf = text(&entries, "OEBPS/content.opf");
+    let opf = parse(&opf).unwrap();
+    let identifier = opf
+        .descendants()
+        .find(|v| v.attribute("id") == Some("pub-id"))
+        .unwrap();
+    assert_eq!(
+        identifier.text(),
+        Some("urn:uuid:86628a02-f01c-8a0d-a9f2-73d8bc5d3152")
+    );
+}
+
+#[test]
+fn imported_publications_are_checked_against_limits() {
+    let bytes = sample()
+        .export_epub(Cursor::new(Vec::new()))
+        .unwrap()
+        .into_inner();
+    assert!(FobZ::import_epub(Cursor::new(bytes.clone())).is_ok());
+
+    let limits = ReadLimits {
+        max_entries: 2,
+        ..Default::default()
+    };
+    assert!(matches!(
+        FobZ::import_epub_with(Cursor::new(bytes.clone()), &limits),
+        Err(FobzError::LimitExceeded {
+            limit: Limit::EntryCount,
+            max: 2,
+            ..
+        })
+    ));
+
+    let mut zip = ZipWriter::new_append(Cursor::new(bytes)).unwrap();
+    zip.start_file("OEBPS/../../evil.xhtml", SimpleFileOptions::default())
+        .unwrap();
+    zip.write_all(b"<p>Evil</p>").unwrap();
+    let bytes = zip.finish().unwrap().into_inner();
+    assert!(matches!(
+        FobZ::import_epub(Cursor::new(bytes)),
+        Err(FobzError::EntryPathTraversal(name)) if name == "OEBPS/../../evil.xhtml"
+    ));
+}
+
+#[test]
+fn relocated_links_are_percent_encoded() {
+    let files: &[(&str, &[u8])] = &[
+        ("mimetype", b"application/epub+zip"),
+        (
+            "META-INF/container.xml",
+            br#"<?xml version="1.0"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles><rootfile full-path="book.opf" media-type="application/oebps-package+xml"/></rootfiles>
+</container>"#,
+        ),
+        (
+            "book.opf",
+            br#"<?xml version="1.0"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0" unique-identifier="id">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+    <dc:title>Spaces</dc:title>
+    <dc:identifier id="id">spaces</dc:identifier>
+  </metadata>
+  <manifest>
+    <item id="one" href="one.xhtml" media-type="application/xhtml+xml"/>
+    <item id="pic" href="My%20Images/a%20b%25.jpg" media-type="image/jpeg"/>
+  </manifest>
+  <spine><itemref idref="one"/></spine>
+</package>"#,
+        ),
+        (
+            "one.xhtml",
+            br#"<html><body><img src="My%20Images/a%20b%25.jpg#x"/></body></html>"#,
+        ),
+        ("My Images/a b%.jpg", b"\xFF\xD8\xFF\xE0"),
+    ];
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    for (name, data) in files {
+        zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+        zip.write_all(data).unwrap();
+    }
+    let epub = zip.finish().unwrap().into_inner();
+
+    let (fobz, _) = FobZ::import_epub(Cursor::new(epub)).unwrap();
+    assert!(fobz
+        .get_resource_info("resources/My Images/a b%.jpg")
+        .is_some());
+    let (_, one) = fobz.get_content("contents/one.xhtml").unwrap();
+    assert!(one.contains("src=\"../resources/My%20Images/a%20b%25.jpg#x\""));
+}
+
+#[test]
+fn exported_paths_are_percent_encoded() {
+    let mut fobz = FobZ::new("Paths".into(), "".into(), "".into(), vec![]);
+    fobz.add_content(
+        "contents/a%20b.html".into(),
+        "Encoded".into(),
+        "<p>A</p>".into(),
+    )
+    .unwrap();
+    fobz.add_content(
+        "contents/a b.html".into(),
+        "Spaced".into(),
+        "<p id=\"end\">B</p>".into(),
+    )
+    .unwrap();
+    fobz.add_anchor(None, "contents/a b.html".into(), "end".into(), "End".into())
+        .unwrap();
+    fobz.add_resource("resources/c d.png".into(), "Cover".into(), PNG.to_vec())
+        .unwrap();
+    fobz.add_style("styles/my style.css".into(), "p {}".into())
+        .unwrap();
+    fobz.get_manifest_mut()
+        .set_cover("resources/c d.png".into());
+    fobz.get_manifest_mut()
+        .set_index("contents/a b.html".into());
+
+    let bytes = fobz
+        .export_epub(Cursor::new(Vec::new()))
+        .unwrap()
+        .into_inner();
+    let (names, entries) = read_entries(&bytes);
+    assert!(names.contains(&"OEBPS/contents/a%20b.html".to_string()));
+    assert!(names.contains(&"OEBPS/contents/a b.html".to_string()));
+
+    let opf = text(&entries, "OEBPS/content.opf");
+    let package = parse(&opf).unwrap();
+    let hrefs: Vec<&str> = package
+        .descendants()
+        .filter(|v| v.has_tag_name((OPF, "item")))
+        .filter_map(|v| v.attribute("href"))
+        .collect();
+    assert!(hrefs.contains(&"contents/a%2520b.html"));
+    assert!(hrefs.contains(&"contents/a%20b.html"));
+    assert!(hrefs.contains(&"resources/c%20d.png"));
+    assert!(hrefs.contains(&"styles/my%20style.css"));
+
+    let nav = text(&entries, "OEBPS/nav.xhtml");
+    assert!(nav.contains("href=\"contents/a%2520b.html\""));
+    assert!(nav.contains("href=\"contents/a%20b.html#end\""));
+    let page = text(&entries, "OEBPS/contents/a b.html");
+    assert!(page.contains("href=\"../styles/my%20style.css\""));
+
+    let (imported, report) = FobZ::import_epub(Cursor::new(bytes)).unwrap();
+    assert!(report.is_complete(), "{:?}", report.issues());
+    let titles: Vec<(&str, &str)> = imported
+        .get_toc()
+        .iter()
+        .map(|v| (v.path.as_str(), v.title.as_str()))
+        .collect();
+    assert_eq!(
+        titles,
+        [
+            ("contents/a%20b.html", "Encoded"),
+            ("contents/a b.html", "Spaced")
+        ]
+    );
+    assert_eq!(imported.get_manifest().get_cover(), "resources/c d.png");
+    assert!(imported.get_style("styles/my style.css").is_some());
+    assert!(imported.validate().is_valid());
+}
+
+#[test]
+fn pages_are_well_formed_xhtml() {
+    let mut fobz = FobZ::new("Markup".into(), "".into(), "".into(), vec![]);
+    fobz.add_content(
+        "contents/fragment.html".into(),
+        "Fragment".into(),
+        "<p>A&nbsp;B &copy; &amp; C & D &unknown; 1 < 2</p>\
+         <input disabled type=checkbox checked><details open><summary>S</summary></details>\
+         <img src=a.png alt='say \"hi\"' ALT=x></img><br>"
+            .into(),
+    )
+    .unwrap();
+    fobz.add_content(
+        "contents/lowercase.html".into(),
+        "Lowercase".into(),
+        "<!-- a -- b ---><!doctype html>\n<html lang=en><head><title><T></title></head>\
+         <body><!-- c --><p>y</p><!---></body></html>"
+            .into(),
+    )
+    .unwrap();
+    fobz.add_content(
+        "contents/document.html".into(),
+        "Document".into(),
+        "<!DOCTYPE html>\n<html><head><title>T&eacute;</title></head>\
+         <body><p title=\"a&mdash;b\" hidden>x&hellip;</p></body></html>"
+            .into(),
+    )
+    .unwrap();
+
+    let bytes = fobz
+        .export_epub(Cursor::new(Vec::new()))
+        .unwrap()
+        .into_inner();
+    let (names, entries) = read_entries(&bytes);
+
+    let page = text(&entries, "OEBPS/contents/fragment.html");
+    let xml = parse(&page).unwrap_or_else(|err| panic!("{} is not well-formed: {}", page, err));
+    let body: String = xml
+        .descendants()
+        .find(|v| v.has_tag_name((XHTML, "p")))
+        .unwrap()
+        .descendants()
+        .filter(|v| v.is_text())
+        .filter_map(|v| v.text())
+        .collect();
+    assert_eq!(body, "A\u{a0}B © & C & D &unknown; 1 < 2");
+    let input = xml
+        .descendants()
+        .find(|v| v.has_tag_name((XHTML, "input")))
+        .unwrap();
+    assert_eq!(input.attribute("disabled"), Some("disabled"));
+    assert_eq!(input.attribute("type"), Some("checkbox"));
+    let img = xml
+        .descendants()
+        .find(|v| v.has_tag_name((XHTML, "img")))
+        .unwrap();
+    assert_eq!(img.attribute("alt"), Some("say \"hi\""));
+
+    let page = text(&entries, "OEBPS/contents/document.html");
+    let xml = parse(&page).unwrap_or_else(|err| panic!("{} is not well-formed: {}", page, err));
+    let p = xml
+        .descendants()
+        .find(|v| v.has_tag_name((XHTML, "p")))
+        .unwrap();
+    assert_eq!(p.attribute("title"), Some("a—b"));
+    assert_eq!(p.attribute("hidden"), Some("hidden"));
+    assert_eq!(p.text(), Some("x…"));
+
+    // Lowercase doctypes still mark complete documents, and comments are made valid XML.
+    let page = text(&entries, "OEBPS/contents/lowercase.html");
+    let xml = parse(&page).unwrap_or_else(|err| panic!("{} is not well-formed: {}", page, err));
+    assert!(xml.root_element().has_tag_name((XHTML, "html")));
+    assert_eq!(
+        xml.descendants()
+            .filter(|v| v.has_tag_name((XHTML, "html")))
+            .count(),
+        1
+    );
+    assert!(xml.descendants().any(|v| v.is_comment()));
+    let title = xml
+        .descendants()
+        .find(|v| v.has_tag_name((XHTML, "title")))
+        .unwrap();
+    assert_eq!(title.text(), Some("<T>"));
+
+    // The default cover is only embedded when the manifest points at it.
+    assert!(names.contains(&"OEBPS/default/no_cover.jpg".to_string()));
+    fobz.get_manifest_mut()
+        .set_cover("resources/cover.png".into());
+    fobz.add_resource("resources/cover.png".into(), "Cover".into(), PNG.to_vec())
+        .unwrap();
+    let bytes = fobz
+        .export_epub(Cursor::new(Vec::new()))
+        .unwrap()
+        .into_inner();
+    let (names, _) = read_entries(&bytes);
+    assert!(!names.iter().any(|v| v.starts_with("OEBPS/default/")));
+}
@@ -0,0 +1,92 @@
+use std::io::Cursor;
+
+use fobzip::{
+    compression::{Compression, CompressionPolicy},
+    options::{LoadMode, OpenOptions, SaveOptions},
+    FobZ,
+};
+
+const PNG: &[u8] = b"\x89PNG\r\n\x1A\n\0\0\0\0";
+
+fn sample() -> FobZ {
+    let mut fobz = FobZ::new(
+        "Sample".into(),
+        "John Doe".into(),
+        "A description.".into(),
+        vec![],
+    );
+    fobz.add_content(
+        "contents/ch1.html".into(),
+        "Chapter 1".into(),
+        "<p>Lazily read chapter.</p>".into(),
+    )
+    .unwrap();
+    fobz.add_resource("resources/cat.png".into(), "Cat".into(), PNG.to_vec())
+        .unwrap();
+    fobz.add_style("styles/main.css".into(), "p { color: red; }".into())
+        .unwrap();
+    fobz
+}
+
+fn open_lazily(bytes: Vec<u8>) -> FobZ {
+    FobZ::from_reader_with(
+        Cursor::new(bytes),
+        &OpenOptions {
+            mode: LoadMode::Lazy,
+            ..Default::default()
+        },
+    )
+    .unwrap()
+}
+
+#[test]
+fn lazily_opened_documents_read_and_save_every_payload() {
+    let bytes = sample().to_bytes().unwrap();
+    let fobz = open_lazily(bytes.clone());
+
+    let (_, content) = fobz.try_get_content("contents/ch1.html").unwrap().unwrap();
+    assert_eq!(content, "<p>Lazily read chapter.</p>");
+    let (_, resource) = fobz.try_get_resource("resources/cat.png").unwrap().unwrap();
+    assert_eq!(resource, PNG);
+    let (_, style) = fobz.try_get_style("styles/main.css").unwrap().unwrap();
+    assert_eq!(style, "p { color: red; }");
+    assert!(fobz
+        .try_get_content("contents/missing.html")
+        .unwrap()
+        .is_none());
+
+    let saved = fobz.to_bytes().unwrap();
+    assert_eq!(saved, bytes);
+
+    let reopened = FobZ::from_bytes(&saved).unwrap();
+    assert_eq!(
+        reopened.get_content("contents/ch1.html").unwrap().1,
+        "<p>Lazily read chapter.</p>"
+    );
+    assert_eq!(reopened.get_resource("resources/cat.png").unwrap().1, PNG);
+}
+
+#[test]
+fn lazy_read_errors_are_reported() {
+    let options = SaveOptions {
+        compression: CompressionPolicy::new(Compression::Stored),
+        ..Default::default()
+    };
+    let mut bytes = sample()
+        .write_to_with(Cursor::new(Vec::new()), &options)
+        .unwrap()
+        .into_inner();
+
+    // Corrupt the stored content, so that its checksum no longer matches.
+    let needle = b"Lazily read";
+    let offset = bytes
+        .windows(needle.len())
+        .position(|v| v == needle)
+        .unwrap();
+    bytes[offset] = b'l';
+
+    let fobz = open_lazily(bytes);
+    assert!(fobz.try_get_content("contents/ch1.html").is_err());
+    assert!(fobz.get_content("contents/ch1.html").is_none());
+    assert!(fobz.get_content_info("contents/ch1.html").is_some());
+}
@@ -0,0 +1,157 @@
+use std::io::{Cursor, Write};
+
+use fobzip::{
+    error::FobzError,
+    options::{Limit, OpenOptions, ReadLimits},
+    FobZ,
+};
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+fn archive_with(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let entries: [(&str, &[u8]); 4] = [
+        (
+            "manifest.json",
+            br#"{"version":"1.3","title":"","author":"","description":"","tags":[],"index":"default/no_section.html","cover":"default/no_cover.jpg"}"#,
+        ),
+        ("toc.json", br#"{"sections":[]}"#),
+        ("tor.json", br#"{"resources":[]}"#),
+        ("tos.json", br#"{"styles":[]}"#),
+    ];
+
+    for (name, bytes) in entries.iter().chain(files) {
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        zip.start_file(*name, options).unwrap();
+        zip.write_all(bytes).unwrap();
+    }
+
+    zip.finish().unwrap().into_inner()
+}
+
+fn open_with(bytes: Vec<u8>, limits: ReadLimits) -> Result<FobZ, FobzError> {
+    FobZ::from_reader_with(
+        Cursor::new(bytes),
+        &OpenOptions {
+            limits,
+            ..Default::default()
+        },
+    )
+}
+
+#[test]
+fn unsafe_entry_names_are_refused() {
+    let bytes = archive_with(&[("resources/../../etc/x", b"x")]);
+    assert!(matches!(
+        FobZ::from_bytes(&bytes),
+        Err(FobzError::EntryPathTraversal(name)) if name == "resources/../../etc/x"
+    ));
+
+    let bytes = archive_with(&[("/etc/x", b"x")]);
+    assert!(matches!(
+        FobZ::from_bytes(&bytes),
+        Err(FobzError::AbsoluteEntryPath(_))
+    ));
+    let bytes = archive_with(&[("C:/x", b"x")]);
+    assert!(matches!(
+        FobZ::from_bytes(&bytes),
+        Err(FobzError::AbsoluteEntryPath(_))
+    ));
+
+    let bytes = archive_with(&[("resources\\x.png", b"x")]);
+    assert!(matches!(
+        FobZ::from_bytes(&bytes),
+        Err(FobzError::BackslashInEntryPath(_))
+    ));
+
+    // Names merely containing dots are fine.
+    let bytes = archive_with(&[("resources/..x/a..b", b"x")]);
+    assert!(FobZ::from_bytes(&bytes).is_ok());
+}
+
+#[test]
+fn duplicate_entries_are_refused() {
+    let mut bytes = archive_with(&[("resources/a.png", b"first"), ("resources/b.png", b"other")]);
+
+    // Writers refuse duplicates, so rename the second entry in place.
+    let needle = b"resources/b.png";
+    let mut i = 0;
+    while let Some(offset) = bytes[i..].windows(needle.len()).position(|v| v == needle) {
+        bytes[i + offset..i + offset + needle.len()].copy_from_slice(b"resources/a.png");
+        i += offset + needle.len();
+    }
+
+    assert!(matches!(
+        FobZ::from_bytes(&bytes),
+        Err(FobzError::DuplicateEntry(name)) if name == "resources/a.png"
+    ));
+    assert!(FobZ::validate_reader(Cursor::new(bytes)).is_err());
+}
+
+#[test]
+fn archives_exceeding_limits_are_refused() {
+    let bytes = archive_with(&[("resources/a.bin", &[1; 1000]), ("styles/a.css", b"p {}")]);
+    assert!(FobZ::from_bytes(&bytes).is_ok());
+
+    let limits = ReadLimits {
+        max_entries: 5,
+        ..Default::default()
+    };
+    assert!(matches!(
+        open_with(bytes.clone(), limits),
+        Err(FobzError::LimitExceeded {
+            limit: Limit::EntryCount,
+            entry: None,
+            value: 6,
+            max: 5,
+        })
+    ));
+
+    let limits = ReadLimits {
+        max_entry_size: 999,
+        ..Default::default()
+    };
+    assert!(matches!(
+        open_with(bytes.clone(), limits),
+        Err(FobzError::LimitExceeded {
+            limit: Limit::EntrySize,
+            entry: Some(name),
+            value: 1000,
+            ..
+        }) if name == "resources/a.bin"
+    ));
+
+    let limits = ReadLimits {
+        max_total_size: 1000,
+        ..Default::default()
+    };
+    assert!(matches!(
+        open_with(bytes, limits),
+        Err(FobzError::LimitExceeded {
+            limit: Limit::TotalSize,
+            entry: None,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn highly_compressed_entries_are_refused() {
+    let zeros = vec![0; 4 << 20];
+    let bytes = archive_with(&[("resources/bomb.bin", &zeros)]);
+
+    let err = FobZ::from_bytes(&bytes).unwrap_err();
+    assert!(matches!(
+        err,
+        FobzError::LimitExceeded {
+            limit: Limit::CompressionRatio,
+            ..
+        }
+    ));
+    assert!(err.to_string().contains("'resources/bomb.bin'"));
+
+    let limits = ReadLimits {
+        max_compression_ratio: u64::MAX,
+        ..Default::default()
+    };
+    assert!(open_with(bytes, limits).is_ok());
+}
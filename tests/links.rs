@@ -0,0 +1,141 @@
+use fobzip::{links::LinkProblem, FobZ};
+
+fn sample() -> FobZ {
+    let mut fobz = FobZ::new(
+        "Sample".into(),
+        "John Doe".into(),
+        "A description.".into(),
+        vec![],
+    );
+    fobz.add_content(
+        "contents/one.html".into(),
+        "One".into(),
+        "<link rel=\"stylesheet\" href=\"../styles/main.css\">\n\
+         <h1 id=\"top\">One</h1>\n\
+         <p><a href=\"two.html#end\">Next</a> <a href=\"two.html#nowhere\">Lost</a>\n\
+         <a href=\"#top\">Top</a> <a href=\"#gone\">Gone</a>\n\
+         <img src=\"../resources/missing.png\"> <a href=\"../../outside.html\">Out</a>\n\
+         <a href=\"https://example.com/?a=1&amp;b=2\">Site</a> <a href=\"mailto:john@example.com\">Mail</a>\n\
+         <img src=\"data:image/png;base64,AAAA\"></p>\n"
+            .into(),
+    )
+    .unwrap();
+    fobz.add_content(
+        "contents/two.html".into(),
+        "Two".into(),
+        "<p><a name=\"end\">End</a> <a href=\"one.html#top\">Back</a></p>\n".into(),
+    )
+    .unwrap();
+    fobz.add_style(
+        "styles/main.css".into(),
+        "body {\n  background: url(\"../resources/paper.jpg\");\n}\n\
+         @import url(https://fonts.example.com/font.css);\n"
+            .into(),
+    )
+    .unwrap();
+    fobz
+}
+
+#[test]
+fn broken_references_are_located() {
+    let report = sample().check_links().unwrap();
+    assert!(!report.is_ok());
+
+    let broken: Vec<(&str, usize, &str, LinkProblem)> = report
+        .broken()
+        .iter()
+        .map(|v| (v.source.as_str(), v.line, v.reference.as_str(), v.problem))
+        .collect();
+    assert_eq!(
+        broken,
+        [
+            (
+                "contents/one.html",
+                3,
+                "two.html#nowhere",
+                LinkProblem::MissingFragment
+            ),
+            (
+                "contents/one.html",
+                4,
+                "#gone",
+                LinkProblem::MissingFragment
+            ),
+            (
+                "contents/one.html",
+                5,
+                "../resources/missing.png",
+                LinkProblem::MissingTarget
+            ),
+            (
+                "contents/one.html",
+                5,
+                "../../outside.html",
+                LinkProblem::OutsideArchive
+            ),
+            (
+                "styles/main.css",
+                2,
+                "../resources/paper.jpg",
+                LinkProblem::MissingTarget
+            ),
+        ]
+    );
+    assert_eq!(
+        report.broken()[2].target.as_deref(),
+        Some("resources/missing.png")
+    );
+    assert_eq!(
+        report.broken()[0].to_string(),
+        "contents/one.html:3: broken reference 'two.html#nowhere': no element with this id"
+    );
+}
+
+#[test]
+fn external_links_are_listed() {
+    let report = sample().check_links().unwrap();
+
+    let external: Vec<(&str, usize, &str)> = report
+        .external()
+        .iter()
+        .map(|v| (v.source.as_str(), v.line, v.url.as_str()))
+        .collect();
+    assert_eq!(
+        external,
+        [
+            ("contents/one.html", 6, "https://example.com/?a=1&b=2"),
+            ("contents/one.html", 6, "mailto:john@example.com"),
+            ("styles/main.css", 4, "https://fonts.example.com/font.css"),
+        ]
+    );
+}
+
+#[test]
+fn non_ascii_text_is_scanned() {
+    let mut fobz = FobZ::new("".into(), "".into(), "".into(), vec![]);
+    fobz.add_content(
+        "contents/café.html".into(),
+        "Café".into(),
+        "<p style=\"content: 'é'; background: url(../resources/crème.png)\">Café</p>\n".into(),
+    )
+    .unwrap();
+    fobz.add_style(
+        "styles/main.css".into(),
+        "p::after { content: \"café\"; }\nbody { background: URL(../resources/thé.png); }\n".into(),
+    )
+    .unwrap();
+
+    let report = fobz.check_links().unwrap();
+    let broken: Vec<(&str, usize, &str)> = report
+        .broken()
+        .iter()
+        .map(|v| (v.source.as_str(), v.line, v.reference.as_str()))
+        .collect();
+    assert_eq!(
+        broken,
+        [
+            ("contents/café.html", 1, "../resources/crème.png"),
+            ("styles/main.css", 2, "../resources/thé.png"),
+        ]
+    );
+}
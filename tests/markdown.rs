@@ -0,0 +1,216 @@
+use std::{fs, path::PathBuf};
+
+use fobzip::{
+    import::ImportIssueKind,
+    manifest::{Contributor, Role, Series},
+    FobZ,
+};
+
+const PNG: &[u8] = b"\x89PNG\r\n\x1A\n\0\0\0\0";
+
+/// Writes a small Markdown project into a fresh directory of the system's temporary directory.
+fn project(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("fobzip-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("chapters")).unwrap();
+    fs::create_dir_all(dir.join("images")).unwrap();
+
+    fs::write(
+        dir.join("01-intro.md"),
+        "---\n\
+         title: \"The Book\"\n\
+         author: Jane Doe\n\
+         tags: [fiction, sample]\n\
+         language: en\n\
+         series: Samples\n\
+         series_position: 2\n\
+         translators:\n  - John Roe\n\
+         cover: images/cover.png\n\
+         edition: second\n\
+         ---\n\
+         # Introduction\n\n\
+         ![A diagram](images/diagram.png)\n\n\
+         Read [the next chapter](chapters/02-next.md#top) or [this](missing.md).\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.join("chapters/02-next.md"),
+        "---\ntitle: ignored\n---\n\
+         Some text before the heading.\n\n\
+         ## Next *steps* {#top}\n\n\
+         ![Same diagram](../images/diagram.png)\n\n\
+         Back to the [introduction](../01-intro.md).\n",
+    )
+    .unwrap();
+    fs::write(dir.join("images/diagram.png"), PNG).unwrap();
+    fs::write(dir.join("images/cover.png"), PNG).unwrap();
+    fs::write(dir.join("notes.txt"), "not Markdown").unwrap();
+
+    dir
+}
+
+#[test]
+fn directory_is_imported() {
+    let dir = project("dir");
+    let (fobz, report) = FobZ::import_markdown_dir(dir.to_str().unwrap()).unwrap();
+
+    let manifest = fobz.get_manifest();
+    assert_eq!(manifest.get_title(), "The Book");
+    assert_eq!(manifest.get_author(), "Jane Doe");
+    assert_eq!(manifest.get_tags(), &["fiction", "sample"]);
+    assert_eq!(manifest.get_language().unwrap(), "en");
+    assert_eq!(
+        manifest.get_series(),
+        Some(&Series::new("Samples".into(), 2))
+    );
+    assert_eq!(
+        manifest.get_contributors(),
+        &[Contributor::new("John Roe".into(), Role::Translator)]
+    );
+    assert_eq!(manifest.get_extra()["edition"], "second");
+    assert_eq!(manifest.get_cover(), "resources/images/cover.png");
+    assert_eq!(manifest.get_index(), "contents/01-intro.html");
+
+    let titles: Vec<(&str, &str)> = fobz
+        .get_toc()
+        .iter()
+        .map(|v| (v.path.as_str(), v.title.as_str()))
+        .collect();
+    assert_eq!(
+        titles,
+        [
+            ("contents/01-intro.html", "Introduction"),
+            ("contents/chapters/02-next.html", "Next steps")
+        ]
+    );
+
+    let (_, intro) = fobz.get_content("contents/01-intro.html").unwrap();
+    assert!(intro.contains("<h1>Introduction</h1>"));
+    assert!(intro.contains("src=\"../resources/images/diagram.png\""));
+    assert!(intro.contains("href=\"chapters/02-next.html#top\""));
+    assert!(intro.contains("href=\"missing.md\""));
+
+    let (_, next) = fobz.get_content("contents/chapters/02-next.html").unwrap();
+    assert!(next.contains("id=\"top\""));
+    assert!(next.contains("src=\"../../resources/images/diagram.png\""));
+    assert!(next.contains("href=\"../01-intro.html\""));
+
+    // The diagram is copied once, named after the first alternative text referencing it.
+    let (info, bytes) = fobz.get_resource("resources/images/diagram.png").unwrap();
+    assert_eq!(info.name, "A diagram");
+    assert_eq!(info.media_type, "image/png");
+    assert_eq!(bytes, PNG);
+
+    let issues: Vec<ImportIssueKind> = report.issues().iter().map(|v| v.kind).collect();
+    assert_eq!(
+        issues,
+        [
+            ImportIssueKind::UnresolvedLink,
+            ImportIssueKind::UnmappedMetadata
+        ]
+    );
+    assert!(fobz.validate().is_valid());
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn files_are_imported_in_the_given_order() {
+    let dir = project("files");
+    let paths = [
+        dir.join("chapters/02-next.md")
+            .to_string_lossy()
+            .to_string(),
+        dir.join("01-intro.md").to_string_lossy().to_string(),
+    ];
+    let (fobz, _) = FobZ::import_markdown(&paths).unwrap();
+
+    let paths: Vec<&str> = fobz.get_toc().iter().map(|v| v.path.as_str()).collect();
+    assert_eq!(
+        paths,
+        ["contents/chapters/02-next.html", "contents/01-intro.html"]
+    );
+
+    // Only the first file's front matter fills the manifest.
+    assert_eq!(fobz.get_manifest().get_title(), "ignored");
+    assert_eq!(
+        fobz.get_manifest().get_index(),
+        "contents/chapters/02-next.html"
+    );
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn only_media_inside_the_directory_is_copied() {
+    let root = std::env::temp_dir().join(format!("fobzip-media-{}", std::process::id()));
+    let dir = root.join("book");
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(&dir).unwrap();
+    let secret = root.join("secret.png");
+    fs::write(&secret, PNG).unwrap();
+    fs::write(dir.join("notes.txt"), "Notes").unwrap();
+    fs::write(dir.join("photo.png"), PNG).unwrap();
+    fs::write(
+        dir.join("book.md"),
+        format!(
+            "# Book\n\n\
+             ![Photo](photo.png) [Notes](notes.txt)\n\n\
+             ![Secret](../secret.png) ![Absolute]({})\n",
+            secret.to_string_lossy()
+        ),
+    )
+    .unwrap();
+
+    let (fobz, report) = FobZ::import_markdown_dir(dir.to_str().unwrap()).unwrap();
+
+    let resources: Vec<&str> = fobz.get_tor().iter().map(|v| v.path.as_str()).collect();
+    assert_eq!(resources, ["resources/photo.png"]);
+
+    let (_, book) = fobz.get_content("contents/book.html").unwrap();
+    assert!(book.contains("src=\"../resources/photo.png\""));
+    assert!(book.contains("href=\"notes.txt\""));
+    assert!(book.contains("src=\"../secret.png\""));
+    assert!(book.contains(&format!("src=\"{}\"", secret.to_string_lossy())));
+
+    let issues: Vec<(ImportIssueKind, bool)> = report
+        .issues()
+        .iter()
+        .map(|v| (v.kind, v.message.contains("outside of")))
+        .collect();
+    assert_eq!(
+        issues,
+        [
+            (ImportIssueKind::UnresolvedLink, false),
+            (ImportIssueKind::UnresolvedLink, true),
+            (ImportIssueKind::UnresolvedLink, true),
+        ]
+    );
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn relocated_links_are_percent_encoded() {
+    let dir = std::env::temp_dir().join(format!("fobzip-encoded-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("my images")).unwrap();
+    fs::write(dir.join("my images/a b%.png"), PNG).unwrap();
+    fs::write(
+        dir.join("01 one.md"),
+        "# One\n\n![Photo](my%20images/a%20b%25.png) [Two](02%20two.md#end)\n",
+    )
+    .unwrap();
+    fs::write(dir.join("02 two.md"), "# Two {#end}\n").unwrap();
+
+    let (fobz, _) = FobZ::import_markdown_dir(dir.to_str().unwrap()).unwrap();
+
+    let resources: Vec<&str> = fobz.get_tor().iter().map(|v| v.path.as_str()).collect();
+    assert_eq!(resources, ["resources/my images/a b%.png"]);
+
+    let (_, one) = fobz.get_content("contents/01 one.html").unwrap();
+    assert!(one.contains("src=\"../resources/my%20images/a%20b%25.png\""));
+    assert!(one.contains("href=\"02%20two.html#end\""));
+
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,71 @@
+use std::io::{Cursor, Write};
+
+use fobzip::{media, sanitize::SanitizePolicy, FobZ};
+use zip::{write::SimpleFileOptions, ZipWriter};
+
+#[test]
+fn known_extensions_are_trusted_over_the_content() {
+    assert_eq!(
+        media::detect("resources/a.txt", b"BM, not a bitmap"),
+        "text/plain"
+    );
+    assert_eq!(
+        media::detect("resources/a.json", b"{\"icon\": \"<svg></svg>\"}"),
+        "application/json"
+    );
+    assert_eq!(
+        media::detect("resources/a.png", b"\xFF\xD8\xFF\xE0"),
+        "image/png"
+    );
+}
+
+#[test]
+fn unknown_extensions_fall_back_to_strong_signatures() {
+    assert_eq!(media::detect("resources/blob", b"GIF89a...."), "image/gif");
+    assert_eq!(
+        media::detect("resources/blob", b"\xEF\xBB\xBF<?xml version=\"1.0\"?>\n<!-- logo -->\n<svg xmlns=\"http://www.w3.org/2000/svg\"/>"),
+        "image/svg+xml"
+    );
+
+    // Short or misplaced signatures say nothing about the content.
+    assert_eq!(
+        media::detect("resources/blob", b"BM text"),
+        media::OCTET_STREAM
+    );
+    assert_eq!(
+        media::detect("resources/blob", b"\xFF\xFB\x90\x00"),
+        media::OCTET_STREAM
+    );
+    assert_eq!(
+        media::detect("resources/blob", b"<p>An <svg> element.</p>"),
+        media::OCTET_STREAM
+    );
+    assert_eq!(
+        media::detect(
+            "resources/blob",
+            b"<?xml version=\"1.0\"?><html><svg/></html>"
+        ),
+        media::OCTET_STREAM
+    );
+}
+
+#[test]
+fn xhtml_files_outside_contents_are_resources() {
+    let mut fobz = FobZ::new("".into(), "".into(), "".into(), vec![]);
+    fobz.add_content("contents/a.xhtml".into(), "A".into(), "<p>A</p>".into())
+        .unwrap();
+    let mut bytes = fobz.to_bytes().unwrap();
+
+    // Append an unlisted resource to the archive.
+    let mut zip = ZipWriter::new_append(Cursor::new(&mut bytes)).unwrap();
+    zip.start_file("resources/x.xhtml", SimpleFileOptions::default())
+        .unwrap();
+    zip.write_all(b"<p>X</p><script></script>").unwrap();
+    zip.finish().unwrap();
+
+    // Only contents are sanitized, so the resource is left as it is.
+    let mut fobz = FobZ::from_bytes(&bytes).unwrap();
+    assert!(fobz.get_content("contents/a.xhtml").is_some());
+    assert!(fobz.sanitize(&SanitizePolicy::Strict).unwrap().is_clean());
+    assert!(fobz.get_content("resources/x.xhtml").is_none());
+}
@@ -0,0 +1,112 @@
+use std::io::{Cursor, Write};
+
+use fobzip::{error::FobzError, migration::CURRENT_VERSION, FobZ};
+use zip::{write::SimpleFileOptions, ZipWriter};
+
+fn archive_with_version(version: &str) -> Vec<u8> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let entries = [
+        (
+            "manifest.json",
+            format!(
+                r#"{{"version":"{}","title":"","author":"","description":"","tags":[],"index":"default/no_section.html","cover":"default/no_cover.jpg"}}"#,
+                version
+            ),
+        ),
+        ("toc.json", r#"{"sections":[]}"#.into()),
+        ("tor.json", r#"{"resources":[]}"#.into()),
+        ("tos.json", r#"{"styles":[]}"#.into()),
+    ];
+
+    for (name, json) in entries {
+        zip.start_file(name, SimpleFileOptions::default()).unwrap();
+        zip.write_all(json.as_bytes()).unwrap();
+    }
+
+    zip.finish().unwrap().into_inner()
+}
+
+#[test]
+fn version_1_0_is_migrated() {
+    let fobz = FobZ::open("tests/fixtures/v1.0.fobz").unwrap();
+
+    assert_eq!(fobz.get_manifest().get_version(), CURRENT_VERSION);
+
+    let (cover, _) = fobz.get_resource("resources/cover.png").unwrap();
+    assert_eq!(cover.media_type, "image/png");
+
+    // Without a known extension the media type is detected from the content.
+    let (blob, _) = fobz.get_resource("resources/blob").unwrap();
+    assert_eq!(blob.media_type, "image/gif");
+
+    assert!(fobz.validate().is_valid());
+}
+
+#[test]
+fn version_1_1_is_migrated() {
+    let fobz = FobZ::open("tests/fixtures/v1.1.fobz").unwrap();
+
+    assert_eq!(fobz.get_manifest().get_version(), CURRENT_VERSION);
+
+    let hrefs: Vec<String> = fobz.get_toc().iter().map(|v| v.href()).collect();
+    assert_eq!(
+        hrefs,
+        [
+            "contents/chapter1.html",
+            "contents/chapter1.html#part2",
+            "contents/chapter2.html"
+        ]
+    );
+
+    let (part, content) = fobz.get_content("contents/chapter1.html#part2").unwrap();
+    assert_eq!(part.anchor.as_deref(), Some("part2"));
+    assert!(content.contains("id=\"part2\""));
+
+    assert!(fobz.validate().is_valid());
+}
+
+#[test]
+fn version_1_2_is_migrated() {
+    let fobz = FobZ::open("tests/fixtures/v1.2.fobz").unwrap();
+
+    let manifest = fobz.get_manifest();
+    assert_eq!(manifest.get_version(), CURRENT_VERSION);
+    assert!(manifest.get_contributors().is_empty());
+    assert!(manifest.get_identifiers().is_empty());
+    assert_eq!(manifest.get_language(), None);
+
+    let hrefs: Vec<(usize, String)> = fobz
+        .get_toc()
+        .depth_first()
+        .map(|(depth, info)| (depth, info.href()))
+        .collect();
+    assert_eq!(
+        hrefs,
+        [
+            (0, "contents/chapter1.html".into()),
+            (1, "contents/chapter1.html#part2".into()),
+            (1, "contents/chapter1/notes.html".into()),
+            (0, "contents/chapter2.html".into()),
+        ]
+    );
+
+    assert!(fobz.validate().is_valid());
+}
+
+#[test]
+fn newer_minor_version_is_read_but_not_written_back() {
+    let fobz = FobZ::from_bytes(&archive_with_version("1.99")).unwrap();
+    assert_eq!(fobz.get_manifest().get_version(), "1.99");
+
+    let err = fobz.to_bytes().unwrap_err();
+    assert!(matches!(err, FobzError::UnsupportedVersion(v) if v == "1.99"));
+}
+
+#[test]
+fn unknown_major_version_is_refused() {
+    let err = FobZ::from_bytes(&archive_with_version("2.0")).unwrap_err();
+    assert!(matches!(err, FobzError::UnsupportedVersion(v) if v == "2.0"));
+
+    let err = FobZ::from_bytes(&archive_with_version("0.3")).unwrap_err();
+    assert!(matches!(err, FobzError::UnsupportedVersion(v) if v == "0.3"));
+}
@@ -0,0 +1,159 @@
+use fobzip::{
+    error::FobzError,
+    options::{LoadMode, OpenOptions},
+    FobZ,
+};
+
+const PNG: &[u8] = b"\x89PNG\r\n\x1A\n\0\0\0\0";
+
+fn sample() -> FobZ {
+    let mut fobz = FobZ::new(
+        "Sample".into(),
+        "John Doe".into(),
+        "A description.".into(),
+        vec![],
+    );
+    fobz.add_content(
+        "contents/ch1.html".into(),
+        "Chapter 1".into(),
+        "<link rel=\"stylesheet\" href=\"../styles/main.css\">\n\
+         <p id=\"top\"><img src=\"../resources/cat.png\"> <a href=\"ch2.html#end\">Next</a> \
+         <a href=\"#top\">Top</a> <a href=\"https://example.com/ch1.html\">Out</a></p>\n"
+            .into(),
+    )
+    .unwrap();
+    fobz.add_content(
+        "contents/ch2.html".into(),
+        "Chapter 2".into(),
+        "<p id=\"end\"><a href=\"ch1.html?x=1&amp;y=2#top\">Back</a> \
+         <span style=\"background: url(../resources/cat.png)\">Cat</span></p>\n"
+            .into(),
+    )
+    .unwrap();
+    fobz.add_anchor(None, "contents/ch1.html".into(), "top".into(), "Top".into())
+        .unwrap();
+    fobz.add_resource("resources/cat.png".into(), "Cat".into(), PNG.to_vec())
+        .unwrap();
+    fobz.add_style(
+        "styles/main.css".into(),
+        "@import \"base.css\";\nbody { background: url('../resources/cat.png'); }\n".into(),
+    )
+    .unwrap();
+    fobz.get_manifest_mut()
+        .set_index("contents/ch1.html".into());
+    fobz.get_manifest_mut()
+        .set_cover("resources/cat.png".into());
+    fobz
+}
+
+#[test]
+fn moving_a_content_rewrites_references() {
+    let mut fobz = sample();
+    fobz.rename("contents/ch1.html", "contents/part 1/ch1.html".into())
+        .unwrap();
+
+    let (info, content) = fobz.get_content("contents/part 1/ch1.html").unwrap();
+    assert_eq!(info.title, "Chapter 1");
+    assert!(content.contains("href=\"../../styles/main.css\""));
+    assert!(content.contains("src=\"../../resources/cat.png\""));
+    assert!(content.contains("href=\"../ch2.html#end\""));
+    assert!(content.contains("href=\"#top\""));
+    assert!(content.contains("href=\"https://example.com/ch1.html\""));
+
+    let (_, content) = fobz.get_content("contents/ch2.html").unwrap();
+    assert!(content.contains("href=\"part%201/ch1.html?x=1&amp;y=2#top\""));
+    assert!(content.contains("url(../resources/cat.png)"));
+
+    assert!(fobz.get_content("contents/ch1.html").is_none());
+    let paths: Vec<String> = fobz.get_toc().iter().map(|v| v.href()).collect();
+    assert_eq!(
+        paths,
+        [
+            "contents/part 1/ch1.html",
+            "contents/ch2.html",
+            "contents/part 1/ch1.html#top"
+        ]
+    );
+    assert_eq!(fobz.get_manifest().get_index(), "contents/part 1/ch1.html");
+    assert!(fobz.validate().is_valid());
+}
+
+#[test]
+fn moving_resources_and_styles_rewrites_references() {
+    let mut fobz = sample();
+    fobz.rename("resources/cat.png", "resources/images/cat.png".into())
+        .unwrap();
+    fobz.rename("styles/main.css", "styles/theme/main.css".into())
+        .unwrap();
+
+    assert_eq!(
+        fobz.get_resource("resources/images/cat.png").unwrap().1,
+        PNG
+    );
+    assert_eq!(fobz.get_manifest().get_cover(), "resources/images/cat.png");
+
+    let (_, content) = fobz.get_content("contents/ch1.html").unwrap();
+    assert!(content.contains("href=\"../styles/theme/main.css\""));
+    assert!(content.contains("src=\"../resources/images/cat.png\""));
+    let (_, content) = fobz.get_content("contents/ch2.html").unwrap();
+    assert!(content.contains("url(../resources/images/cat.png)"));
+
+    let (_, style) = fobz.get_style("styles/theme/main.css").unwrap();
+    assert_eq!(
+        style,
+        "@import \"../base.css\";\nbody { background: url('../../resources/images/cat.png'); }\n"
+    );
+}
+
+#[test]
+fn invalid_renames_are_refused() {
+    let mut fobz = sample();
+
+    assert!(matches!(
+        fobz.rename("contents/missing.html", "contents/other.html".into()),
+        Err(FobzError::UnknownPath(_))
+    ));
+    assert!(matches!(
+        fobz.rename("contents/ch1.html", "contents/ch2.html".into()),
+        Err(FobzError::DuplicatePath(_))
+    ));
+    assert!(matches!(
+        fobz.rename("contents/ch1.html", "resources/ch1.html".into()),
+        Err(FobzError::InvalidPath(_))
+    ));
+    assert!(matches!(
+        fobz.rename("styles/main.css", "styles/main.txt".into()),
+        Err(FobzError::InvalidPath(_))
+    ));
+    assert!(matches!(
+        fobz.rename("contents/ch1.html", "contents/ch#1.html".into()),
+        Err(FobzError::InvalidPath(_))
+    ));
+    assert!(matches!(
+        fobz.rename("default/no_cover.jpg", "resources/cover.jpg".into()),
+        Err(FobzError::InvalidPath(_))
+    ));
+    assert!(fobz.get_content("contents/ch1.html").is_some());
+}
+
+#[test]
+fn lazily_loaded_entries_are_moved() {
+    let bytes = sample().to_bytes().unwrap();
+    let mut fobz = FobZ::from_reader_with(
+        std::io::Cursor::new(bytes),
+        &OpenOptions {
+            mode: LoadMode::Lazy,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    fobz.rename("resources/cat.png", "resources/kitten.png".into())
+        .unwrap();
+    let reopened = FobZ::from_bytes(&fobz.to_bytes().unwrap()).unwrap();
+    assert_eq!(
+        reopened.get_resource("resources/kitten.png").unwrap().1,
+        PNG
+    );
+    assert!(reopened.validate().is_valid());
+}
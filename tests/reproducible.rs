@@ -0,0 +1,48 @@
+use fobzip::FobZ;
+
+fn sample() -> FobZ {
+    let mut fobz = FobZ::new(
+        "Title".into(),
+        "Author".into(),
+        "Description".into(),
+        vec!["tag".into()],
+    );
+
+    for i in 0..16 {
+        fobz.add_content(
+            format!("contents/{}.html", i),
+            format!("Chapter {}", i),
+            format!("<p>{}</p>", i),
+        )
+        .unwrap();
+        fobz.add_resource(
+            format!("resources/{}.png", i),
+            i.to_string(),
+            vec![i as u8; 64],
+        )
+        .unwrap();
+        fobz.add_style(
+            format!("styles/{}.css", i),
+            format!("p {{ margin: {}px; }}", i),
+        )
+        .unwrap();
+    }
+
+    fobz
+}
+
+#[test]
+fn equal_documents_save_to_identical_bytes() {
+    let first = sample().to_bytes().unwrap();
+    let second = sample().to_bytes().unwrap();
+
+    assert_eq!(first, second);
+}
+
+#[test]
+fn reopened_document_saves_to_identical_bytes() {
+    let first = sample().to_bytes().unwrap();
+    let reopened = FobZ::from_bytes(&first).unwrap().to_bytes().unwrap();
+
+    assert_eq!(first, reopened);
+}
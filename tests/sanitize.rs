@@ -0,0 +1,303 @@
+use fobzip::{
+    options::OpenOptions,
+    sanitize::{AllowList, RemovalKind, SanitizePolicy},
+    FobZ,
+};
+
+const UNSAFE: &str = "<!DOCTYPE html>\n<html><head><title>T</title>\
+    <meta http-equiv=\"refresh\" content=\"0; url=javascript:alert(1)\">\
+    <style>p { color: red; }</style></head>\n\
+    <body onload=\"steal()\"><p class='intro' style=\"color: blue\">Hello <font>old</font> \
+    <a href=\"java&#9;script:alert(1)\" title=\"x\">link</a> \
+    <a href='https://example.com/?a=1&amp;b=\"2\"'>ok</a></p>\
+    <script>document.write(\"<p>\")</script>\
+    <iframe src=\"https://example.com\"><p>nested</p></iframe>\
+    <img src=\"data:image/png;base64,AAAA\" alt=\"dot\"/></body></html>\n";
+
+fn kinds(report: &fobzip::sanitize::SanitizeReport) -> Vec<(RemovalKind, String, Option<String>)> {
+    report
+        .removals()
+        .iter()
+        .map(|v| (v.kind, v.element.clone(), v.attribute.clone()))
+        .collect()
+}
+
+#[test]
+fn strict_keeps_only_text_markup() {
+    let (html, report) = SanitizePolicy::Strict.sanitize("contents/a.html", UNSAFE);
+
+    assert_eq!(
+        html,
+        "<!DOCTYPE html>\n<html><head><title>T</title>\
+         <meta content=\"0; url=javascript:alert(1)\"></head>\n\
+         <body><p>Hello old \
+         <a title=\"x\">link</a> \
+         <a href=\"https://example.com/?a=1&amp;b=&quot;2&quot;\">ok</a></p>\
+         <img alt=\"dot\"/></body></html>\n"
+    );
+    assert_eq!(
+        kinds(&report),
+        [
+            (
+                RemovalKind::Attribute,
+                "meta".into(),
+                Some("http-equiv".into())
+            ),
+            (RemovalKind::Content, "style".into(), None),
+            (RemovalKind::Attribute, "body".into(), Some("onload".into())),
+            (RemovalKind::Attribute, "p".into(), Some("class".into())),
+            (RemovalKind::Attribute, "p".into(), Some("style".into())),
+            (RemovalKind::Element, "font".into(), None),
+            (RemovalKind::Url, "a".into(), Some("href".into())),
+            (RemovalKind::Content, "script".into(), None),
+            (RemovalKind::Content, "iframe".into(), None),
+            (RemovalKind::Url, "img".into(), Some("src".into())),
+        ]
+    );
+    assert_eq!(
+        report.removals()[0].to_string(),
+        "contents/a.html: removed attribute 'http-equiv' of <meta>"
+    );
+}
+
+#[test]
+fn policies_differ_in_what_they_keep() {
+    let (html, _) = SanitizePolicy::Standard.sanitize("contents/a.html", UNSAFE);
+    assert!(html.contains("<style>p { color: red; }</style>"));
+    assert!(html.contains("<p class=\"intro\" style=\"color: blue\">"));
+    assert!(!html.contains("<font>"));
+    assert!(!html.contains("<script"));
+
+    let (html, _) = SanitizePolicy::Permissive.sanitize("contents/a.html", UNSAFE);
+    assert!(html.contains("<font>old</font>"));
+    assert!(html.contains("<img src=\"data:image/png;base64,AAAA\" alt=\"dot\"/>"));
+    assert!(!html.contains("iframe") && !html.contains("nested"));
+    assert!(!html.contains("onload") && !html.contains("http-equiv"));
+
+    let custom = SanitizePolicy::Custom(AllowList {
+        elements: vec!["p".into(), "a".into(), "script".into()],
+        attributes: vec!["href".into(), "data-*".into()],
+        url_schemes: vec!["https".into()],
+    });
+    let (html, _) = custom.sanitize(
+        "contents/a.html",
+        "<div data-x=\"1\"><p data-y=\"2\" id=\"p\"><a href=\"http://a.com\">a</a> \
+         <a href=\"https://b.com\">b</a></p><script>x()</script></div>",
+    );
+    assert_eq!(
+        html,
+        "<p data-y=\"2\"><a>a</a> <a href=\"https://b.com\">b</a></p>"
+    );
+
+    // Kept tags are written back from what was parsed.
+    let clean = "<P class = 'a'>Unchanged <b>markup</B></p>";
+    let (html, report) = SanitizePolicy::Standard.sanitize("contents/a.html", clean);
+    assert_eq!(html, "<p class=\"a\">Unchanged <b>markup</b></p>");
+    assert!(report.is_clean());
+}
+
+#[test]
+fn policy_applies_on_open_add_and_demand() {
+    let mut fobz = FobZ::new(
+        "Sample".into(),
+        "John Doe".into(),
+        "A description.".into(),
+        vec![],
+    );
+    fobz.add_content("contents/a.html".into(), "A".into(), UNSAFE.into())
+        .unwrap();
+    let bytes = fobz.to_bytes().unwrap();
+
+    let mut opened = FobZ::from_reader_with(
+        std::io::Cursor::new(bytes.clone()),
+        &OpenOptions {
+            sanitize_policy: SanitizePolicy::Standard,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert!(!opened
+        .get_content("contents/a.html")
+        .unwrap()
+        .1
+        .contains("<script"));
+    let report = opened.take_sanitize_report();
+    assert_eq!(report.removals().len(), 7);
+    assert!(opened.take_sanitize_report().is_clean());
+
+    opened
+        .add_content(
+            "contents/b.html".into(),
+            "B".into(),
+            "<p onclick=\"x()\">B</p>".into(),
+        )
+        .unwrap();
+    assert_eq!(opened.get_content("contents/b.html").unwrap().1, "<p>B</p>");
+    assert_eq!(opened.take_sanitize_report().removals().len(), 1);
+
+    let mut fobz = FobZ::from_bytes(&bytes).unwrap();
+    assert!(fobz
+        .get_content("contents/a.html")
+        .unwrap()
+        .1
+        .contains("<script"));
+    let report = fobz.sanitize(&SanitizePolicy::Strict).unwrap();
+    assert_eq!(report.removals().len(), 10);
+    assert!(!fobz
+        .get_content("contents/a.html")
+        .unwrap()
+        .1
+        .contains("<script"));
+    assert!(fobz.sanitize(&SanitizePolicy::Strict).unwrap().is_clean());
+}
+
+#[test]
+fn markup_hidden_in_comments_and_declarations_is_found() {
+    let payloads = [
+        "<!--><script>alert(1)</script>-->",
+        "<!---><img src=x onerror=alert(1)>-->",
+        "<!-- a --!><script>alert(1)</script>-->",
+        "<? \"><script>alert(1)</script>\" ?>",
+        "<!DOCTYPE \"><img src=x onerror=alert(1)>\">",
+        "<![CDATA[ ><img src=x onerror=alert(1)> ]]>",
+        "</ <!-- ><script>alert(1)</script> -->",
+        "<p \"><script>alert(1)</script>\">",
+        "<p title=a\"><img src=x onerror=alert(1)>\">",
+    ];
+
+    for policy in [
+        SanitizePolicy::Strict,
+        SanitizePolicy::Standard,
+        SanitizePolicy::Permissive,
+    ] {
+        for payload in payloads {
+            let (html, report) = policy.sanitize("contents/a.html", payload);
+            assert!(!report.is_clean(), "{:?}: {}", policy, payload);
+            assert!(
+                !html.contains("<script") && !html.contains("onerror"),
+                "{:?}: {} became {}",
+                policy,
+                payload,
+                html
+            );
+        }
+    }
+}
+
+#[test]
+fn comments_are_removed_and_a_leading_doctype_is_kept() {
+    let (html, report) = SanitizePolicy::Permissive.sanitize(
+        "contents/a.html",
+        "<?xml version=\"1.0\"?>\n<!doctype HTML SYSTEM \"about:legacy-compat\">\n\
+         <p>A<!-- note -->B<![CDATA[C]]></p><!DOCTYPE html>",
+    );
+
+    assert_eq!(html, "\n<!DOCTYPE html>\n<p>AB</p>");
+    assert_eq!(
+        kinds(&report),
+        [
+            (RemovalKind::Comment, "#comment".into(), None),
+            (RemovalKind::Comment, "#comment".into(), None),
+            (RemovalKind::Comment, "#comment".into(), None),
+            (RemovalKind::Comment, "!doctype".into(), None),
+        ]
+    );
+    assert_eq!(
+        report.removals()[0].to_string(),
+        "contents/a.html: removed a comment or declaration"
+    );
+}
+
+#[test]
+fn markup_parsed_differently_by_browsers_is_removed() {
+    let payloads = [
+        "<noscript><p title=\"</noscript><img src=x onerror=alert(1)>\"></p></noscript>",
+        "<svg><style><img src=x onerror=alert(1)></style></svg>",
+        "<svg><animate attributeName=\"href\" values=\"javascript:alert(1)\"/></svg>",
+        "<xmp><img src=x onerror=alert(1)></xmp>",
+        "<math><mtext><img src=x onerror=alert(1)></mtext></math>",
+    ];
+
+    for payload in payloads {
+        let (html, report) = SanitizePolicy::Permissive.sanitize("contents/a.html", payload);
+        assert!(!report.is_clean(), "{}", payload);
+        assert!(
+            !html.contains("onerror") && !html.contains("javascript:"),
+            "{} became {}",
+            payload,
+            html
+        );
+    }
+
+    let (html, report) = SanitizePolicy::Permissive.sanitize(
+        "contents/a.html",
+        "<p><animate attributeName=\"href\" values=\"javascript:alert(1)\"/></p>",
+    );
+    assert_eq!(html, "<p></p>");
+    assert_eq!(
+        kinds(&report),
+        [(RemovalKind::Element, "animate".into(), None)]
+    );
+}
+
+#[test]
+fn self_closing_opaque_elements_still_hide_their_content() {
+    let (html, report) = SanitizePolicy::Strict.sanitize(
+        "contents/a.html",
+        "<p>x</p><script/>alert(1)</script><svg/><p>y</p>",
+    );
+
+    assert_eq!(html, "<p>x</p><p>y</p>");
+    assert_eq!(
+        kinds(&report),
+        [
+            (RemovalKind::Content, "script".into(), None),
+            (RemovalKind::Content, "svg".into(), None),
+        ]
+    );
+}
+
+#[test]
+fn markup_read_differently_by_browsers_cannot_hide_scripts() {
+    let payloads = [
+        // Character references browsers decode in URLs.
+        "<a href=\"javascript&colon;alert(1)\">x</a>",
+        "<a href=\"java&Tab;script:alert(1)\">x</a>",
+        "<a href=\"java&NewLine;script&#58alert(1)\">x</a>",
+        "<a href=\"javascript&#x3A;alert(1)\">x</a>",
+        // Raw text elements, whose text is not markup.
+        "<title><p title=\"</title><img src=x onerror=alert(1)>\"></p>",
+        "<title/><p title=\"</title><img src=x onerror=alert(1)>\"></p>",
+        "<textarea><p title=\"</textarea><img src=x onerror=alert(1)>\"></p>",
+        // Attribute names as browsers read them.
+        "<p =\" onmouseover=alert(1) \">x</p>",
+        "<img/onerror=alert(1)>",
+    ];
+
+    for policy in [
+        SanitizePolicy::Strict,
+        SanitizePolicy::Standard,
+        SanitizePolicy::Permissive,
+    ] {
+        for payload in payloads {
+            let (html, report) = policy.sanitize("contents/a.html", payload);
+            assert!(!report.is_clean(), "{:?}: {}", policy, payload);
+            let lower = html.to_ascii_lowercase();
+            assert!(
+                !lower.contains("javascript")
+                    && !lower.contains("onerror")
+                    && !lower.contains("onmouseover"),
+                "{:?}: {} became {}",
+                policy,
+                payload,
+                html
+            );
+        }
+    }
+
+    // The text of a title is kept as it is.
+    let (html, report) =
+        SanitizePolicy::Strict.sanitize("contents/a.html", "<title>a <b> &amp; c</title>");
+    assert_eq!(html, "<title>a <b> &amp; c</title>");
+    assert!(report.is_clean());
+}
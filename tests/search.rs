@@ -0,0 +1,150 @@
+use fobzip::{search::SearchIndex, FobZ};
+
+fn sample() -> FobZ {
+    let mut fobz = FobZ::new(
+        "Sample".into(),
+        "John Doe".into(),
+        "A description.".into(),
+        vec![],
+    );
+
+    fobz.add_content(
+        "contents/one.html".into(),
+        "The Harbour".into(),
+        "<h1>The Harbour</h1>\n\
+         <p>The old lighthouse keeper watched the ships. Every night the lighthouse \
+         turned its lamp towards the sea, and the keeper wrote in his logbook.</p>\n"
+            .into(),
+    )
+    .unwrap();
+    fobz.add_content(
+        "contents/two.html".into(),
+        "The Storm".into(),
+        "<h1>The Storm</h1>\n<p>A storm broke over the harbour; the <em>keeper</em> lit the \
+         lamp.</p>\n"
+            .into(),
+    )
+    .unwrap();
+    fobz.add_content(
+        "contents/three.html".into(),
+        "Café".into(),
+        "<p>Coffee at the café, far from any lighthouse.</p>\n".into(),
+    )
+    .unwrap();
+
+    fobz
+}
+
+#[test]
+fn hits_are_ranked_with_offsets_and_snippets() {
+    let fobz = sample();
+    let index = fobz.build_search_index().unwrap();
+    assert_eq!(index.len(), 3);
+
+    let hits = index.search("Lighthouse");
+    let paths: Vec<&str> = hits.iter().map(|v| v.path.as_str()).collect();
+    assert_eq!(paths, ["contents/one.html", "contents/three.html"]);
+    assert_eq!(hits[0].title, "The Harbour");
+    assert!(hits[0].score > hits[1].score);
+    assert_eq!(hits[0].matches.len(), 2);
+
+    let text: Vec<char> = index
+        .get_text("contents/one.html")
+        .unwrap()
+        .chars()
+        .collect();
+    let (start, end) = hits[0].matches[0];
+    assert_eq!(text[start..end].iter().collect::<String>(), "lighthouse");
+    assert!(hits[0].snippet.contains("old lighthouse keeper"));
+    assert!(!hits[0].snippet.contains('\n'));
+
+    let hits = index.search("café");
+    assert_eq!(hits.len(), 1);
+    assert_eq!(
+        hits[0].snippet,
+        "Coffee at the café, far from any lighthouse."
+    );
+
+    assert!(index.search("keeper storm")[0].path == "contents/two.html");
+    assert!(index.search("keeper coffee").is_empty());
+    assert!(index.search("  ").is_empty());
+}
+
+#[test]
+fn phrases_and_prefixes_match() {
+    let index = sample().build_search_index().unwrap();
+
+    let hits = index.search("\"lighthouse keeper\"");
+    assert_eq!(hits.len(), 1);
+    let text: Vec<char> = index.get_text(&hits[0].path).unwrap().chars().collect();
+    let (start, end) = hits[0].matches[0];
+    assert_eq!(
+        text[start..end].iter().collect::<String>(),
+        "lighthouse keeper"
+    );
+    assert!(index.search("\"keeper lighthouse\"").is_empty());
+
+    let hits = index.search("harb*");
+    assert_eq!(hits.len(), 2);
+    assert_eq!(index.search("log*").len(), 1);
+    assert!(index.search("log").is_empty());
+    assert_eq!(index.search("\"the light*\"").len(), 1);
+}
+
+#[test]
+fn stored_index_is_saved_and_invalidated() {
+    let mut fobz = sample();
+    assert!(fobz.get_search_index().is_none());
+    fobz.store_search_index().unwrap();
+
+    let bytes = fobz.to_bytes().unwrap();
+    let reopened = FobZ::from_bytes(&bytes).unwrap();
+    let index = reopened.get_search_index().unwrap();
+    assert_eq!(index, fobz.get_search_index().unwrap());
+    assert_eq!(SearchIndex::from_bytes(&index.to_bytes()).unwrap(), *index);
+    let report = FobZ::validate_reader(std::io::Cursor::new(&bytes)).unwrap();
+    assert!(report.issues().is_empty());
+    assert_eq!(reopened.search("storm").unwrap().len(), 1);
+
+    assert!(SearchIndex::from_bytes(&index.to_bytes()[..20]).is_err());
+    assert!(SearchIndex::from_bytes(b"not an index").is_err());
+
+    fobz.remove_content("contents/two.html".into());
+    assert!(fobz.get_search_index().is_none());
+    assert!(fobz.search("storm").unwrap().is_empty());
+}
+
+#[test]
+fn stored_index_is_invalidated_by_section_changes() {
+    let mut fobz = sample();
+    let edits: [fn(&mut FobZ); 4] = [
+        |fobz| fobz.move_content("contents/three.html", 0).unwrap(),
+        |fobz| {
+            fobz.swap_contents("contents/one.html", "contents/two.html")
+                .unwrap()
+        },
+        |fobz| {
+            fobz.reorder_contents(&[
+                "contents/two.html".into(),
+                "contents/three.html".into(),
+                "contents/one.html".into(),
+            ])
+            .unwrap()
+        },
+        |fobz| {
+            fobz.add_anchor(None, "contents/one.html".into(), "end".into(), "End".into())
+                .unwrap()
+        },
+    ];
+
+    for edit in edits {
+        fobz.store_search_index().unwrap();
+        edit(&mut fobz);
+        assert!(fobz.get_search_index().is_none());
+    }
+
+    // Failed edits leave the stored index alone.
+    fobz.store_search_index().unwrap();
+    assert!(fobz.move_content("contents/missing.html", 0).is_err());
+    assert!(fobz.get_search_index().is_some());
+}
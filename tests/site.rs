@@ -0,0 +1,141 @@
+use std::fs;
+
+use fobzip::FobZ;
+
+const PNG: &[u8] = b"\x89PNG\r\n\x1A\n\0\0\0\0";
+
+fn sample() -> FobZ {
+    let mut fobz = FobZ::new(
+        "Sample".into(),
+        "John Doe".into(),
+        "A description.".into(),
+        vec![],
+    );
+    fobz.get_manifest_mut()
+        .set_cover("resources/cover.png".into());
+
+    fobz.add_content(
+        "contents/one.html".into(),
+        "One".into(),
+        "<p>First</p>".into(),
+    )
+    .unwrap();
+    fobz.add_child_content(
+        "contents/one.html",
+        "contents/part/two.html".into(),
+        "Two".into(),
+        "<!DOCTYPE html>\n<html>\n<head><title>Two</title></head>\n<body class=\"x\"><p id=\"end\">Second</p></body>\n</html>\n".into(),
+    )
+    .unwrap();
+    fobz.add_anchor(
+        Some("contents/part/two.html"),
+        "contents/part/two.html".into(),
+        "end".into(),
+        "The End".into(),
+    )
+    .unwrap();
+    fobz.add_content(
+        "contents/three.html".into(),
+        "Three".into(),
+        "<p>Third</p>".into(),
+    )
+    .unwrap();
+    fobz.add_resource("resources/cover.png".into(), "Cover".into(), PNG.to_vec())
+        .unwrap();
+    fobz.add_style("styles/a.css".into(), "p { margin: 0; }".into())
+        .unwrap();
+    fobz.add_style("styles/b.css".into(), "p { padding: 0; }".into())
+        .unwrap();
+
+    fobz
+}
+
+#[test]
+fn site_is_browsable() {
+    let dir = std::env::temp_dir().join(format!("fobzip-site-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    sample().export_site(dir.to_str().unwrap()).unwrap();
+
+    let read = |path: &str| fs::read_to_string(dir.join(path)).unwrap();
+
+    // The index shows the cover and the nested table of contents.
+    let index = read("index.html");
+    assert!(index.contains("<img class=\"fobz-cover\" src=\"resources/cover.png\""));
+    assert!(index.contains("<h1>Sample</h1>"));
+    assert!(index.contains("href=\"contents/one.html\">One</a>\n<ol>"));
+    assert!(index.contains("href=\"contents/part/two.html#end\">The End</a>"));
+    assert!(index.contains("href=\"contents/one.html\">Start reading</a>"));
+    assert!(index.contains("<link rel=\"stylesheet\" href=\"styles/a.css\">"));
+    assert_eq!(fs::read(dir.join("resources/cover.png")).unwrap(), PNG);
+    assert!(dir.join("styles/b.css").is_file());
+
+    // Fragments are wrapped in a page linking every stylesheet.
+    let one = read("contents/one.html");
+    assert!(one.contains("<title>One</title>"));
+    assert!(one.contains("href=\"../styles/a.css\""));
+    assert!(one.contains("href=\"../styles/b.css\""));
+    assert!(!one.contains("fobz-previous"));
+    assert!(one.contains("<a class=\"fobz-next\" href=\"part/two.html\">Two</a>"));
+    assert!(one.contains("<a class=\"fobz-index\" href=\"../index.html\">"));
+
+    // Complete documents keep their structure, with the additions inserted.
+    let two = read("contents/part/two.html");
+    assert!(two.starts_with("<!DOCTYPE html>\n<html>\n<head><title>Two</title>"));
+    assert!(two.contains("href=\"../../styles/a.css\">\n"));
+    assert!(two.contains("href=\"../../styles/b.css\">\n</head>"));
+    assert!(two.contains("<body class=\"x\">\n<nav class=\"fobz-nav\">"));
+    assert!(two.contains("<a class=\"fobz-previous\" href=\"../one.html\">One</a>"));
+    assert!(two.contains("<a class=\"fobz-next\" href=\"../three.html\">Three</a>"));
+    assert_eq!(two.matches("<nav class=\"fobz-nav\">").count(), 2);
+
+    let three = read("contents/three.html");
+    assert!(three.contains("href=\"part/two.html\">Two</a>"));
+    assert!(!three.contains("fobz-next"));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn links_to_unusual_file_names_are_percent_encoded() {
+    let dir = std::env::temp_dir().join(format!("fobzip-site-encoded-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+
+    let mut fobz = FobZ::new("Encoded".into(), "".into(), "".into(), vec![]);
+    fobz.get_manifest_mut()
+        .set_cover("resources/my cover.png".into());
+    fobz.add_content(
+        "contents/part 1.html".into(),
+        "One".into(),
+        "<p>First</p>".into(),
+    )
+    .unwrap();
+    fobz.add_content(
+        "contents/100%.html".into(),
+        "Two".into(),
+        "<p>Second</p>".into(),
+    )
+    .unwrap();
+    fobz.add_resource(
+        "resources/my cover.png".into(),
+        "Cover".into(),
+        PNG.to_vec(),
+    )
+    .unwrap();
+    fobz.add_style("styles/main style.css".into(), "p { margin: 0; }".into())
+        .unwrap();
+    fobz.export_site(dir.to_str().unwrap()).unwrap();
+
+    let read = |path: &str| fs::read_to_string(dir.join(path)).unwrap();
+
+    let index = read("index.html");
+    assert!(index.contains("href=\"styles/main%20style.css\""));
+    assert!(index.contains("src=\"resources/my%20cover.png\""));
+    assert!(index.contains("href=\"contents/part%201.html\""));
+    assert!(index.contains("href=\"contents/100%25.html\""));
+
+    let one = read("contents/part 1.html");
+    assert!(one.contains("href=\"../styles/main%20style.css\""));
+    assert!(one.contains("href=\"100%25.html\""));
+
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,110 @@
+use std::fs;
+
+use fobzip::{options::TextFormat, FobZ};
+
+fn sample() -> FobZ {
+    let mut fobz = FobZ::new(
+        "Sample".into(),
+        "John Doe".into(),
+        "A description.".into(),
+        vec![],
+    );
+
+    fobz.add_content(
+        "contents/one.html".into(),
+        "One".into(),
+        "<!DOCTYPE html>\n<html><head><title>One</title><style>p { margin: 0; }</style></head>\n\
+         <body>\n<h1>Chapter <em>one</em></h1>\n\
+         <p>Some <strong>bold</strong> text &amp; a <a href=\"https://example.com\">link</a>, \
+         the <a href=\"part/two.html#end\">next part</a> and <code>a*b</code>.</p>\n\
+         <p><img src=\"../resources/picture.png\" alt=\"ignored\"> <img src=\"other.png\" alt=\"Other\"></p>\n\
+         <ul><li>First</li><li>Second<ol start=\"3\"><li>Nested</li></ol></li></ul>\n\
+         <blockquote><p>Quoted</p><p>Again</p></blockquote>\n\
+         <table><tr><th>A</th><th>B</th></tr><tr><td>1</td><td>2</td></tr></table>\n\
+         </body></html>\n"
+            .into(),
+    )
+    .unwrap();
+    fobz.add_child_content(
+        "contents/one.html",
+        "contents/part/two.html".into(),
+        "Two".into(),
+        "<h2 id=\"end\">Two</h2>\n<p>Back to <a href=\"../one.html\">one</a>.</p>\n".into(),
+    )
+    .unwrap();
+    fobz.add_resource(
+        "resources/picture.png".into(),
+        "A picture".into(),
+        b"\x89PNG\r\n\x1A\n\0\0\0\0".to_vec(),
+    )
+    .unwrap();
+
+    fobz
+}
+
+#[test]
+fn markdown_keeps_the_structure() {
+    let text = sample().to_text(TextFormat::Markdown).unwrap();
+
+    assert_eq!(
+        text,
+        "# Chapter *one*\n\
+         \n\
+         Some **bold** text & a [link](https://example.com), the next part and `a*b`.\n\
+         \n\
+         ![A picture](resources/picture.png) ![Other](contents/other.png)\n\
+         \n\
+         - First\n\
+         - Second\n\
+         \x20 3. Nested\n\
+         \n\
+         > Quoted\n\
+         >\n\
+         > Again\n\
+         \n\
+         | A | B |\n\
+         | --- | --- |\n\
+         | 1 | 2 |\n\
+         \n\
+         ## Two\n\
+         \n\
+         Back to one.\n"
+    );
+}
+
+#[test]
+fn plain_text_drops_the_markup() {
+    let text = sample().to_text(TextFormat::Plain).unwrap();
+
+    assert!(text.starts_with("Chapter one\n\nSome bold text & a link (https://example.com), "));
+    assert!(text.contains("[A picture] [Other]"));
+    assert!(text.contains("\n    Quoted\n\n    Again\n"));
+    assert!(text.contains("\nA | B\n1 | 2\n"));
+    assert!(!text.contains("margin"));
+}
+
+#[test]
+fn sections_link_to_each_other() {
+    let sections = sample().to_text_sections(TextFormat::Markdown).unwrap();
+
+    let paths: Vec<&str> = sections.iter().map(|(v, _)| v.as_str()).collect();
+    assert_eq!(paths, ["contents/one.md", "contents/part/two.md"]);
+    assert!(sections[0]
+        .1
+        .contains("the [next part](part/two.md#end) and"));
+    assert!(sections[0]
+        .1
+        .contains("![A picture](../resources/picture.png)"));
+    assert!(sections[1].1.contains("Back to [one](../one.md)."));
+
+    let dir = std::env::temp_dir().join(format!("fobzip-text-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    sample()
+        .export_text_sections(dir.to_str().unwrap(), TextFormat::Plain)
+        .unwrap();
+    assert_eq!(
+        fs::read_to_string(dir.join("contents/part/two.txt")).unwrap(),
+        "Two\n\nBack to one.\n"
+    );
+    fs::remove_dir_all(dir).unwrap();
+}
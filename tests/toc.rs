@@ -0,0 +1,162 @@
+use fobzip::{
+    error::FobzError,
+    toc::{ContentInfo, TableOfContents},
+};
+
+fn sample() -> TableOfContents {
+    let mut toc = TableOfContents::new();
+    for name in ["a", "b", "c"] {
+        toc.add(ContentInfo::new(
+            format!("contents/{}.html", name),
+            name.to_uppercase(),
+        ));
+    }
+    for name in ["b1", "b2"] {
+        toc.add_child(
+            "contents/b.html",
+            ContentInfo::new(format!("contents/{}.html", name), name.to_uppercase()),
+        )
+        .unwrap();
+    }
+    toc
+}
+
+fn order(toc: &TableOfContents) -> Vec<(usize, &str)> {
+    toc.depth_first()
+        .map(|(depth, info)| (depth, info.title.as_str()))
+        .collect()
+}
+
+fn paths(names: &[&str]) -> Vec<String> {
+    names
+        .iter()
+        .map(|v| format!("contents/{}.html", v))
+        .collect()
+}
+
+#[test]
+fn sections_are_inserted_at_a_position() {
+    let mut toc = sample();
+
+    toc.insert_at(0, ContentInfo::new("contents/x.html".into(), "X".into()))
+        .unwrap();
+    toc.insert_at(4, ContentInfo::new("contents/y.html".into(), "Y".into()))
+        .unwrap();
+    assert_eq!(
+        order(&toc),
+        [
+            (0, "X"),
+            (0, "A"),
+            (0, "B"),
+            (1, "B1"),
+            (1, "B2"),
+            (0, "C"),
+            (0, "Y")
+        ]
+    );
+
+    assert!(matches!(
+        toc.insert_at(6, ContentInfo::new("contents/z.html".into(), "Z".into())),
+        Err(FobzError::IndexOutOfBounds { index: 6, len: 5 })
+    ));
+    assert!(matches!(
+        toc.insert_at(0, ContentInfo::new("contents/b1.html".into(), "B1".into())),
+        Err(FobzError::DuplicatePath(v)) if v == "contents/b1.html"
+    ));
+    assert_eq!(toc.len(), 5);
+}
+
+#[test]
+fn sections_move_among_their_siblings() {
+    let mut toc = sample();
+
+    toc.move_to("contents/a.html", 2).unwrap();
+    assert_eq!(
+        order(&toc),
+        [(0, "B"), (1, "B1"), (1, "B2"), (0, "C"), (0, "A")]
+    );
+    toc.move_to("contents/b.html", 1).unwrap();
+    toc.move_to("contents/b2.html", 0).unwrap();
+    assert_eq!(
+        order(&toc),
+        [(0, "C"), (0, "B"), (1, "B2"), (1, "B1"), (0, "A")]
+    );
+
+    assert!(matches!(
+        toc.move_to("contents/b1.html", 2),
+        Err(FobzError::IndexOutOfBounds { index: 2, len: 2 })
+    ));
+    assert!(matches!(
+        toc.move_to("contents/missing.html", 0),
+        Err(FobzError::UnknownPath(v)) if v == "contents/missing.html"
+    ));
+}
+
+#[test]
+fn sections_are_swapped_with_their_children() {
+    let mut toc = sample();
+
+    toc.swap("contents/a.html", "contents/b.html").unwrap();
+    assert_eq!(
+        order(&toc),
+        [(0, "B"), (1, "B1"), (1, "B2"), (0, "A"), (0, "C")]
+    );
+    toc.swap("contents/b2.html", "contents/c.html").unwrap();
+    assert_eq!(
+        order(&toc),
+        [(0, "B"), (1, "B1"), (1, "C"), (0, "A"), (0, "B2")]
+    );
+
+    assert!(matches!(
+        toc.swap("contents/b.html", "contents/b1.html"),
+        Err(FobzError::InvalidPath(v)) if v == "contents/b1.html"
+    ));
+    assert!(matches!(
+        toc.swap("contents/a.html", "contents/missing.html"),
+        Err(FobzError::UnknownPath(v)) if v == "contents/missing.html"
+    ));
+}
+
+#[test]
+fn sibling_sections_are_reordered() {
+    let mut toc = sample();
+
+    toc.reorder(&paths(&["c", "b", "a"])).unwrap();
+    toc.reorder(&paths(&["b2", "b1"])).unwrap();
+    assert_eq!(
+        order(&toc),
+        [(0, "C"), (0, "B"), (1, "B2"), (1, "B1"), (0, "A")]
+    );
+}
+
+#[test]
+fn invalid_orders_leave_the_table_unchanged() {
+    let mut toc = sample();
+    let before = order(&toc)
+        .into_iter()
+        .map(|(depth, title)| (depth, title.to_string()))
+        .collect::<Vec<_>>();
+
+    assert!(matches!(
+        toc.reorder(&paths(&["a", "b1", "c"])),
+        Err(FobzError::UnknownPath(v)) if v == "contents/b1.html"
+    ));
+    assert!(matches!(
+        toc.reorder(&paths(&["a", "missing", "c"])),
+        Err(FobzError::UnknownPath(v)) if v == "contents/missing.html"
+    ));
+    assert!(matches!(
+        toc.reorder(&paths(&["a", "b", "a"])),
+        Err(FobzError::DuplicatePath(v)) if v == "contents/a.html"
+    ));
+    assert!(matches!(
+        toc.reorder(&paths(&["c", "a"])),
+        Err(FobzError::MissingEntry(v)) if v == "contents/b.html"
+    ));
+
+    let after: Vec<_> = order(&toc)
+        .into_iter()
+        .map(|(depth, title)| (depth, title.to_string()))
+        .collect();
+    assert_eq!(after, before);
+}
@@ -0,0 +1,192 @@
+use std::{fs, path::PathBuf};
+
+use fobzip::{error::FobzError, options::PackOptions, FobZ};
+
+const PNG: &[u8] = b"\x89PNG\r\n\x1A\n\0\0\0\0";
+
+/// Returns a fresh directory of the system's temporary directory.
+fn directory(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("fobzip-unpack-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+fn sample() -> FobZ {
+    let mut fobz = FobZ::new(
+        "Sample".into(),
+        "John Doe".into(),
+        "A description.".into(),
+        vec!["sample".into()],
+    );
+    fobz.add_content(
+        "contents/one.html".into(),
+        "One".into(),
+        "<p>First</p>".into(),
+    )
+    .unwrap();
+    fobz.add_child_content(
+        "contents/one.html",
+        "contents/part/two.html".into(),
+        "Two".into(),
+        "<p>Second</p>".into(),
+    )
+    .unwrap();
+    fobz.add_resource("resources/cover.png".into(), "Cover".into(), PNG.to_vec())
+        .unwrap();
+    fobz.add_style("styles/main.css".into(), "p { margin: 0; }".into())
+        .unwrap();
+    fobz
+}
+
+#[test]
+fn round_trip_is_lossless() {
+    let dir = directory("round-trip");
+    let fobz = sample();
+    fobz.unpack(dir.to_str().unwrap()).unwrap();
+
+    assert!(dir.join("manifest.json").is_file());
+    assert_eq!(
+        fs::read_to_string(dir.join("contents/part/two.html")).unwrap(),
+        "<p>Second</p>"
+    );
+    assert_eq!(fs::read(dir.join("resources/cover.png")).unwrap(), PNG);
+
+    let packed = FobZ::pack(dir.to_str().unwrap()).unwrap();
+    assert_eq!(packed.to_bytes().unwrap(), fobz.to_bytes().unwrap());
+
+    // Documents read from older archives round-trip in the current layout.
+    let fixture = FobZ::open("tests/fixtures/v1.1.fobz").unwrap();
+    fixture.unpack(dir.to_str().unwrap()).unwrap();
+    let packed = FobZ::pack(dir.to_str().unwrap()).unwrap();
+    assert_eq!(packed.to_bytes().unwrap(), fixture.to_bytes().unwrap());
+
+    // Unpacking again removed the files of the previous document.
+    assert!(!dir.join("contents/part").exists());
+    assert!(!dir.join("default").exists());
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn only_files_of_an_earlier_unpack_are_removed() {
+    let dir = directory("kept");
+    sample().unpack(dir.to_str().unwrap()).unwrap();
+    fs::write(dir.join("contents/notes.html"), "<p>Notes</p>").unwrap();
+    fs::create_dir_all(dir.join("resources/drafts")).unwrap();
+    fs::write(dir.join("resources/drafts/sketch.png"), PNG).unwrap();
+
+    let mut fobz = sample();
+    fobz.remove_content("contents/part/two.html".into());
+    fobz.remove_resource("resources/cover.png".into());
+    fobz.unpack(dir.to_str().unwrap()).unwrap();
+
+    assert!(!dir.join("contents/part").exists());
+    assert!(!dir.join("resources/cover.png").exists());
+    assert!(dir.join("contents/notes.html").is_file());
+    assert!(dir.join("resources/drafts/sketch.png").is_file());
+
+    // A directory that was never unpacked to keeps everything it holds.
+    let foreign = directory("foreign");
+    fs::create_dir_all(foreign.join("styles")).unwrap();
+    fs::write(foreign.join("styles/site.css"), "").unwrap();
+    sample().unpack(foreign.to_str().unwrap()).unwrap();
+    assert!(foreign.join("styles/site.css").is_file());
+
+    fs::remove_dir_all(dir).unwrap();
+    fs::remove_dir_all(foreign).unwrap();
+}
+
+#[test]
+fn unlisted_files_are_rejected_unless_registered() {
+    let dir = directory("unlisted");
+    sample().unpack(dir.to_str().unwrap()).unwrap();
+    fs::write(
+        dir.join("contents/three.html"),
+        "<html><head><title>Three</title></head><body></body></html>",
+    )
+    .unwrap();
+    fs::write(dir.join("resources/extra.png"), PNG).unwrap();
+    fs::write(dir.join("styles/.hidden.css"), "").unwrap();
+
+    match FobZ::pack(dir.to_str().unwrap()) {
+        Err(FobzError::UnlistedEntry(path)) => assert_eq!(path, "contents/three.html"),
+        other => panic!("unexpected result: {:?}", other.map(|_| ())),
+    }
+
+    let options = PackOptions {
+        auto_register: true,
+    };
+    let fobz = FobZ::pack_with(dir.to_str().unwrap(), &options).unwrap();
+    assert_eq!(
+        fobz.get_content_info("contents/three.html").unwrap().title,
+        "Three"
+    );
+    let info = fobz.get_resource_info("resources/extra.png").unwrap();
+    assert_eq!(info.name, "extra");
+    assert_eq!(info.media_type, "image/png");
+    assert!(fobz.get_style_info("styles/.hidden.css").is_none());
+    assert!(fobz.validate().is_valid());
+
+    fs::write(dir.join("contents/notes.txt"), "").unwrap();
+    assert!(matches!(
+        FobZ::pack_with(dir.to_str().unwrap(), &options),
+        Err(FobzError::InvalidPath(_))
+    ));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn files_missing_from_the_directory_are_rejected() {
+    let dir = directory("missing");
+    let mut fobz = sample();
+    fobz.get_manifest_mut()
+        .set_cover("resources/cover.png".into());
+    // An index pointing at an anchor only needs the file it is in.
+    fobz.get_manifest_mut()
+        .set_index("contents/part/two.html#end".into());
+    fobz.unpack(dir.to_str().unwrap()).unwrap();
+    assert!(FobZ::pack(dir.to_str().unwrap()).is_ok());
+
+    for path in [
+        "contents/part/two.html",
+        "resources/cover.png",
+        "styles/main.css",
+    ] {
+        let bytes = fs::read(dir.join(path)).unwrap();
+        fs::remove_file(dir.join(path)).unwrap();
+        match FobZ::pack(dir.to_str().unwrap()) {
+            Err(FobzError::MissingEntry(entry)) => assert_eq!(entry, path),
+            other => panic!("unexpected result: {:?}", other.map(|_| ())),
+        }
+        fs::write(dir.join(path), bytes).unwrap();
+    }
+
+    // The built-in defaults are provided by the library itself.
+    fs::remove_dir_all(dir.join("default")).unwrap();
+    assert!(FobZ::pack(dir.to_str().unwrap()).is_ok());
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn stored_search_index_is_rebuilt_from_edited_contents() {
+    let dir = directory("search");
+    let mut fobz = sample();
+    fobz.store_search_index().unwrap();
+    fobz.unpack(dir.to_str().unwrap()).unwrap();
+    assert!(dir.join("search/index.bin").is_file());
+
+    fs::write(dir.join("contents/one.html"), "<p>Lighthouse</p>").unwrap();
+    let packed = FobZ::pack(dir.to_str().unwrap()).unwrap();
+    let index = packed.get_search_index().unwrap();
+    assert!(index.search("First").is_empty());
+    assert_eq!(index.search("Lighthouse")[0].path, "contents/one.html");
+
+    // Without a stored index, none is built.
+    fs::remove_dir_all(dir.join("search")).unwrap();
+    let packed = FobZ::pack(dir.to_str().unwrap()).unwrap();
+    assert!(packed.get_search_index().is_none());
+
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,162 @@
+use std::io::{Cursor, Write};
+
+use fobzip::{
+    validate::{IssueKind, Severity, ValidationReport},
+    FobZ,
+};
+use zip::{write::SimpleFileOptions, ZipWriter};
+
+const MANIFEST: &str = r#"{"version":"1.3","title":"","author":"","description":"","tags":[],"index":"default/no_section.html","cover":"default/no_cover.jpg"}"#;
+
+fn validate(manifest: &str, toc: &str, tor: &str, tos: &str, files: &[&str]) -> ValidationReport {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let tables = [
+        ("manifest.json", manifest),
+        ("toc.json", toc),
+        ("tor.json", tor),
+        ("tos.json", tos),
+    ];
+
+    for (name, json) in tables {
+        zip.start_file(name, SimpleFileOptions::default()).unwrap();
+        zip.write_all(json.as_bytes()).unwrap();
+    }
+    for name in files {
+        zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+        zip.write_all(b"<p></p>").unwrap();
+    }
+
+    let bytes = zip.finish().unwrap().into_inner();
+    FobZ::validate_reader(Cursor::new(bytes)).unwrap()
+}
+
+fn single_issue(report: &ValidationReport) -> (IssueKind, Severity, &str) {
+    assert_eq!(report.issues().len(), 1, "{:?}", report.issues());
+    let issue = &report.issues()[0];
+    (issue.kind, issue.severity, &issue.path)
+}
+
+const EMPTY_TOC: &str = r#"{"sections":[]}"#;
+const EMPTY_TOR: &str = r#"{"resources":[]}"#;
+const EMPTY_TOS: &str = r#"{"styles":[]}"#;
+
+#[test]
+fn consistent_archives_have_no_issues() {
+    let report = validate(
+        MANIFEST,
+        r#"{"sections":[{"path":"contents/a.html","title":"A"}]}"#,
+        r#"{"resources":[{"path":"resources/a.png","name":"A","media_type":"image/png"}]}"#,
+        r#"{"styles":[{"path":"styles/a.css"}]}"#,
+        &["contents/a.html", "resources/a.png", "styles/a.css"],
+    );
+
+    assert!(report.issues().is_empty());
+    assert!(report.is_valid());
+}
+
+#[test]
+fn dangling_entries_are_errors() {
+    let report = validate(
+        MANIFEST,
+        EMPTY_TOC,
+        EMPTY_TOR,
+        r#"{"styles":[{"path":"styles/missing.css"}]}"#,
+        &[],
+    );
+
+    assert_eq!(
+        single_issue(&report),
+        (
+            IssueKind::DanglingEntry,
+            Severity::Error,
+            "styles/missing.css"
+        )
+    );
+    assert!(!report.is_valid());
+}
+
+#[test]
+fn orphan_files_are_warnings() {
+    let report = validate(
+        MANIFEST,
+        EMPTY_TOC,
+        EMPTY_TOR,
+        EMPTY_TOS,
+        &["contents/orphan.html"],
+    );
+
+    assert_eq!(
+        single_issue(&report),
+        (
+            IssueKind::OrphanFile,
+            Severity::Warning,
+            "contents/orphan.html"
+        )
+    );
+    assert!(report.is_valid());
+}
+
+#[test]
+fn duplicate_paths_are_errors() {
+    let report = validate(
+        MANIFEST,
+        r#"{"sections":[{"path":"contents/a.html","title":"A"},{"path":"contents/a.html","title":"B"}]}"#,
+        EMPTY_TOR,
+        EMPTY_TOS,
+        &["contents/a.html"],
+    );
+
+    assert_eq!(
+        single_issue(&report),
+        (IssueKind::DuplicatePath, Severity::Error, "contents/a.html")
+    );
+}
+
+#[test]
+fn unresolved_index_is_an_error() {
+    let manifest = MANIFEST.replace("default/no_section.html", "contents/missing.html");
+    let report = validate(&manifest, EMPTY_TOC, EMPTY_TOR, EMPTY_TOS, &[]);
+
+    assert_eq!(
+        single_issue(&report),
+        (
+            IssueKind::UnresolvedIndex,
+            Severity::Error,
+            "contents/missing.html"
+        )
+    );
+}
+
+#[test]
+fn index_may_point_at_an_anchor() {
+    let manifest = MANIFEST.replace("default/no_section.html", "contents/a.html#intro");
+    let toc = r#"{"sections":[{"path":"contents/a.html","title":"A"}]}"#;
+    let report = validate(&manifest, toc, EMPTY_TOR, EMPTY_TOS, &["contents/a.html"]);
+    assert!(report.issues().is_empty(), "{:?}", report.issues());
+
+    let manifest = MANIFEST.replace("default/no_section.html", "contents/b.html#intro");
+    let report = validate(&manifest, toc, EMPTY_TOR, EMPTY_TOS, &["contents/a.html"]);
+    assert_eq!(
+        single_issue(&report),
+        (
+            IssueKind::UnresolvedIndex,
+            Severity::Error,
+            "contents/b.html#intro"
+        )
+    );
+}
+
+#[test]
+fn unresolved_cover_is_an_error() {
+    let manifest = MANIFEST.replace("default/no_cover.jpg", "resources/cover.png");
+    let report = validate(&manifest, EMPTY_TOC, EMPTY_TOR, EMPTY_TOS, &[]);
+
+    assert_eq!(
+        single_issue(&report),
+        (
+            IssueKind::UnresolvedCover,
+            Severity::Error,
+            "resources/cover.png"
+        )
+    );
+}